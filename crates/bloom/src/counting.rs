@@ -0,0 +1,231 @@
+/**
+ * @file counting.rs
+ * @author Krisna Pranav
+ * @brief counting
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use crate::{double_hash, fast_stable_hash_128, num_bits, num_hashes, split_u128};
+
+/// A bloom filter with a counter instead of a bit per position, so items can be removed
+/// again. Counters stick at their maximum once they overflow, since they no longer know how
+/// many items set them.
+#[derive(
+    Clone,
+    Debug,
+    bincode::Encode,
+    bincode::Decode,
+    serde::Serialize,
+    serde::Deserialize,
+    PartialEq,
+    Eq,
+)]
+pub struct CountingBloomFilter {
+    num_hashes: u64,
+    counters: Vec<u8>,
+} // pub struct CountingBloomFilter
+
+impl CountingBloomFilter {
+    pub fn new(estimated_items: u64, fp: f64) -> Self {
+        let num_counters = num_bits(estimated_items, fp);
+        Self {
+            num_hashes: num_hashes(num_counters, estimated_items),
+            counters: vec![0; num_counters as usize],
+        }
+    } // pub fn new(estimated_items: u64, fp: f64) -> Self
+
+    pub fn empty_from(other: &Self) -> Self {
+        Self {
+            num_hashes: other.num_hashes,
+            counters: vec![0; other.counters.len()],
+        }
+    } // pub fn empty_from(other: &Self) -> Self
+
+    fn positions(&self, item: &[u8]) -> impl Iterator<Item = usize> {
+        let num_counters = self.counters.len();
+        let hash = split_u128(fast_stable_hash_128(item));
+
+        (0..self.num_hashes).map(move |i| double_hash(hash, i, num_counters))
+    } // fn positions(&self, item: &[u8]) -> impl Iterator<Item = usize>
+
+    fn insert_raw(&mut self, item: &[u8]) {
+        for i in self.positions(item).collect::<Vec<_>>() {
+            self.counters[i] = self.counters[i].saturating_add(1);
+        }
+    } // fn insert_raw(&mut self, item: &[u8])
+
+    fn remove_raw(&mut self, item: &[u8]) -> bool {
+        if !self.contains_raw(item) {
+            return false;
+        }
+
+        for i in self.positions(item).collect::<Vec<_>>() {
+            if self.counters[i] < u8::MAX {
+                self.counters[i] -= 1;
+            }
+        }
+
+        true
+    } // fn remove_raw(&mut self, item: &[u8]) -> bool
+
+    fn contains_raw(&self, item: &[u8]) -> bool {
+        self.positions(item).all(|i| self.counters[i] > 0)
+    } // fn contains_raw(&self, item: &[u8]) -> bool
+
+    fn count_raw(&self, item: &[u8]) -> u8 {
+        self.positions(item)
+            .map(|i| self.counters[i])
+            .min()
+            .unwrap_or_default()
+    } // fn count_raw(&self, item: &[u8]) -> u8
+
+    pub fn insert(&mut self, item: u64) {
+        self.insert_raw(&item.to_le_bytes())
+    } // pub fn insert(&mut self, item: u64)
+
+    pub fn insert_u128(&mut self, item: u128) {
+        self.insert_raw(&item.to_le_bytes())
+    } // pub fn insert_u128(&mut self, item: u128)
+
+    /// Remove an item that was inserted before. Returns false, and leaves the filter as it
+    /// is, if the item is not in the filter. Removing an item that was never inserted but
+    /// is a false positive removes the items it collides with.
+    pub fn remove(&mut self, item: u64) -> bool {
+        self.remove_raw(&item.to_le_bytes())
+    } // pub fn remove(&mut self, item: u64) -> bool
+
+    pub fn remove_u128(&mut self, item: u128) -> bool {
+        self.remove_raw(&item.to_le_bytes())
+    } // pub fn remove_u128(&mut self, item: u128) -> bool
+
+    pub fn contains(&self, item: u64) -> bool {
+        self.contains_raw(&item.to_le_bytes())
+    } // pub fn contains(&self, item: u64) -> bool
+
+    pub fn contains_u128(&self, item: u128) -> bool {
+        self.contains_raw(&item.to_le_bytes())
+    } // pub fn contains_u128(&self, item: u128) -> bool
+
+    /// An upper bound of the number of times an item was inserted.
+    pub fn count(&self, item: u64) -> u8 {
+        self.count_raw(&item.to_le_bytes())
+    } // pub fn count(&self, item: u64) -> u8
+
+    pub fn expected_fp_rate(&self) -> f64 {
+        if self.counters.is_empty() {
+            return 1.0;
+        }
+
+        let fill =
+            self.counters.iter().filter(|c| **c > 0).count() as f64 / self.counters.len() as f64;
+
+        fill.powi(self.num_hashes as i32)
+    } // pub fn expected_fp_rate(&self) -> f64
+
+    /// Add the counters of a filter created with the same parameters. Panics if the
+    /// filters have a different number of counters or hashes.
+    pub fn union(&mut self, other: Self) {
+        assert_eq!(
+            self.counters.len(),
+            other.counters.len(),
+            "cannot union counting bloom filters with a different number of counters"
+        );
+        assert_eq!(
+            self.num_hashes, other.num_hashes,
+            "cannot union counting bloom filters with a different number of hashes"
+        );
+
+        for (counter, other) in self.counters.iter_mut().zip(other.counters) {
+            *counter = counter.saturating_add(other);
+        }
+    } // pub fn union(&mut self, other: Self)
+} // impl CountingBloomFilter
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn remove() {
+        let mut filter = CountingBloomFilter::new(100, 0.01);
+
+        filter.insert(1);
+        filter.insert(1);
+        filter.insert(2);
+        assert_eq!(filter.count(1), 2);
+
+        assert!(filter.remove(1));
+        assert!(filter.contains(1));
+        assert!(filter.remove(1));
+        assert!(!filter.contains(1));
+        assert!(!filter.remove(1));
+        assert!(filter.contains(2));
+    } // fn remove()
+
+    #[test]
+    fn saturates() {
+        let mut filter = CountingBloomFilter::new(100, 0.01);
+
+        for _ in 0..300 {
+            filter.insert(1);
+        }
+
+        for _ in 0..300 {
+            filter.remove(1);
+        }
+
+        assert!(filter.contains(1));
+    } // fn saturates()
+
+    #[test]
+    fn union_and_serialize() {
+        let mut a = CountingBloomFilter::new(100, 0.01);
+        let mut b = CountingBloomFilter::empty_from(&a);
+
+        a.insert(1);
+        b.insert(1);
+        b.insert_u128(u128::MAX);
+        a.union(b);
+
+        assert_eq!(a.count(1), 2);
+        assert!(a.contains_u128(u128::MAX));
+
+        let bytes = bincode::encode_to_vec(&a, bincode::config::standard()).unwrap();
+        let (decoded, _): (CountingBloomFilter, _) =
+            bincode::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+        assert_eq!(decoded, a);
+
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CountingBloomFilter>(&json).unwrap(),
+            a
+        );
+    } // fn union_and_serialize()
+
+    proptest! {
+        #[test]
+        fn remove_keeps_others(
+            kept in prop::collection::hash_set(any::<u64>(), 1..200),
+            removed in prop::collection::hash_set(any::<u64>(), 1..200),
+        ) {
+            let mut filter = CountingBloomFilter::new(400, 0.01);
+
+            for item in kept.iter().chain(&removed) {
+                filter.insert(*item);
+            }
+
+            for item in removed.difference(&kept) {
+                prop_assert!(filter.remove(*item));
+            }
+
+            for item in &kept {
+                prop_assert!(filter.contains(*item));
+            }
+        }
+    }
+} // mod tests
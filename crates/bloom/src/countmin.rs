@@ -0,0 +1,322 @@
+/**
+ * @file countmin.rs
+ * @author Krisna Pranav
+ * @brief countmin
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use crate::{double_hash, fast_stable_hash_128, split_u128};
+
+/// Estimates the frequencies of items in a stream. Estimates are never too low, and with
+/// probability `1 - delta` they are at most `epsilon` times the total count too high.
+#[derive(Clone, Debug, bincode::Encode, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(try_from = "CountMinSketchData")]
+pub struct CountMinSketch {
+    width: u64,
+    depth: u64,
+    total: u64,
+    counters: Vec<u64>,
+} // pub struct CountMinSketch
+
+/// A decoded [`CountMinSketch`] that has not been checked yet.
+#[derive(bincode::Decode, serde::Deserialize)]
+struct CountMinSketchData {
+    width: u64,
+    depth: u64,
+    total: u64,
+    counters: Vec<u64>,
+} // struct CountMinSketchData
+
+impl TryFrom<CountMinSketchData> for CountMinSketch {
+    type Error = String;
+
+    fn try_from(data: CountMinSketchData) -> Result<Self, Self::Error> {
+        let size = data.width.checked_mul(data.depth);
+
+        if data.width == 0 || size != Some(data.counters.len() as u64) {
+            return Err(format!(
+                "count-min sketch of {}x{} has {} counters",
+                data.width,
+                data.depth,
+                data.counters.len()
+            ));
+        }
+
+        Ok(Self {
+            width: data.width,
+            depth: data.depth,
+            total: data.total,
+            counters: data.counters,
+        })
+    } // fn try_from(data: CountMinSketchData) -> Result<Self, Self::Error>
+} // impl TryFrom<CountMinSketchData> for CountMinSketch
+
+impl bincode::Decode for CountMinSketch {
+    fn decode<D: bincode::de::Decoder>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        CountMinSketchData::decode(decoder)?
+            .try_into()
+            .map_err(bincode::error::DecodeError::OtherString)
+    } // fn decode<D: bincode::de::Decoder>(decoder: &mut D) -> Result<Self, DecodeError>
+} // impl bincode::Decode for CountMinSketch
+
+bincode::impl_borrow_decode!(CountMinSketch);
+
+impl CountMinSketch {
+    /// A sketch whose estimates are within `epsilon` times the total count with probability
+    /// `1 - delta`. Panics unless `epsilon` is positive and `delta` is between 0 and 1.
+    pub fn new(epsilon: f64, delta: f64) -> Self {
+        assert!(
+            epsilon > 0.0 && epsilon.is_finite(),
+            "epsilon must be positive, found {epsilon}"
+        );
+        assert!(
+            delta > 0.0 && delta < 1.0,
+            "delta must be between 0 and 1, found {delta}"
+        );
+
+        let width = (std::f64::consts::E / epsilon).ceil().max(1.0) as u64;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as u64;
+
+        Self::with_dimensions(width, depth)
+    } // pub fn new(epsilon: f64, delta: f64) -> Self
+
+    pub fn with_dimensions(width: u64, depth: u64) -> Self {
+        let width = width.max(1);
+        let depth = depth.max(1);
+        let size = width
+            .checked_mul(depth)
+            .and_then(|size| usize::try_from(size).ok())
+            .expect("count-min sketch is too large");
+
+        Self {
+            width,
+            depth,
+            total: 0,
+            counters: vec![0; size],
+        }
+    } // pub fn with_dimensions(width: u64, depth: u64) -> Self
+
+    pub fn empty_from(other: &Self) -> Self {
+        Self::with_dimensions(other.width, other.depth)
+    } // pub fn empty_from(other: &Self) -> Self
+
+    fn positions(&self, item: &[u8]) -> impl Iterator<Item = usize> {
+        let width = self.width as usize;
+        let hash = split_u128(fast_stable_hash_128(item));
+
+        (0..self.depth).map(move |row| row as usize * width + double_hash(hash, row, width))
+    } // fn positions(&self, item: &[u8]) -> impl Iterator<Item = usize>
+
+    pub fn add(&mut self, item: &[u8], count: u64) {
+        for i in self.positions(item).collect::<Vec<_>>() {
+            self.counters[i] = self.counters[i].saturating_add(count);
+        }
+
+        self.total = self.total.saturating_add(count);
+    } // pub fn add(&mut self, item: &[u8], count: u64)
+
+    pub fn insert(&mut self, item: &[u8]) {
+        self.add(item, 1)
+    } // pub fn insert(&mut self, item: &[u8])
+
+    pub fn estimate(&self, item: &[u8]) -> u64 {
+        self.positions(item)
+            .map(|i| self.counters[i])
+            .min()
+            .unwrap_or_default()
+    } // pub fn estimate(&self, item: &[u8]) -> u64
+
+    /// The sum of all counts added.
+    pub fn total(&self) -> u64 {
+        self.total
+    } // pub fn total(&self) -> u64
+
+    /// Add the counters of a sketch with the same dimensions. Panics if the dimensions
+    /// differ.
+    pub fn union(&mut self, other: Self) {
+        assert_eq!(
+            (self.width, self.depth),
+            (other.width, other.depth),
+            "cannot union count-min sketches with different dimensions"
+        );
+
+        for (counter, other) in self.counters.iter_mut().zip(other.counters) {
+            *counter = counter.saturating_add(other);
+        }
+
+        self.total = self.total.saturating_add(other.total);
+    } // pub fn union(&mut self, other: Self)
+} // impl CountMinSketch
+
+/// The most frequent items of a stream, counted by a [`CountMinSketch`].
+#[derive(
+    Clone,
+    Debug,
+    bincode::Encode,
+    bincode::Decode,
+    serde::Serialize,
+    serde::Deserialize,
+    PartialEq,
+    Eq,
+)]
+pub struct HeavyHitters {
+    k: usize,
+    sketch: CountMinSketch,
+    top: Vec<(String, u64)>,
+} // pub struct HeavyHitters
+
+impl HeavyHitters {
+    pub fn new(k: usize, sketch: CountMinSketch) -> Self {
+        Self {
+            k,
+            sketch,
+            top: Vec::new(),
+        }
+    } // pub fn new(k: usize, sketch: CountMinSketch) -> Self
+
+    pub fn add(&mut self, item: &str, count: u64) {
+        self.sketch.add(item.as_bytes(), count);
+        let estimate = self.sketch.estimate(item.as_bytes());
+
+        match self.top.iter_mut().find(|(top, _)| top == item) {
+            Some((_, count)) => *count = estimate,
+            None => self.top.push((item.to_string(), estimate)),
+        }
+
+        self.truncate();
+    } // pub fn add(&mut self, item: &str, count: u64)
+
+    pub fn insert(&mut self, item: &str) {
+        self.add(item, 1)
+    } // pub fn insert(&mut self, item: &str)
+
+    fn truncate(&mut self) {
+        self.top
+            .sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+        self.top.truncate(self.k);
+    } // fn truncate(&mut self)
+
+    /// The items with the highest estimated counts, most frequent first.
+    pub fn top(&self) -> &[(String, u64)] {
+        &self.top
+    } // pub fn top(&self) -> &[(String, u64)]
+
+    pub fn sketch(&self) -> &CountMinSketch {
+        &self.sketch
+    } // pub fn sketch(&self) -> &CountMinSketch
+
+    /// Merge the counts of another shard. The candidates of both are re-estimated with the
+    /// merged sketch. An item frequent across shards but in neither top is still missed.
+    pub fn union(&mut self, other: Self) {
+        self.sketch.union(other.sketch);
+
+        let mut candidates: Vec<_> = self
+            .top
+            .drain(..)
+            .chain(other.top)
+            .map(|(item, _)| item)
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+
+        self.top = candidates
+            .into_iter()
+            .map(|item| {
+                let estimate = self.sketch.estimate(item.as_bytes());
+                (item, estimate)
+            })
+            .collect();
+
+        self.truncate();
+    } // pub fn union(&mut self, other: Self)
+} // impl HeavyHitters
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn heavy_hitters() {
+        let mut a = HeavyHitters::new(2, CountMinSketch::new(0.001, 0.01));
+        let mut b = HeavyHitters::new(2, CountMinSketch::empty_from(a.sketch()));
+
+        for i in 0..1_000 {
+            a.insert(&format!("host{i}.com"));
+            b.insert(&format!("other{i}.com"));
+        }
+
+        a.add("example.com", 100);
+        a.add("rust-lang.org", 50);
+        b.add("rust-lang.org", 80);
+        b.add("docs.rs", 10);
+
+        a.union(b);
+
+        let top: Vec<_> = a.top().iter().map(|(item, _)| item.as_str()).collect();
+        assert_eq!(top, ["rust-lang.org", "example.com"]);
+        assert_eq!(a.sketch().total(), 2_240);
+
+        let bytes = bincode::encode_to_vec(&a, bincode::config::standard()).unwrap();
+        let (decoded, _): (HeavyHitters, _) =
+            bincode::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+        assert_eq!(decoded, a);
+    } // fn heavy_hitters()
+
+    #[test]
+    fn reject_invalid() {
+        let config = bincode::config::standard();
+
+        for (width, depth, counters) in [
+            (4u64, 2u64, vec![0u64; 4]),
+            (0, 2, vec![]),
+            (u64::MAX, 2, vec![]),
+        ] {
+            let bytes =
+                bincode::encode_to_vec((width, depth, 0u64, counters.clone()), config).unwrap();
+            assert!(bincode::decode_from_slice::<CountMinSketch, _>(&bytes, config).is_err());
+
+            let json = serde_json::json!({ "width": width, "depth": depth, "total": 0, "counters": counters });
+            assert!(serde_json::from_value::<CountMinSketch>(json).is_err());
+        }
+    } // fn reject_invalid()
+
+    #[test]
+    #[should_panic(expected = "epsilon must be positive")]
+    fn invalid_epsilon() {
+        CountMinSketch::new(0.0, 0.01);
+    } // fn invalid_epsilon()
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn estimate_bounds(items in prop::collection::vec(0..500u64, 1..5_000)) {
+            let epsilon = 0.01;
+            let mut sketch = CountMinSketch::new(epsilon, 0.001);
+            let mut counts = std::collections::HashMap::new();
+
+            for item in &items {
+                sketch.insert(&item.to_le_bytes());
+                *counts.entry(*item).or_insert(0u64) += 1;
+            }
+
+            let mut overestimate = 0;
+            for (item, count) in &counts {
+                let estimate = sketch.estimate(&item.to_le_bytes());
+                prop_assert!(estimate >= *count);
+                overestimate += estimate - count;
+            }
+
+            // each estimate is within the bound with high probability, so on average they are
+            let mean = overestimate as f64 / counts.len() as f64;
+            prop_assert!(mean <= epsilon * items.len() as f64);
+        }
+    }
+} // mod tests
@@ -0,0 +1,234 @@
+/**
+ * @file hyperloglog.rs
+ * @author Krisna Pranav
+ * @brief hyperloglog
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use crate::fast_stable_hash_64;
+
+pub const MIN_PRECISION: u8 = 4;
+pub const MAX_PRECISION: u8 = 18;
+
+/// Estimates the number of distinct items with `2^precision` registers of a byte each. The
+/// relative standard error is about `1.04 / sqrt(2^precision)`, so 0.8% at precision 14.
+#[derive(Clone, Debug, bincode::Encode, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(try_from = "HyperLogLogData")]
+pub struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+} // pub struct HyperLogLog
+
+/// A decoded [`HyperLogLog`] that has not been checked yet.
+#[derive(bincode::Decode, serde::Deserialize)]
+struct HyperLogLogData {
+    precision: u8,
+    registers: Vec<u8>,
+} // struct HyperLogLogData
+
+impl TryFrom<HyperLogLogData> for HyperLogLog {
+    type Error = String;
+
+    fn try_from(data: HyperLogLogData) -> Result<Self, Self::Error> {
+        if !(MIN_PRECISION..=MAX_PRECISION).contains(&data.precision) {
+            return Err(format!(
+                "hyperloglog precision {} is not between {MIN_PRECISION} and {MAX_PRECISION}",
+                data.precision
+            ));
+        }
+
+        if data.registers.len() != 1 << data.precision {
+            return Err(format!(
+                "hyperloglog with precision {} has {} registers",
+                data.precision,
+                data.registers.len()
+            ));
+        }
+
+        Ok(Self {
+            precision: data.precision,
+            registers: data.registers,
+        })
+    } // fn try_from(data: HyperLogLogData) -> Result<Self, Self::Error>
+} // impl TryFrom<HyperLogLogData> for HyperLogLog
+
+impl bincode::Decode for HyperLogLog {
+    fn decode<D: bincode::de::Decoder>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        HyperLogLogData::decode(decoder)?
+            .try_into()
+            .map_err(bincode::error::DecodeError::OtherString)
+    } // fn decode<D: bincode::de::Decoder>(decoder: &mut D) -> Result<Self, DecodeError>
+} // impl bincode::Decode for HyperLogLog
+
+bincode::impl_borrow_decode!(HyperLogLog);
+
+impl HyperLogLog {
+    pub fn new(precision: u8) -> Self {
+        assert!(
+            (MIN_PRECISION..=MAX_PRECISION).contains(&precision),
+            "precision must be between {MIN_PRECISION} and {MAX_PRECISION}"
+        );
+
+        Self {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    } // pub fn new(precision: u8) -> Self
+
+    pub fn empty_from(other: &Self) -> Self {
+        Self::new(other.precision)
+    } // pub fn empty_from(other: &Self) -> Self
+
+    pub fn precision(&self) -> u8 {
+        self.precision
+    } // pub fn precision(&self) -> u8
+
+    pub fn insert_raw(&mut self, item: &[u8]) {
+        let hash = fast_stable_hash_64(item);
+        let p = self.precision as u32;
+
+        let register = (hash >> (64 - p)) as usize;
+        // the guard bit bounds the rank when the remaining bits are all zero
+        let rank = ((hash << p) | (1 << (p - 1))).leading_zeros() as u8 + 1;
+
+        self.registers[register] = self.registers[register].max(rank);
+    } // pub fn insert_raw(&mut self, item: &[u8])
+
+    pub fn insert(&mut self, item: u64) {
+        self.insert_raw(&item.to_le_bytes())
+    } // pub fn insert(&mut self, item: u64)
+
+    pub fn insert_u128(&mut self, item: u128) {
+        self.insert_raw(&item.to_le_bytes())
+    } // pub fn insert_u128(&mut self, item: u128)
+
+    pub fn estimate_card(&self) -> u64 {
+        let m = self.registers.len() as f64;
+
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|rank| 2.0_f64.powi(-(*rank as i32)))
+            .sum();
+        let estimate = alpha * m * m / sum;
+
+        let zeros = self.registers.iter().filter(|rank| **rank == 0).count();
+
+        // linear counting is more accurate while many registers are still empty
+        if estimate <= 2.5 * m && zeros > 0 {
+            return (m * (m / zeros as f64).ln()).round() as u64;
+        }
+
+        estimate.round() as u64
+    } // pub fn estimate_card(&self) -> u64
+
+    /// Merge the registers of a sketch with the same precision. Panics if the precisions
+    /// differ.
+    pub fn union(&mut self, other: Self) {
+        assert_eq!(
+            self.precision, other.precision,
+            "cannot union hyperloglogs with different precisions"
+        );
+
+        for (rank, other) in self.registers.iter_mut().zip(other.registers) {
+            *rank = (*rank).max(other);
+        }
+    } // pub fn union(&mut self, other: Self)
+} // impl HyperLogLog
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new(14)
+    } // fn default() -> Self
+} // impl Default for HyperLogLog
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn estimate() {
+        for card in [0, 10, 1_000, 100_000] {
+            let mut hll = HyperLogLog::default();
+
+            for item in 0..card {
+                hll.insert(item);
+                hll.insert(item);
+            }
+
+            let estimate = hll.estimate_card() as f64;
+            assert!(
+                (estimate - card as f64).abs() <= card as f64 * 0.03,
+                "estimated {estimate} for {card}"
+            );
+        }
+    } // fn estimate()
+
+    #[test]
+    fn serialize() {
+        let mut hll = HyperLogLog::new(8);
+        hll.insert_raw(b"example.com");
+
+        let bytes = bincode::encode_to_vec(&hll, bincode::config::standard()).unwrap();
+        let (decoded, _): (HyperLogLog, _) =
+            bincode::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+        assert_eq!(decoded, hll);
+
+        let json = serde_json::to_string(&hll).unwrap();
+        assert_eq!(serde_json::from_str::<HyperLogLog>(&json).unwrap(), hll);
+    } // fn serialize()
+
+    #[test]
+    fn reject_invalid() {
+        let config = bincode::config::standard();
+
+        for (precision, registers) in [(8u8, vec![0u8; 16]), (2, vec![0; 4]), (30, vec![])] {
+            let bytes = bincode::encode_to_vec((precision, registers.clone()), config).unwrap();
+            assert!(bincode::decode_from_slice::<HyperLogLog, _>(&bytes, config).is_err());
+
+            let json = serde_json::json!({ "precision": precision, "registers": registers });
+            assert!(serde_json::from_value::<HyperLogLog>(json).is_err());
+        }
+    } // fn reject_invalid()
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn union_is_estimate_of_both(
+            a in prop::collection::hash_set(any::<u64>(), 0..5_000),
+            b in prop::collection::hash_set(any::<u64>(), 0..5_000),
+        ) {
+            let mut left = HyperLogLog::new(12);
+            let mut right = HyperLogLog::empty_from(&left);
+            let mut both = HyperLogLog::empty_from(&left);
+
+            for item in &a {
+                left.insert(*item);
+                both.insert(*item);
+            }
+
+            for item in &b {
+                right.insert(*item);
+                both.insert(*item);
+            }
+
+            left.union(right);
+            prop_assert_eq!(left, both);
+        }
+    }
+} // mod tests
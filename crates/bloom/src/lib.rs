@@ -10,6 +10,16 @@
  */
 use bitvec::vec::BitVec;
 
+mod counting;
+mod countmin;
+mod hyperloglog;
+mod scalable;
+
+pub use counting::CountingBloomFilter;
+pub use countmin::{CountMinSketch, HeavyHitters};
+pub use hyperloglog::HyperLogLog;
+pub use scalable::ScalableBloomFilter;
+
 pub fn combine_u64s(nums: [u64; 2]) -> u128 {
     ((nums[0] as u128) << 64) | (nums[1] as u128)
 } // pub fn combine_u64s(nums: [u64; 2]) -> u128
@@ -30,28 +40,44 @@ pub fn fast_stable_hash_128(t: &[u8]) -> u128 {
 
 const LARGE_PRIME: u64 = 11400714819323198549;
 
+/// The serialized format of [`U64BloomFilter`]. Version 0 filters set a single bit per item
+/// and were serialized without a version.
+const FORMAT_VERSION: u8 = 1;
+
 #[inline]
 fn num_bits(estimated_items: u64, fp: f64) -> u64 {
-    ((estimated_items as f64) * fp.ln() / (-8.0 * 2.0_f64.ln().powi(2))).ceil() as u64
+    ((estimated_items as f64) * fp.ln() / (-(2.0_f64.ln().powi(2))))
+        .ceil()
+        .max(1.0) as u64
 } // fn num_bits(estimated_items: u64, fp: f64) -> u64
 
 #[inline]
 fn num_hashes(num_bits: u64, estimated_items: u64) -> u64 {
-    (((num_bits as f64) / estimated_items as f64 * 2.0_f64.ln()).ceil() as u64).max(1)
+    (((num_bits as f64) / estimated_items.max(1) as f64 * 2.0_f64.ln()).ceil() as u64).max(1)
 } // fn num_hashes(num_bits: u64, estimated_items: u64) -> u64
 
-#[derive(
-    Clone,
-    bincode::Encode,
-    bincode::Decode,
-    Debug,
-    serde::Serialize,
-    serde::Deserialize,
-    PartialEq,
-    Eq,
-)]
+/// The bit of the `i`th hash of an item, derived from the two halves of its 128 bit hash.
+#[inline]
+fn double_hash([a, b]: [u64; 2], i: u64, num_bits: usize) -> usize {
+    (((a.wrapping_mul(i).wrapping_add(b)) % LARGE_PRIME) % num_bits as u64) as usize
+} // fn double_hash([a, b]: [u64; 2], i: u64, num_bits: usize) -> usize
+
+fn legacy_version() -> u8 {
+    0
+} // fn legacy_version() -> u8
+
+fn legacy_num_hashes() -> u64 {
+    1
+} // fn legacy_num_hashes() -> u64
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct U64BloomFilter {
-    #[bincode(with_serde)]
+    #[serde(default = "legacy_version")]
+    version: u8,
+
+    #[serde(default = "legacy_num_hashes")]
+    num_hashes: u64,
+
     bit_vec: BitVec,
 } // pub struct U64BloomFilter
 
@@ -59,43 +85,72 @@ impl U64BloomFilter {
     pub fn new(estimated_items: u64, fp: f64) -> Self {
         let num_bits = num_bits(estimated_items, fp);
         Self {
+            version: FORMAT_VERSION,
+            num_hashes: num_hashes(num_bits, estimated_items),
             bit_vec: BitVec::repeat(false, num_bits as usize),
         }
     } // pub fn new(estimated_items: u64, fp: f64) -> Self
 
     pub fn empty_from(other: &Self) -> Self {
         Self {
+            version: other.version,
+            num_hashes: other.num_hashes,
             bit_vec: BitVec::repeat(false, other.bit_vec.len()),
         }
     } // pub fn empty_from(other: &Self) -> Self
 
+    pub fn num_hashes(&self) -> u64 {
+        self.num_hashes
+    } // pub fn num_hashes(&self) -> u64
+
     pub fn fill(&mut self) {
         for i in 0..self.bit_vec.len() {
             self.bit_vec.set(i, true);
         }
     } // pub fn fill(&mut self)
 
-    fn hash(item: u64) -> usize {
+    fn legacy_hash(item: u64) -> usize {
         item.wrapping_mul(LARGE_PRIME) as usize
-    } // fn hash(item: u64) -> usize
+    } // fn legacy_hash(item: u64) -> usize
 
-    pub fn insert(&mut self, item: u64) {
-        let h = Self::hash(item);
+    /// The bits of an item. Filters in the legacy format keep their single bit per item,
+    /// so they can still be queried after loading.
+    fn bits(&self, item: &[u8], legacy: u64) -> impl Iterator<Item = usize> + '_ {
         let num_bits = self.bit_vec.len();
-        self.bit_vec.set(h % num_bits, true);
+        let legacy = (self.version == 0).then(|| Self::legacy_hash(legacy) % num_bits);
+        let hash = split_u128(fast_stable_hash_128(item));
+
+        let hashes = (0..self.num_hashes)
+            .filter(move |_| legacy.is_none())
+            .map(move |i| double_hash(hash, i, num_bits));
+
+        legacy.into_iter().chain(hashes)
+    } // fn bits(&self, item: &[u8], legacy: u64) -> impl Iterator<Item = usize> + '_
+
+    fn insert_bits(&mut self, item: &[u8], legacy: u64) {
+        let bits: Vec<_> = self.bits(item, legacy).collect();
+
+        for bit in bits {
+            self.bit_vec.set(bit, true);
+        }
+    } // fn insert_bits(&mut self, item: &[u8], legacy: u64)
+
+    pub fn insert(&mut self, item: u64) {
+        self.insert_bits(&item.to_le_bytes(), item)
     } // pub fn insert(&mut self, item: u64)
 
     pub fn insert_u128(&mut self, item: u128) {
-        self.insert(item as u64)
+        self.insert_bits(&item.to_le_bytes(), item as u64)
     } // pub fn insert_u128(&mut self, item: u128)
 
     pub fn contains(&self, item: u64) -> bool {
-        let h = Self::hash(item);
-        self.bit_vec[h % self.bit_vec.len()]
+        self.bits(&item.to_le_bytes(), item)
+            .all(|bit| self.bit_vec[bit])
     } // pub fn contains(&self, item: u64) -> bool
 
     pub fn contains_u128(&self, item: u128) -> bool {
-        self.contains(item as u64)
+        self.bits(&item.to_le_bytes(), item as u64)
+            .all(|bit| self.bit_vec[bit])
     } // pub fn contains_u128(&self, item: u128) -> bool
 
     pub fn estimate_card(&self) -> u64 {
@@ -109,19 +164,105 @@ impl U64BloomFilter {
             return u64::MAX;
         }
 
-        (-(self.bit_vec.len() as i64)
-            * (1.0 - (num_ones as f64) / (self.bit_vec.len() as f64)).ln() as i64)
-            .try_into()
-            .unwrap_or_default()
+        let num_bits = self.bit_vec.len() as f64;
+
+        (-(num_bits / self.num_hashes as f64) * (1.0 - (num_ones as f64) / num_bits).ln()).round()
+            as u64
     } // pub fn estimate_card(&self) -> u64
 
+    /// The probability that `contains` returns true for an item that was never inserted,
+    /// given the bits that are currently set.
+    pub fn expected_fp_rate(&self) -> f64 {
+        if self.bit_vec.is_empty() {
+            return 1.0;
+        }
+
+        let fill = self.bit_vec.count_ones() as f64 / self.bit_vec.len() as f64;
+
+        fill.powi(self.num_hashes as i32)
+    } // pub fn expected_fp_rate(&self) -> f64
+
     pub fn union(&mut self, other: Self) {
         debug_assert_eq!(self.bit_vec.len(), other.bit_vec.len());
+        debug_assert_eq!(self.num_hashes, other.num_hashes);
+        debug_assert_eq!(self.version, other.version);
 
         self.bit_vec |= other.bit_vec;
     } // pub fn union(&mut self, other: Self)
 } // impl U64BloomFilter
 
+/// Filters are encoded as a zero, the format version, the number of hashes and the bits.
+/// Legacy filters only encoded the bits, whose encoding starts with the non-empty name of
+/// their bit order, so a leading zero tells the formats apart.
+impl bincode::Encode for U64BloomFilter {
+    fn encode<E: bincode::enc::Encoder>(
+        &self,
+        encoder: &mut E,
+    ) -> Result<(), bincode::error::EncodeError> {
+        bincode::Encode::encode(&0u64, encoder)?;
+        bincode::Encode::encode(&self.version, encoder)?;
+        bincode::Encode::encode(&self.num_hashes, encoder)?;
+        bincode::Encode::encode(&bincode::serde::Compat(&self.bit_vec), encoder)
+    } // fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError>
+} // impl bincode::Encode for U64BloomFilter
+
+impl bincode::Decode for U64BloomFilter {
+    fn decode<D: bincode::de::Decoder>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        use bincode::de::read::Reader;
+
+        let marker = u64::decode(decoder)?;
+
+        if marker == 0 {
+            let version = u8::decode(decoder)?;
+
+            if version > FORMAT_VERSION {
+                return Err(bincode::error::DecodeError::OtherString(format!(
+                    "unsupported bloom filter version {version}"
+                )));
+            }
+
+            let num_hashes = u64::decode(decoder)?;
+            let bit_vec = bincode::serde::Compat::<BitVec>::decode(decoder)?.0;
+
+            return Ok(Self {
+                version,
+                num_hashes,
+                bit_vec,
+            });
+        }
+
+        // the rest of the legacy serde encoding of the bits: the name of the bit order,
+        // the width and index of the first bit, the number of bits and the words.
+        let name_len = marker as usize;
+        decoder.claim_bytes_read(name_len)?;
+        let mut name = vec![0; name_len];
+        decoder.reader().read(&mut name)?;
+
+        let _width = u8::decode(decoder)?;
+        let head = u8::decode(decoder)? as usize;
+        let bits = u64::decode(decoder)? as usize;
+        let words = Vec::<usize>::decode(decoder)?;
+
+        let words = BitVec::<usize>::from_vec(words);
+
+        if head + bits > words.len() {
+            return Err(bincode::error::DecodeError::Other(
+                "legacy bloom filter has fewer bits than it claims",
+            ));
+        }
+
+        Ok(Self {
+            version: legacy_version(),
+            num_hashes: legacy_num_hashes(),
+            bit_vec: words[head..head + bits].to_bitvec(),
+        })
+    } // fn decode<D: bincode::de::Decoder>(decoder: &mut D) -> Result<Self, DecodeError>
+} // impl bincode::Decode for U64BloomFilter
+
+bincode::impl_borrow_decode!(U64BloomFilter);
+
 #[derive(bincode::Encode, bincode::Decode)]
 pub struct BytesBloomFilter<T> {
     #[bincode(with_serde)]
@@ -146,11 +287,10 @@ impl<T> BytesBloomFilter<T> {
     } // fn hash_raw(item: &[u8]) -> [u64; 2]
 
     pub fn contains_raw(&self, item: &[u8]) -> bool {
-        let [a, b] = Self::hash_raw(item);
+        let hash = Self::hash_raw(item);
 
         for i in 0..self.num_hashes {
-            let h = ((a.wrapping_mul(i).wrapping_add(b)) % LARGE_PRIME) % self.bit_vec.len() as u64;
-            if !self.bit_vec[h as usize] {
+            if !self.bit_vec[double_hash(hash, i, self.bit_vec.len())] {
                 return false;
             }
         }
@@ -159,11 +299,11 @@ impl<T> BytesBloomFilter<T> {
     } // pub fn contains_raw(&self, item: &[u8]) -> bool
 
     pub fn insert_raw(&mut self, item: &[u8]) {
-        let [a, b] = Self::hash_raw(item);
+        let hash = Self::hash_raw(item);
+        let num_bits = self.bit_vec.len();
 
         for i in 0..self.num_hashes {
-            let h = ((a.wrapping_mul(i).wrapping_add(b)) % LARGE_PRIME) % self.bit_vec.len() as u64;
-            self.bit_vec.set(h as usize, true);
+            self.bit_vec.set(double_hash(hash, i, num_bits), true);
         }
     } // pub fn insert_raw(&mut self, item: &[u8])
 } // impl<T> BytesBloomFilter<T>
@@ -180,3 +320,140 @@ where
         self.contains_raw(item.as_ref())
     } // pub fn contains(&self, item: &T) -> bool
 } // impl<T> BytesBloomFilter<T> where T: AsRef<[u8]>
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// The layout of filters serialized before the format was versioned.
+    #[derive(bincode::Encode, serde::Serialize)]
+    struct Legacy {
+        #[bincode(with_serde)]
+        bit_vec: BitVec,
+    } // struct Legacy
+
+    fn empirical_fp_rate(filter: &U64BloomFilter, offset: u64) -> f64 {
+        let trials = 10_000;
+        let hits = (offset..offset + trials)
+            .filter(|item| filter.contains(*item))
+            .count();
+
+        hits as f64 / trials as f64
+    } // fn empirical_fp_rate(filter: &U64BloomFilter, offset: u64) -> f64
+
+    proptest! {
+        #[test]
+        fn no_false_negatives(items in prop::collection::vec(any::<u64>(), 1..500), wide in prop::collection::vec(any::<u128>(), 1..500)) {
+            let mut filter = U64BloomFilter::new(1_000, 0.01);
+
+            for item in &items {
+                filter.insert(*item);
+            }
+
+            for item in &wide {
+                filter.insert_u128(*item);
+            }
+
+            for item in &items {
+                prop_assert!(filter.contains(*item));
+            }
+
+            for item in &wide {
+                prop_assert!(filter.contains_u128(*item));
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        #[test]
+        fn fp_rate(fp in 0.001..0.1f64, estimated_items in 500..5_000u64, start in any::<u32>()) {
+            let mut filter = U64BloomFilter::new(estimated_items, fp);
+
+            for item in 0..estimated_items {
+                filter.insert(item + start as u64 * 2);
+            }
+
+            let offset = u64::MAX / 2;
+            let empirical = empirical_fp_rate(&filter, offset);
+            let expected = filter.expected_fp_rate();
+
+            prop_assert!(expected < fp * 1.5 + 0.002, "expected {expected} for fp {fp}");
+            prop_assert!(empirical < fp * 1.5 + 0.005, "empirical {empirical} for fp {fp}");
+            prop_assert!((empirical - expected).abs() < expected * 0.5 + 0.005);
+
+            let card = filter.estimate_card() as f64;
+            prop_assert!((card - estimated_items as f64).abs() < estimated_items as f64 * 0.1);
+        }
+    }
+
+    #[test]
+    fn bincode_roundtrip() {
+        let mut filter = U64BloomFilter::new(100, 0.01);
+        filter.insert(42);
+
+        let bytes = bincode::encode_to_vec(&filter, bincode::config::standard()).unwrap();
+        let (decoded, _): (U64BloomFilter, _) =
+            bincode::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+
+        assert_eq!(decoded, filter);
+        assert!(decoded.contains(42));
+    } // fn bincode_roundtrip()
+
+    #[test]
+    fn loads_legacy() {
+        let mut bit_vec = BitVec::repeat(false, 1_000);
+        for item in [1u64, 2, 3] {
+            bit_vec.set(U64BloomFilter::legacy_hash(item) % 1_000, true);
+        }
+        let legacy = Legacy { bit_vec };
+
+        let bytes = bincode::encode_to_vec(&legacy, bincode::config::standard()).unwrap();
+        let (filter, read): (U64BloomFilter, _) =
+            bincode::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+
+        assert_eq!(read, bytes.len());
+        assert_eq!(filter.bit_vec, legacy.bit_vec);
+        assert_eq!(filter.num_hashes(), 1);
+        assert!(filter.contains(1) && filter.contains(2) && filter.contains(3));
+
+        let json = serde_json::to_string(&legacy).unwrap();
+        let from_json: U64BloomFilter = serde_json::from_str(&json).unwrap();
+        assert_eq!(from_json, filter);
+
+        // legacy filters keep their format when written again
+        let bytes = bincode::encode_to_vec(&filter, bincode::config::standard()).unwrap();
+        let (again, _): (U64BloomFilter, _) =
+            bincode::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+        assert_eq!(again, filter);
+        assert!(again.contains(2));
+    } // fn loads_legacy()
+
+    #[test]
+    fn u128_uses_all_bits() {
+        let mut filter = U64BloomFilter::new(1_000, 0.01);
+        filter.insert_u128(7);
+
+        // the low 64 bits alone used to decide membership
+        let collisions = (1..1_000u128)
+            .filter(|high| filter.contains_u128((high << 64) | 7))
+            .count();
+
+        assert!(collisions < 10);
+    } // fn u128_uses_all_bits()
+
+    #[test]
+    fn union() {
+        let mut a = U64BloomFilter::new(100, 0.01);
+        let mut b = U64BloomFilter::empty_from(&a);
+
+        a.insert(1);
+        b.insert(2);
+        a.union(b);
+
+        assert!(a.contains(1) && a.contains(2));
+    } // fn union()
+} // mod tests
@@ -0,0 +1,255 @@
+/**
+ * @file scalable.rs
+ * @author Krisna Pranav
+ * @brief scalable
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use crate::U64BloomFilter;
+
+/// Each layer holds this many times the items of the layer before it.
+const GROWTH: u64 = 2;
+
+/// Each layer has this times the false positive rate of the layer before it, so the rate
+/// of the whole filter converges to the configured one however many layers are added.
+const TIGHTENING: f64 = 0.5;
+
+/// A bloom filter that adds a larger layer with a lower false positive rate whenever its
+/// newest layer is full, so it never needs to know the number of items up front.
+#[derive(
+    Clone, Debug, bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize, PartialEq,
+)]
+pub struct ScalableBloomFilter {
+    initial_capacity: u64,
+    fp: f64,
+    layers: Vec<U64BloomFilter>,
+    items: Vec<u64>,
+} // pub struct ScalableBloomFilter
+
+impl ScalableBloomFilter {
+    pub fn new(initial_capacity: u64, fp: f64) -> Self {
+        let mut filter = Self {
+            initial_capacity: initial_capacity.max(1),
+            fp,
+            layers: Vec::new(),
+            items: Vec::new(),
+        };
+
+        filter.add_layer();
+        filter
+    } // pub fn new(initial_capacity: u64, fp: f64) -> Self
+
+    fn capacity(&self, layer: usize) -> u64 {
+        self.initial_capacity
+            .saturating_mul(GROWTH.saturating_pow(layer as u32))
+    } // fn capacity(&self, layer: usize) -> u64
+
+    fn layer_fp(&self, layer: usize) -> f64 {
+        self.fp * (1.0 - TIGHTENING) * TIGHTENING.powi(layer as i32)
+    } // fn layer_fp(&self, layer: usize) -> f64
+
+    fn add_layer(&mut self) {
+        let layer = self.layers.len();
+
+        self.layers.push(U64BloomFilter::new(
+            self.capacity(layer),
+            self.layer_fp(layer),
+        ));
+        self.items.push(0);
+    } // fn add_layer(&mut self)
+
+    fn insert_with(
+        &mut self,
+        contains: impl Fn(&U64BloomFilter) -> bool,
+        insert: impl Fn(&mut U64BloomFilter),
+    ) {
+        if self.layers.iter().any(&contains) {
+            return;
+        }
+
+        let last = self.layers.len() - 1;
+
+        if self.items[last] >= self.capacity(last) {
+            self.add_layer();
+        }
+
+        let last = self.layers.len() - 1;
+        insert(&mut self.layers[last]);
+        self.items[last] += 1;
+    } // fn insert_with(&mut self, contains: impl Fn(&U64BloomFilter) -> bool, insert: impl Fn(&mut U64BloomFilter))
+
+    pub fn insert(&mut self, item: u64) {
+        self.insert_with(|layer| layer.contains(item), |layer| layer.insert(item))
+    } // pub fn insert(&mut self, item: u64)
+
+    pub fn insert_u128(&mut self, item: u128) {
+        self.insert_with(
+            |layer| layer.contains_u128(item),
+            |layer| layer.insert_u128(item),
+        )
+    } // pub fn insert_u128(&mut self, item: u128)
+
+    pub fn contains(&self, item: u64) -> bool {
+        self.layers.iter().any(|layer| layer.contains(item))
+    } // pub fn contains(&self, item: u64) -> bool
+
+    pub fn contains_u128(&self, item: u128) -> bool {
+        self.layers.iter().any(|layer| layer.contains_u128(item))
+    } // pub fn contains_u128(&self, item: u128) -> bool
+
+    pub fn num_layers(&self) -> usize {
+        self.layers.len()
+    } // pub fn num_layers(&self) -> usize
+
+    /// The number of distinct items inserted, not counting items that were false positives
+    /// when inserted. After a [`union`](Self::union) the items of shared layers are
+    /// estimated, and the length is at most the sum of the lengths of both filters.
+    pub fn len(&self) -> u64 {
+        self.items.iter().sum()
+    } // pub fn len(&self) -> u64
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    } // pub fn is_empty(&self) -> bool
+
+    pub fn expected_fp_rate(&self) -> f64 {
+        1.0 - self
+            .layers
+            .iter()
+            .map(|layer| 1.0 - layer.expected_fp_rate())
+            .product::<f64>()
+    } // pub fn expected_fp_rate(&self) -> f64
+
+    /// Merge the layers of a filter created with the same capacity and false positive rate.
+    /// Layers that only the other filter has are taken over as they are. Items both filters
+    /// contain are only counted once, as far as the merged bits can tell. Panics if the
+    /// filters were created with a different capacity or false positive rate.
+    pub fn union(&mut self, other: Self) {
+        assert_eq!(
+            self.initial_capacity, other.initial_capacity,
+            "cannot union scalable bloom filters with different initial capacities"
+        );
+        assert_eq!(
+            self.fp.to_bits(),
+            other.fp.to_bits(),
+            "cannot union scalable bloom filters with different false positive rates"
+        );
+
+        for (i, (layer, items)) in other.layers.into_iter().zip(other.items).enumerate() {
+            if i < self.layers.len() {
+                self.layers[i].union(layer);
+
+                // the sum counts items of both shards twice, the bits only once
+                let max = self.items[i].saturating_add(items);
+                let min = self.items[i].max(items);
+                self.items[i] = self.layers[i].estimate_card().clamp(min, max);
+            } else {
+                self.layers.push(layer);
+                self.items.push(items);
+            }
+        }
+    } // pub fn union(&mut self, other: Self)
+} // impl ScalableBloomFilter
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn grows() {
+        let mut filter = ScalableBloomFilter::new(100, 0.01);
+
+        for item in 0..10_000 {
+            filter.insert(item);
+        }
+
+        assert!(filter.num_layers() > 1);
+        assert!((0..10_000).all(|item| filter.contains(item)));
+        assert!(filter.len() > 9_900);
+
+        let hits = (1_000_000..1_010_000)
+            .filter(|item| filter.contains(*item))
+            .count();
+        assert!((hits as f64 / 10_000.0) < 0.02, "{hits} false positives");
+        assert!(filter.expected_fp_rate() < 0.02);
+    } // fn grows()
+
+    #[test]
+    fn union() {
+        let mut a = ScalableBloomFilter::new(10, 0.01);
+        let mut b = ScalableBloomFilter::new(10, 0.01);
+
+        a.insert(1);
+        for item in 100..200 {
+            b.insert(item);
+        }
+
+        a.union(b);
+
+        assert!(a.contains(1));
+        assert!((100..200).all(|item| a.contains(item)));
+
+        // overlapping shards count their shared items once, so no layer is added early
+        let mut a = ScalableBloomFilter::new(100, 0.01);
+        let mut b = ScalableBloomFilter::new(100, 0.01);
+
+        for item in 0..80 {
+            a.insert(item);
+            b.insert(item);
+        }
+
+        a.union(b);
+        assert!(a.len() < 100, "{} items", a.len());
+
+        for item in 80..90 {
+            a.insert(item);
+        }
+        assert_eq!(a.num_layers(), 1);
+    } // fn union()
+
+    #[test]
+    #[should_panic(expected = "different false positive rates")]
+    fn union_mismatched() {
+        let mut a = ScalableBloomFilter::new(10, 0.01);
+        a.union(ScalableBloomFilter::new(10, 0.02));
+    } // fn union_mismatched()
+
+    #[test]
+    fn serialize() {
+        let mut filter = ScalableBloomFilter::new(10, 0.01);
+        for item in 0..100 {
+            filter.insert_u128(item);
+        }
+
+        let bytes = bincode::encode_to_vec(&filter, bincode::config::standard()).unwrap();
+        let (decoded, _): (ScalableBloomFilter, _) =
+            bincode::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+        assert_eq!(decoded, filter);
+
+        let json = serde_json::to_string(&filter).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ScalableBloomFilter>(&json).unwrap(),
+            filter
+        );
+    } // fn serialize()
+
+    proptest! {
+        #[test]
+        fn no_false_negatives(items in prop::collection::vec(any::<u64>(), 1..2_000)) {
+            let mut filter = ScalableBloomFilter::new(16, 0.01);
+
+            for item in &items {
+                filter.insert(*item);
+            }
+
+            for item in &items {
+                prop_assert!(filter.contains(*item));
+            }
+        }
+    }
+} // mod tests
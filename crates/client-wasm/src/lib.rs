@@ -29,10 +29,62 @@ impl From<Error> for JsValue {
     }
 }
 
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = console, js_name = log)]
-    fn console_log(s: &str);
+/// A problem in an optic, with the byte range and the 1-based line and column it starts at.
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationError {
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub expected: Vec<String>,
+}
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Validation {
+    pub valid: bool,
+    pub errors: Vec<ValidationError>,
+}
+
+/// Parse the optic and report where it fails. Parsing stops at the first error.
+pub fn validate(source: &str) -> Validation {
+    let errors = match optics::Optic::parse(source) {
+        Ok(_) => Vec::new(),
+        Err(err) => {
+            let (start, end) = err.span().unwrap_or((source.len(), source.len()));
+            let (line, column) = line_column(source, start);
+
+            let message = if start < end {
+                format!("{err}: `{}`", &source[start..end])
+            } else {
+                err.to_string()
+            };
+
+            vec![ValidationError {
+                message,
+                start,
+                end,
+                line,
+                column,
+                expected: err.expected().to_vec(),
+            }]
+        }
+    };
+
+    Validation {
+        valid: errors.is_empty(),
+        errors,
+    }
+}
+
+/// The 1-based line and column of a byte offset, counting columns in characters.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    (line, before[line_start..].chars().count() + 1)
 }
 
 #[wasm_bindgen]
@@ -40,6 +92,7 @@ pub struct Optic;
 
 #[wasm_bindgen]
 impl Optic {
+    /// The host rankings of an optic as a JSON string.
     #[wasm_bindgen(js_name = parsePreferenceOptic)]
     pub fn parse_preference_optic(contents: JsValue) -> Result<JsValue, Error> {
         let optic_contents: String = serde_wasm_bindgen::from_value(contents)?;
@@ -47,8 +100,74 @@ impl Optic {
 
         let rankings_json = serde_json::to_string(&host_rankings)?;
 
-        console_log(&("Parsed rankings to JSON: ".to_owned() + &rankings_json));
-
         Ok(serde_wasm_bindgen::to_value(&rankings_json)?)
     }
+
+    /// The whole optic, with its rules, host rankings and discard flag.
+    pub fn parse(contents: &str) -> Result<JsValue, Error> {
+        let optic = optics::Optic::parse(contents)?;
+
+        Ok(serde_wasm_bindgen::to_value(&optic)?)
+    }
+
+    /// The source of an optic object as returned by `parse`.
+    #[wasm_bindgen(js_name = toSource)]
+    pub fn to_source(optic: JsValue) -> Result<String, Error> {
+        let optic: optics::Optic = serde_wasm_bindgen::from_value(optic)?;
+
+        Ok(optic.to_string())
+    }
+
+    /// The source of an optic with only the given host rankings.
+    #[wasm_bindgen(js_name = hostRankingsToSource)]
+    pub fn host_rankings_to_source(host_rankings: JsValue) -> Result<String, Error> {
+        let host_rankings: optics::HostRankings = serde_wasm_bindgen::from_value(host_rankings)?;
+
+        Ok(host_rankings.into_optic().to_string())
+    }
+
+    /// `{ valid, errors }`, where each error has a `message`, the `start` and `end` byte
+    /// offsets, the `line` and `column` and the `expected` tokens.
+    pub fn validate(contents: &str) -> Result<JsValue, Error> {
+        Ok(serde_wasm_bindgen::to_value(&validate(contents))?)
+    }
+
+    /// Merge two optic objects as returned by `parse`.
+    pub fn merge(a: JsValue, b: JsValue) -> Result<JsValue, Error> {
+        let a: optics::Optic = serde_wasm_bindgen::from_value(a)?;
+        let b: optics::Optic = serde_wasm_bindgen::from_value(b)?;
+
+        Ok(serde_wasm_bindgen::to_value(&a.merge(b))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation() {
+        assert_eq!(
+            validate("Like(Site(\"docs.rs\"));"),
+            Validation {
+                valid: true,
+                errors: Vec::new()
+            }
+        );
+
+        let source = "Like(Site(\"docs.rs\"));\n  Dislike(Title(\"ö\"));";
+        let validation = validate(source);
+        let error = &validation.errors[0];
+
+        assert!(!validation.valid);
+        assert_eq!((error.line, error.column), (2, 11));
+        assert_eq!(&source[error.start..error.end], "Title");
+        assert!(error.message.ends_with("`Title`"));
+        assert_eq!(error.expected, vec!["\"Site\"".to_string()]);
+
+        let source = "Rule { Matches { Title(\"ö\")";
+        let error = &validate(source).errors[0];
+        assert_eq!((error.start, error.end), (source.len(), source.len()));
+        assert_eq!((error.line, error.column), (1, 28));
+    }
 }
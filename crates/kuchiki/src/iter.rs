@@ -4,11 +4,10 @@ use std::borrow::Borrow;
 use std::cell::RefCell;
 use std::iter::Rev;
 
-use selectors::Element;
-
 use crate::node_data_ref::NodeDataRef;
 use crate::select::Selectors;
 use crate::tree::{ElementData, NodeRef};
+use crate::xpath::{XPath, XPathNode, XPathValue};
 use crate::Result;
 
 impl NodeRef {
@@ -172,10 +171,17 @@ impl NodeRef {
         self.inclusive_descendants().select(selectors)
     }
 
-    /// Return the node that matches the given xpath expression.
+    /// Return the elements selected by an XPath 1.0 expression, evaluated with this node
+    /// as the context node, in document order.
     #[inline]
-    pub fn select_xpath(&self, xpath: &str) -> XPathSelect<Elements<Descendants>> {
-        XPathSelect::new(self.inclusive_descendants().elements(), xpath)
+    pub fn select_xpath(&self, xpath: &str) -> Result<XPathSelect> {
+        Ok(XPathSelect::new(self.xpath(xpath)?))
+    }
+
+    /// Evaluate an XPath 1.0 expression with this node as the context node.
+    #[inline]
+    pub fn xpath(&self, expr: &str) -> Result<XPathValue> {
+        XPath::parse(expr)?.evaluate(self)
     }
 
     /// Return the first inclusive descendants element that match the given selector list.
@@ -187,65 +193,34 @@ impl NodeRef {
     }
 }
 
-/// An iterator of nodes that match a given xpath selector.
-pub struct XPathSelect<I> {
-    iter: I,
-    xpath: Vec<crate::xpath::Expr>,
+/// An iterator of the elements selected by an XPath expression.
+#[derive(Debug, Clone)]
+pub struct XPathSelect {
+    iter: std::vec::IntoIter<XPathNode>,
 }
 
-impl<I> XPathSelect<I> {
-    /// Create a new `XPathSelect` iterator.
-    pub fn new(iter: I, xpath: &str) -> Self {
-        let xpath = crate::xpath::Expr::parse(xpath);
-        Self { iter, xpath }
+impl XPathSelect {
+    /// Create a new `XPathSelect` iterator over the elements of a node-set.
+    /// Other values select nothing.
+    pub fn new(value: XPathValue) -> Self {
+        let nodes = match value {
+            XPathValue::Nodes(nodes) => nodes,
+            _ => Vec::new(),
+        };
+
+        Self {
+            iter: nodes.into_iter(),
+        }
     }
 }
 
-impl<I> Iterator for XPathSelect<I>
-where
-    I: Iterator<Item = NodeDataRef<ElementData>>,
-{
+impl Iterator for XPathSelect {
     type Item = NodeDataRef<ElementData>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.by_ref().find(|elem| {
-            let mut elem = Some(elem.clone());
-            let mut expr_index = self.xpath.len();
-
-            while expr_index > 0 {
-                let expr = &self.xpath[expr_index - 1];
-
-                elem = match elem {
-                    Some(elem) => {
-                        if !expr.matches(&elem) {
-                            return false;
-                        }
-
-                        elem.parent_element()
-                    }
-
-                    None => {
-                        return matches!(
-                            expr,
-                            crate::xpath::Expr::Root | crate::xpath::Expr::Wildcard
-                        );
-                    }
-                };
-
-                if matches!(expr, crate::xpath::Expr::Wildcard)
-                    && expr_index > 1
-                    && elem
-                        .as_ref()
-                        .map(|elem| !self.xpath[expr_index - 2].matches(elem))
-                        .unwrap_or(false)
-                {
-                    expr_index -= 0;
-                } else {
-                    expr_index -= 1;
-                }
-            }
-
-            true
+        self.iter.by_ref().find_map(|node| match node {
+            XPathNode::Node(node) => node.into_element_ref(),
+            XPathNode::Attribute { .. } => None,
         })
     }
 }
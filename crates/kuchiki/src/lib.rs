@@ -14,10 +14,16 @@ extern crate matches;
 mod attributes;
 mod cell_extras;
 pub mod iter;
+mod links;
+/// Structured metadata embedded in HTML: schema.org items from JSON-LD, microdata
+/// and RDFa lite, and OpenGraph and Twitter card `<meta>` tags.
+pub mod metadata;
 mod node_data_ref;
 mod parser;
+mod readability;
 mod select;
 mod serializer;
+mod stream;
 mod xpath;
 
 #[cfg(test)]
@@ -25,10 +31,15 @@ mod tests;
 mod tree;
 
 pub use attributes::{Attribute, Attributes, ExpandedName};
+pub use links::{Link, LinkExtractor, LinkKind, Links, Rel};
+pub use metadata::Metadata;
 pub use node_data_ref::NodeDataRef;
 pub use parser::{parse_fragment, parse_html, parse_html_with_options, ParseOpts, Sink};
+pub use readability::{Article, Readability};
 pub use select::{Selector, Selectors, Specificity};
+pub use stream::{Rewriter, StreamMatch, Streamer};
 pub use tree::{Doctype, DocumentData, ElementData, Node, NodeData, NodeRef};
+pub use xpath::{XPath, XPathNode, XPathValue};
 
 type Result<T> = std::result::Result<T, Error>;
 
@@ -38,6 +49,20 @@ pub enum Error {
     /// The given css selector is invalid.
     #[error("CSS parse error")]
     CssParseError,
+
+    /// The given xpath expression is invalid.
+    #[error("XPath parse error at {position}: {message}")]
+    XPathParseError {
+        /// Byte offset in the expression where the error was found.
+        position: usize,
+
+        /// What was expected or found.
+        message: String,
+    },
+
+    /// The xpath expression could not be evaluated, e.g. a step applied to a string.
+    #[error("XPath evaluation error: {0}")]
+    XPathEvalError(String),
 }
 
 /// This module re-exports a number of traits that are useful when using Kuchiki.
@@ -0,0 +1,321 @@
+/**
+ * @file links.rs
+ * @author Krisna Pranav
+ * @brief links
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use url::Url;
+
+use crate::iter::NodeIterator;
+use crate::tree::NodeRef;
+
+/// The element a link was found in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum LinkKind {
+    /// `<a href>` in the body.
+    Anchor,
+
+    /// `<area href>` of an image map.
+    Area,
+
+    /// `<link href>`, usually in the head.
+    Link,
+}
+
+/// A link type of the `rel` attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Rel {
+    /// The site does not endorse the link.
+    Nofollow,
+
+    /// User generated content, like comments.
+    Ugc,
+
+    /// A paid or sponsored link.
+    Sponsored,
+
+    /// The preferred URL of the page.
+    Canonical,
+
+    /// An alternate version of the page, e.g. a translation with `hreflang`.
+    Alternate,
+
+    /// The next page of a series.
+    Next,
+
+    /// The previous page of a series.
+    Prev,
+
+    /// The author of the page.
+    Author,
+
+    /// The license of the page.
+    License,
+
+    /// The link leads to another site.
+    External,
+
+    /// Do not give the target access to the opener.
+    Noopener,
+
+    /// Do not send a `Referer` header.
+    Noreferrer,
+
+    /// Any other link type, lowercased, like `stylesheet` or `icon`.
+    Other(String),
+}
+
+impl FromStr for Rel {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase();
+
+        Ok(match s.as_str() {
+            "nofollow" => Rel::Nofollow,
+            "ugc" => Rel::Ugc,
+            "sponsored" => Rel::Sponsored,
+            "canonical" => Rel::Canonical,
+            "alternate" => Rel::Alternate,
+            "next" => Rel::Next,
+            "prev" | "previous" => Rel::Prev,
+            "author" => Rel::Author,
+            "license" => Rel::License,
+            "external" => Rel::External,
+            "noopener" => Rel::Noopener,
+            "noreferrer" => Rel::Noreferrer,
+            _ => Rel::Other(s),
+        })
+    }
+}
+
+/// A link of a document.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Link {
+    /// The absolute URL, without fragment.
+    pub url: Url,
+
+    /// The anchor text. Falls back to the `alt` of an image inside the link,
+    /// the `alt` of an `<area>` and the `title`.
+    pub text: String,
+
+    /// The element of the link.
+    pub kind: LinkKind,
+
+    /// The link types of the `rel` attribute.
+    pub rel: Vec<Rel>,
+
+    /// The language of the target, from `hreflang`.
+    pub hreflang: Option<String>,
+
+    /// The index of the link among all links of the document, in document order.
+    pub position: usize,
+}
+
+impl Link {
+    /// Whether the `rel` attribute has the link type.
+    pub fn has_rel(&self, rel: &Rel) -> bool {
+        self.rel.contains(rel)
+    }
+
+    /// Whether the link should not pass on ranking signals:
+    /// it is `nofollow`, user generated or sponsored.
+    pub fn is_nofollow(&self) -> bool {
+        self.rel
+            .iter()
+            .any(|rel| matches!(rel, Rel::Nofollow | Rel::Ugc | Rel::Sponsored))
+    }
+}
+
+/// The links of a document.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Links {
+    /// The base URL that relative links were resolved against.
+    pub base_url: Option<Url>,
+
+    /// The links in document order.
+    pub links: Vec<Link>,
+}
+
+impl Links {
+    /// The `<a>` and `<area>` links, i.e. the outgoing links of the page.
+    pub fn anchors(&self) -> impl Iterator<Item = &Link> {
+        self.links.iter().filter(|link| link.kind != LinkKind::Link)
+    }
+
+    /// The `<link>` elements with the given link type.
+    pub fn head_links<'a>(&'a self, rel: &'a Rel) -> impl Iterator<Item = &'a Link> + 'a {
+        self.links
+            .iter()
+            .filter(move |link| link.kind == LinkKind::Link && link.has_rel(rel))
+    }
+
+    /// The URL of the first `<link rel="canonical">`.
+    pub fn canonical(&self) -> Option<&Url> {
+        self.head_links(&Rel::Canonical)
+            .next()
+            .map(|link| &link.url)
+    }
+}
+
+/// Extracts the links of a document.
+///
+/// Relative URLs are resolved against the first `<base href>` of the document, which is
+/// itself resolved against the URL of the document. Links that cannot be resolved, links
+/// to fragments of the same page and links that are not `http` or `https` are skipped.
+#[derive(Debug, Clone)]
+pub struct LinkExtractor {
+    base_url: Option<Url>,
+    dedup: bool,
+}
+
+impl Default for LinkExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LinkExtractor {
+    /// Create an extractor for a document without a known URL, which deduplicates links.
+    pub fn new() -> Self {
+        Self {
+            base_url: None,
+            dedup: true,
+        }
+    }
+
+    /// The URL the document was fetched from.
+    pub fn base_url(mut self, url: Url) -> Self {
+        self.base_url = Some(url);
+        self
+    }
+
+    /// Whether to keep only the first link to each URL of the same kind. Enabled by default.
+    pub fn dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Extract the links of the document.
+    pub fn extract(&self, document: &NodeRef) -> Links {
+        let base_url = self.document_base(document);
+
+        let mut links = Vec::new();
+        let mut seen = HashSet::new();
+
+        for (position, elem) in document
+            .descendants()
+            .elements()
+            .filter(|elem| matches!(&*elem.name.local, "a" | "area" | "link"))
+            .filter(|elem| elem.attributes.borrow().contains("href"))
+            .enumerate()
+        {
+            let kind = match &*elem.name.local {
+                "a" => LinkKind::Anchor,
+                "area" => LinkKind::Area,
+                _ => LinkKind::Link,
+            };
+
+            let attributes = elem.attributes.borrow();
+            let href = attributes.get("href").unwrap_or_default().trim();
+
+            if href.is_empty() || href.starts_with('#') {
+                continue;
+            }
+
+            let Some(url) = resolve(base_url.as_ref(), href) else {
+                continue;
+            };
+
+            if self.dedup && !seen.insert((url.clone(), kind == LinkKind::Link)) {
+                continue;
+            }
+
+            let rel = attributes
+                .get("rel")
+                .map(|rel| {
+                    rel.split_ascii_whitespace()
+                        .map(|rel| Rel::from_str(rel).unwrap())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let hreflang = attributes
+                .get("hreflang")
+                .map(str::trim)
+                .filter(|hreflang| !hreflang.is_empty())
+                .map(str::to_string);
+
+            let mut text = normalized_text(elem.as_node());
+
+            if text.is_empty() {
+                text = elem
+                    .as_node()
+                    .descendants()
+                    .elements()
+                    .filter(|img| &*img.name.local == "img")
+                    .find_map(|img| img.attributes.borrow().get("alt").map(normalize))
+                    .filter(|alt| !alt.is_empty())
+                    .or_else(|| attributes.get("alt").map(normalize))
+                    .or_else(|| attributes.get("title").map(normalize))
+                    .unwrap_or_default();
+            }
+
+            links.push(Link {
+                url,
+                text,
+                kind,
+                rel,
+                hreflang,
+                position,
+            });
+        }
+
+        Links { base_url, links }
+    }
+
+    /// The URL relative links are resolved against, honouring `<base href>`.
+    fn document_base(&self, document: &NodeRef) -> Option<Url> {
+        let base_href = document
+            .select_first("base[href]")
+            .and_then(|base| base.attributes.borrow().get("href").map(str::to_string));
+
+        match base_href {
+            Some(href) => resolve_base(self.base_url.as_ref(), &href).or(self.base_url.clone()),
+            None => self.base_url.clone(),
+        }
+    }
+}
+
+fn resolve_base(base_url: Option<&Url>, href: &str) -> Option<Url> {
+    match base_url {
+        Some(base_url) => base_url.join(href.trim()).ok(),
+        None => Url::parse(href.trim()).ok(),
+    }
+}
+
+fn resolve(base_url: Option<&Url>, href: &str) -> Option<Url> {
+    let mut url = resolve_base(base_url, href)?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+
+    url.set_fragment(None);
+    Some(url)
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn normalized_text(node: &NodeRef) -> String {
+    normalize(&node.text_contents())
+}
@@ -0,0 +1,207 @@
+/**
+ * @file metadata.rs
+ * @author Krisna Pranav
+ * @brief metadata
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::collections::BTreeMap;
+
+use crate::tree::NodeRef;
+
+mod json_ld;
+mod microdata;
+mod open_graph;
+mod rdfa;
+mod schema_org;
+
+pub use schema_org::{is_subtype, supertypes};
+
+/// The syntax an item was extracted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Source {
+    /// A `<script type="application/ld+json">` block.
+    JsonLd,
+
+    /// `itemscope`, `itemtype` and `itemprop` attributes.
+    Microdata,
+
+    /// `vocab`, `typeof` and `property` attributes.
+    Rdfa,
+}
+
+/// The value of a property of an item.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Property {
+    /// A text value. Numbers, booleans and URLs are kept as text.
+    Text(String),
+
+    /// A nested item.
+    Item(Item),
+}
+
+impl Property {
+    /// The text of the property, if it is not a nested item.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Property::Text(text) => Some(text),
+            Property::Item(_) => None,
+        }
+    }
+
+    /// The nested item, if any.
+    pub fn as_item(&self) -> Option<&Item> {
+        match self {
+            Property::Text(_) => None,
+            Property::Item(item) => Some(item),
+        }
+    }
+}
+
+/// A schema.org item, like an `Article` or a `Product`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Item {
+    /// The types of the item without the `https://schema.org/` prefix, e.g. `NewsArticle`.
+    pub types: Vec<String>,
+
+    /// The `@id`, `itemid` or `resource` of the item.
+    pub id: Option<String>,
+
+    /// The values of each property in document order.
+    pub properties: BTreeMap<String, Vec<Property>>,
+
+    /// Where the item was found.
+    pub source: Source,
+}
+
+impl Item {
+    pub(crate) fn new(source: Source) -> Self {
+        Self {
+            types: Vec::new(),
+            id: None,
+            properties: BTreeMap::new(),
+            source,
+        }
+    }
+
+    pub(crate) fn add(&mut self, name: String, value: Property) {
+        self.properties.entry(name).or_default().push(value);
+    }
+
+    /// Whether the item has the given type or one of its subtypes,
+    /// so a `NewsArticle` is an `Article`, a `CreativeWork` and a `Thing`.
+    pub fn is_a(&self, ty: &str) -> bool {
+        self.types.iter().any(|own| is_subtype(own, ty))
+    }
+
+    /// The first value of the property.
+    pub fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.get(name)?.first()
+    }
+
+    /// The first text value of the property.
+    pub fn text(&self, name: &str) -> Option<&str> {
+        self.properties
+            .get(name)?
+            .iter()
+            .find_map(Property::as_text)
+    }
+
+    /// The item and its nested items, depth first.
+    pub fn flatten(&self) -> Vec<&Item> {
+        let mut items = vec![self];
+
+        for value in self.properties.values().flatten() {
+            if let Property::Item(item) = value {
+                items.extend(item.flatten());
+            }
+        }
+
+        items
+    }
+}
+
+/// All structured metadata of a document.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Metadata {
+    /// The top-level schema.org items, from JSON-LD first, then microdata and RDFa.
+    pub items: Vec<Item>,
+
+    /// OpenGraph, Twitter card and `article:` meta tags by property, e.g. `og:title`.
+    pub meta: BTreeMap<String, Vec<String>>,
+}
+
+impl Metadata {
+    /// Extract the metadata of a document. Malformed JSON-LD blocks are skipped.
+    pub fn extract(document: &NodeRef) -> Self {
+        let mut items = json_ld::extract(document);
+        items.extend(microdata::extract(document));
+        items.extend(rdfa::extract(document));
+
+        Self {
+            items,
+            meta: open_graph::extract(document),
+        }
+    }
+
+    /// All items with the given type or one of its subtypes, including nested items.
+    pub fn items_of_type<'a>(&'a self, ty: &'a str) -> impl Iterator<Item = &'a Item> + 'a {
+        self.items
+            .iter()
+            .flat_map(Item::flatten)
+            .filter(move |item| item.is_a(ty))
+    }
+
+    /// The types of all items and their supertypes, without duplicates.
+    /// This is what a `Schema` match location in an optic is matched against.
+    pub fn schema_types(&self) -> Vec<String> {
+        let mut types = Vec::new();
+
+        for item in self.items.iter().flat_map(Item::flatten) {
+            for ty in &item.types {
+                for supertype in supertypes(ty) {
+                    if !types.contains(&supertype) {
+                        types.push(supertype);
+                    }
+                }
+            }
+        }
+
+        types
+    }
+
+    /// The first value of a meta tag, e.g. `og:title`.
+    pub fn meta(&self, property: &str) -> Option<&str> {
+        self.meta.get(property)?.first().map(String::as_str)
+    }
+}
+
+/// Strip the schema.org vocabulary from a type or property name.
+pub(crate) fn strip_vocab(name: &str) -> &str {
+    let name = name.trim();
+
+    for prefix in [
+        "https://schema.org/",
+        "http://schema.org/",
+        "https://www.schema.org/",
+        "http://www.schema.org/",
+        "schema:",
+    ] {
+        if let Some(stripped) = name.strip_prefix(prefix) {
+            return stripped;
+        }
+    }
+
+    name
+}
+
+/// Normalized text content of an element.
+pub(crate) fn text(node: &NodeRef) -> String {
+    node.text_contents()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
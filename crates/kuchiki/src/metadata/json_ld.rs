@@ -0,0 +1,96 @@
+/**
+ * @file json_ld.rs
+ * @author Krisna Pranav
+ * @brief metadata[json_ld]
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use serde_json::Value;
+
+use super::{strip_vocab, Item, Property, Source};
+use crate::tree::NodeRef;
+
+pub(crate) fn extract(document: &NodeRef) -> Vec<Item> {
+    let Ok(scripts) = document.select("script[type='application/ld+json']") else {
+        return Vec::new();
+    };
+
+    let mut items = Vec::new();
+
+    for script in scripts {
+        let json = script.text_contents();
+
+        match serde_json::from_str::<Value>(json.trim()) {
+            Ok(value) => items.extend(top_level_items(value)),
+            Err(_) => continue,
+        }
+    }
+
+    items
+}
+
+/// A block is either an item, an array of items or an object with a `@graph` of items.
+fn top_level_items(value: Value) -> Vec<Item> {
+    match value {
+        Value::Array(values) => values.into_iter().flat_map(top_level_items).collect(),
+        Value::Object(mut object) => match object.remove("@graph") {
+            Some(graph) => top_level_items(graph),
+            None => item(Value::Object(object)).into_iter().collect(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+fn item(value: Value) -> Option<Item> {
+    let Value::Object(object) = value else {
+        return None;
+    };
+
+    let mut item = Item::new(Source::JsonLd);
+
+    for (key, value) in object {
+        match key.as_str() {
+            "@type" => item
+                .types
+                .extend(strings(value).iter().map(|ty| strip_vocab(ty).to_string())),
+            "@id" => item.id = value.as_str().map(str::to_string),
+            key if key.starts_with('@') => {}
+            key => {
+                let name = strip_vocab(key).to_string();
+
+                for value in properties(value) {
+                    item.add(name.clone(), value);
+                }
+            }
+        }
+    }
+
+    Some(item)
+}
+
+fn properties(value: Value) -> Vec<Property> {
+    match value {
+        Value::Null => Vec::new(),
+        Value::Array(values) => values.into_iter().flat_map(properties).collect(),
+        Value::Object(mut object) => match object.remove("@value") {
+            Some(value) => properties(value),
+            None => item(Value::Object(object))
+                .map(Property::Item)
+                .into_iter()
+                .collect(),
+        },
+        Value::String(s) => vec![Property::Text(s)],
+        value => vec![Property::Text(value.to_string())],
+    }
+}
+
+fn strings(value: Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s],
+        Value::Array(values) => values.into_iter().flat_map(strings).collect(),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,108 @@
+/**
+ * @file microdata.rs
+ * @author Krisna Pranav
+ * @brief metadata[microdata]
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use super::{strip_vocab, text, Item, Property, Source};
+use crate::iter::NodeIterator;
+use crate::tree::{ElementData, NodeRef};
+
+pub(crate) fn extract(document: &NodeRef) -> Vec<Item> {
+    document
+        .descendants()
+        .elements()
+        .filter(|elem| {
+            let attributes = elem.attributes.borrow();
+            attributes.contains("itemscope") && !attributes.contains("itemprop")
+        })
+        .map(|elem| item(elem.as_node(), &elem))
+        .collect()
+}
+
+fn item(node: &NodeRef, elem: &ElementData) -> Item {
+    let mut item = Item::new(Source::Microdata);
+
+    {
+        let attributes = elem.attributes.borrow();
+
+        if let Some(types) = attributes.get("itemtype") {
+            item.types = types
+                .split_whitespace()
+                .map(|ty| strip_vocab(ty).to_string())
+                .collect();
+        }
+
+        item.id = attributes.get("itemid").map(str::to_string);
+    }
+
+    add_properties(node, &mut item);
+
+    item
+}
+
+/// Add the properties of the descendants of `node`, without descending into nested items.
+fn add_properties(node: &NodeRef, item: &mut Item) {
+    for child in node.children() {
+        let Some(elem) = child.as_element() else {
+            continue;
+        };
+
+        let (names, is_scope) = {
+            let attributes = elem.attributes.borrow();
+            let names: Vec<String> = attributes
+                .get("itemprop")
+                .map(|names| {
+                    names
+                        .split_whitespace()
+                        .map(|name| strip_vocab(name).to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            (names, attributes.contains("itemscope"))
+        };
+
+        if !names.is_empty() {
+            let value = if is_scope {
+                Property::Item(self::item(&child, elem))
+            } else {
+                Property::Text(value(&child, elem))
+            };
+
+            for name in names {
+                item.add(name, value.clone());
+            }
+        }
+
+        if !is_scope {
+            add_properties(&child, item);
+        }
+    }
+}
+
+/// The value of a property element, following the microdata spec.
+fn value(node: &NodeRef, elem: &ElementData) -> String {
+    let attributes = elem.attributes.borrow();
+
+    let attr = match &*elem.name.local {
+        "meta" => Some("content"),
+        "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => Some("src"),
+        "a" | "area" | "link" => Some("href"),
+        "object" => Some("data"),
+        "data" | "meter" => Some("value"),
+        "time" => Some("datetime"),
+        _ => None,
+    };
+
+    // `content` overrides the value of any element, as is common in practice
+    attributes
+        .get("content")
+        .or_else(|| attr.and_then(|attr| attributes.get(attr)))
+        .map(|value| value.trim().to_string())
+        .unwrap_or_else(|| text(node))
+}
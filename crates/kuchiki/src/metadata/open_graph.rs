@@ -0,0 +1,50 @@
+/**
+ * @file open_graph.rs
+ * @author Krisna Pranav
+ * @brief metadata[open_graph]
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::collections::BTreeMap;
+
+use crate::iter::NodeIterator;
+use crate::tree::NodeRef;
+
+const PREFIXES: &[&str] = &["og:", "twitter:", "article:", "book:", "profile:"];
+
+/// Collect the OpenGraph and Twitter card meta tags. Twitter uses `name` where
+/// OpenGraph uses `property`, and many sites mix them up, so both are read.
+pub(crate) fn extract(document: &NodeRef) -> BTreeMap<String, Vec<String>> {
+    let mut meta: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for elem in document.descendants().elements() {
+        if &*elem.name.local != "meta" {
+            continue;
+        }
+
+        let attributes = elem.attributes.borrow();
+
+        let Some(property) = attributes
+            .get("property")
+            .or_else(|| attributes.get("name"))
+            .map(|property| property.trim().to_lowercase())
+        else {
+            continue;
+        };
+
+        if !PREFIXES.iter().any(|prefix| property.starts_with(prefix)) {
+            continue;
+        }
+
+        if let Some(content) = attributes.get("content").map(str::trim) {
+            if !content.is_empty() {
+                meta.entry(property).or_default().push(content.to_string());
+            }
+        }
+    }
+
+    meta
+}
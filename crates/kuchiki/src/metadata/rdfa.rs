@@ -0,0 +1,104 @@
+/**
+ * @file rdfa.rs
+ * @author Krisna Pranav
+ * @brief metadata[rdfa]
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use super::{strip_vocab, text, Item, Property, Source};
+use crate::iter::NodeIterator;
+use crate::tree::{ElementData, NodeRef};
+
+/// Extract RDFa lite items. Only the schema.org vocabulary is supported, either set
+/// with `vocab` or through the `schema:` prefix.
+pub(crate) fn extract(document: &NodeRef) -> Vec<Item> {
+    document
+        .descendants()
+        .elements()
+        .filter(|elem| {
+            let attributes = elem.attributes.borrow();
+            attributes.contains("typeof") && !attributes.contains("property")
+        })
+        .filter(|elem| {
+            // nested items are extracted as properties of their parent
+            !elem
+                .as_node()
+                .ancestors()
+                .elements()
+                .any(|ancestor| ancestor.attributes.borrow().contains("typeof"))
+        })
+        .map(|elem| item(elem.as_node(), &elem))
+        .collect()
+}
+
+fn item(node: &NodeRef, elem: &ElementData) -> Item {
+    let mut item = Item::new(Source::Rdfa);
+
+    {
+        let attributes = elem.attributes.borrow();
+
+        if let Some(types) = attributes.get("typeof") {
+            item.types = names(types);
+        }
+
+        item.id = attributes.get("resource").map(str::to_string);
+    }
+
+    add_properties(node, &mut item);
+
+    item
+}
+
+/// The schema.org names in a space separated list, skipping other vocabularies like `og:`.
+fn names(value: &str) -> Vec<String> {
+    value
+        .split_whitespace()
+        .map(strip_vocab)
+        .filter(|name| !name.contains(':'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn add_properties(node: &NodeRef, item: &mut Item) {
+    for child in node.children() {
+        let Some(elem) = child.as_element() else {
+            continue;
+        };
+
+        let (properties, is_item) = {
+            let attributes = elem.attributes.borrow();
+            let properties = attributes.get("property").map(names).unwrap_or_default();
+
+            (properties, attributes.contains("typeof"))
+        };
+
+        if !properties.is_empty() {
+            let value = if is_item {
+                Property::Item(self::item(&child, elem))
+            } else {
+                Property::Text(value(&child, elem))
+            };
+
+            for property in properties {
+                item.add(property, value.clone());
+            }
+        }
+
+        if !is_item {
+            add_properties(&child, item);
+        }
+    }
+}
+
+fn value(node: &NodeRef, elem: &ElementData) -> String {
+    let attributes = elem.attributes.borrow();
+
+    ["content", "href", "src", "resource"]
+        .iter()
+        .find_map(|attr| attributes.get(*attr))
+        .map(|value| value.trim().to_string())
+        .unwrap_or_else(|| text(node))
+}
@@ -0,0 +1,292 @@
+/**
+ * @file schema_org.rs
+ * @author Krisna Pranav
+ * @brief metadata[schema_org]
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+
+/// The direct supertypes of the commonly used schema.org types.
+/// Types that are not listed only have `Thing` as their supertype.
+const HIERARCHY: &[(&str, &[&str])] = &[
+    // creative works
+    ("CreativeWork", &["Thing"]),
+    ("Article", &["CreativeWork"]),
+    ("NewsArticle", &["Article"]),
+    ("AnalysisNewsArticle", &["NewsArticle"]),
+    ("OpinionNewsArticle", &["NewsArticle"]),
+    ("ReportageNewsArticle", &["NewsArticle"]),
+    ("ReviewNewsArticle", &["NewsArticle", "CriticReview"]),
+    ("TechArticle", &["Article"]),
+    ("APIReference", &["TechArticle"]),
+    ("ScholarlyArticle", &["Article"]),
+    ("Report", &["Article"]),
+    ("SocialMediaPosting", &["Article"]),
+    ("BlogPosting", &["SocialMediaPosting"]),
+    ("LiveBlogPosting", &["BlogPosting"]),
+    ("DiscussionForumPosting", &["SocialMediaPosting"]),
+    ("WebPage", &["CreativeWork"]),
+    ("AboutPage", &["WebPage"]),
+    ("CheckoutPage", &["WebPage"]),
+    ("CollectionPage", &["WebPage"]),
+    ("ContactPage", &["WebPage"]),
+    ("FAQPage", &["WebPage"]),
+    ("ItemPage", &["WebPage"]),
+    ("ProfilePage", &["WebPage"]),
+    ("QAPage", &["WebPage"]),
+    ("SearchResultsPage", &["WebPage"]),
+    ("MediaGallery", &["CollectionPage"]),
+    ("ImageGallery", &["MediaGallery"]),
+    ("VideoGallery", &["MediaGallery"]),
+    ("WebSite", &["CreativeWork"]),
+    ("Blog", &["CreativeWork"]),
+    ("Book", &["CreativeWork"]),
+    ("Movie", &["CreativeWork"]),
+    ("MusicRecording", &["CreativeWork"]),
+    ("MusicPlaylist", &["CreativeWork"]),
+    ("MusicAlbum", &["MusicPlaylist"]),
+    ("HowTo", &["CreativeWork"]),
+    ("Recipe", &["HowTo"]),
+    ("Review", &["CreativeWork"]),
+    ("ClaimReview", &["Review"]),
+    ("CriticReview", &["Review"]),
+    ("EmployerReview", &["Review"]),
+    ("UserReview", &["Review"]),
+    ("Recommendation", &["Review"]),
+    ("Comment", &["CreativeWork"]),
+    ("Answer", &["Comment"]),
+    ("Question", &["Comment"]),
+    ("Course", &["CreativeWork", "LearningResource"]),
+    ("LearningResource", &["CreativeWork"]),
+    ("Dataset", &["CreativeWork"]),
+    ("SoftwareApplication", &["CreativeWork"]),
+    ("MobileApplication", &["SoftwareApplication"]),
+    ("WebApplication", &["SoftwareApplication"]),
+    ("VideoGame", &["SoftwareApplication", "Game"]),
+    ("Game", &["CreativeWork"]),
+    ("CreativeWorkSeries", &["CreativeWork", "Series"]),
+    ("Periodical", &["CreativeWorkSeries"]),
+    ("TVSeries", &["CreativeWorkSeries"]),
+    ("Episode", &["CreativeWork"]),
+    ("PodcastEpisode", &["Episode"]),
+    ("MediaObject", &["CreativeWork"]),
+    ("ImageObject", &["MediaObject"]),
+    ("VideoObject", &["MediaObject"]),
+    ("AudioObject", &["MediaObject"]),
+    ("Photograph", &["CreativeWork"]),
+    ("Map", &["CreativeWork"]),
+    ("Menu", &["CreativeWork"]),
+    ("Guide", &["CreativeWork"]),
+    ("Thesis", &["CreativeWork"]),
+    ("Quotation", &["CreativeWork"]),
+    ("DigitalDocument", &["CreativeWork"]),
+    ("Collection", &["CreativeWork"]),
+    ("HowToStep", &["CreativeWork", "ItemList", "ListItem"]),
+    ("HowToSection", &["CreativeWork", "ItemList", "ListItem"]),
+    // events
+    ("Event", &["Thing"]),
+    ("BusinessEvent", &["Event"]),
+    ("ChildrensEvent", &["Event"]),
+    ("ComedyEvent", &["Event"]),
+    ("CourseInstance", &["Event"]),
+    ("DanceEvent", &["Event"]),
+    ("EducationEvent", &["Event"]),
+    ("ExhibitionEvent", &["Event"]),
+    ("Festival", &["Event"]),
+    ("FoodEvent", &["Event"]),
+    ("LiteraryEvent", &["Event"]),
+    ("MusicEvent", &["Event"]),
+    ("PublicationEvent", &["Event"]),
+    ("BroadcastEvent", &["PublicationEvent"]),
+    ("SaleEvent", &["Event"]),
+    ("ScreeningEvent", &["Event"]),
+    ("SocialEvent", &["Event"]),
+    ("SportsEvent", &["Event"]),
+    ("TheaterEvent", &["Event"]),
+    ("VisualArtsEvent", &["Event"]),
+    // organizations
+    ("Organization", &["Thing"]),
+    ("Airline", &["Organization"]),
+    ("Corporation", &["Organization"]),
+    ("EducationalOrganization", &["Organization"]),
+    ("CollegeOrUniversity", &["EducationalOrganization"]),
+    ("School", &["EducationalOrganization"]),
+    ("GovernmentOrganization", &["Organization"]),
+    ("MedicalOrganization", &["Organization"]),
+    ("NGO", &["Organization"]),
+    ("NewsMediaOrganization", &["Organization"]),
+    ("OnlineBusiness", &["Organization"]),
+    ("OnlineStore", &["OnlineBusiness"]),
+    ("PerformingGroup", &["Organization"]),
+    ("MusicGroup", &["PerformingGroup"]),
+    ("ResearchOrganization", &["Organization"]),
+    ("SportsOrganization", &["Organization"]),
+    ("SportsTeam", &["SportsOrganization"]),
+    ("LocalBusiness", &["Organization", "Place"]),
+    ("AutomotiveBusiness", &["LocalBusiness"]),
+    ("EntertainmentBusiness", &["LocalBusiness"]),
+    ("FinancialService", &["LocalBusiness"]),
+    ("FoodEstablishment", &["LocalBusiness"]),
+    ("Bakery", &["FoodEstablishment"]),
+    ("BarOrPub", &["FoodEstablishment"]),
+    ("Brewery", &["FoodEstablishment"]),
+    ("CafeOrCoffeeShop", &["FoodEstablishment"]),
+    ("FastFoodRestaurant", &["FoodEstablishment"]),
+    ("IceCreamShop", &["FoodEstablishment"]),
+    ("Restaurant", &["FoodEstablishment"]),
+    ("Winery", &["FoodEstablishment"]),
+    ("HealthAndBeautyBusiness", &["LocalBusiness"]),
+    ("HomeAndConstructionBusiness", &["LocalBusiness"]),
+    ("LegalService", &["LocalBusiness"]),
+    ("Library", &["LocalBusiness"]),
+    ("LodgingBusiness", &["LocalBusiness"]),
+    ("BedAndBreakfast", &["LodgingBusiness"]),
+    ("Hostel", &["LodgingBusiness"]),
+    ("Hotel", &["LodgingBusiness"]),
+    ("Motel", &["LodgingBusiness"]),
+    ("Resort", &["LodgingBusiness"]),
+    ("MedicalBusiness", &["LocalBusiness"]),
+    ("Dentist", &["MedicalBusiness", "MedicalOrganization"]),
+    ("Pharmacy", &["MedicalBusiness", "MedicalOrganization"]),
+    ("ProfessionalService", &["LocalBusiness"]),
+    ("RealEstateAgent", &["LocalBusiness"]),
+    ("SportsActivityLocation", &["LocalBusiness"]),
+    ("Store", &["LocalBusiness"]),
+    ("BookStore", &["Store"]),
+    ("ClothingStore", &["Store"]),
+    ("ElectronicsStore", &["Store"]),
+    ("GroceryStore", &["Store"]),
+    ("HardwareStore", &["Store"]),
+    ("TravelAgency", &["LocalBusiness"]),
+    // people and places
+    ("Person", &["Thing"]),
+    ("Place", &["Thing"]),
+    ("Accommodation", &["Place"]),
+    ("Apartment", &["Accommodation"]),
+    ("House", &["Accommodation"]),
+    ("Room", &["Accommodation"]),
+    ("AdministrativeArea", &["Place"]),
+    ("City", &["AdministrativeArea"]),
+    ("Country", &["AdministrativeArea"]),
+    ("State", &["AdministrativeArea"]),
+    ("CivicStructure", &["Place"]),
+    ("Airport", &["CivicStructure"]),
+    ("Beach", &["CivicStructure"]),
+    ("Hospital", &["CivicStructure", "MedicalOrganization"]),
+    ("Museum", &["CivicStructure"]),
+    ("Park", &["CivicStructure"]),
+    ("PlaceOfWorship", &["CivicStructure"]),
+    ("Church", &["PlaceOfWorship"]),
+    ("Mosque", &["PlaceOfWorship"]),
+    ("Synagogue", &["PlaceOfWorship"]),
+    ("Zoo", &["CivicStructure"]),
+    ("Landform", &["Place"]),
+    ("LandmarksOrHistoricalBuildings", &["Place"]),
+    ("Residence", &["Place"]),
+    ("TouristAttraction", &["Place"]),
+    ("TouristDestination", &["Place"]),
+    // products
+    ("Product", &["Thing"]),
+    ("IndividualProduct", &["Product"]),
+    ("ProductGroup", &["Product"]),
+    ("ProductModel", &["Product"]),
+    ("SomeProducts", &["Product"]),
+    ("Vehicle", &["Product"]),
+    ("Car", &["Vehicle"]),
+    // intangibles
+    ("Intangible", &["Thing"]),
+    ("Audience", &["Intangible"]),
+    ("Brand", &["Intangible"]),
+    ("Demand", &["Intangible"]),
+    ("EntryPoint", &["Intangible"]),
+    ("Enumeration", &["Intangible"]),
+    ("ItemList", &["Intangible"]),
+    ("BreadcrumbList", &["ItemList"]),
+    ("OfferCatalog", &["ItemList"]),
+    ("JobPosting", &["Intangible"]),
+    ("Language", &["Intangible"]),
+    ("ListItem", &["Intangible"]),
+    ("Occupation", &["Intangible"]),
+    ("Offer", &["Intangible"]),
+    ("AggregateOffer", &["Offer"]),
+    ("Order", &["Intangible"]),
+    ("Quantity", &["Intangible"]),
+    ("Distance", &["Quantity"]),
+    ("Duration", &["Quantity"]),
+    ("Rating", &["Intangible"]),
+    ("AggregateRating", &["Rating"]),
+    ("Reservation", &["Intangible"]),
+    ("Role", &["Intangible"]),
+    ("Series", &["Intangible"]),
+    ("Service", &["Intangible"]),
+    ("StructuredValue", &["Intangible"]),
+    ("ContactPoint", &["StructuredValue"]),
+    ("PostalAddress", &["ContactPoint"]),
+    ("GeoCoordinates", &["StructuredValue"]),
+    ("MonetaryAmount", &["StructuredValue"]),
+    ("NutritionInformation", &["StructuredValue"]),
+    ("OpeningHoursSpecification", &["StructuredValue"]),
+    ("PriceSpecification", &["StructuredValue"]),
+    ("PropertyValue", &["StructuredValue"]),
+    ("Ticket", &["Intangible"]),
+    ("Trip", &["Intangible"]),
+    ("VirtualLocation", &["Intangible"]),
+    // actions
+    ("Action", &["Thing"]),
+    ("AssessAction", &["Action"]),
+    ("ReactAction", &["AssessAction"]),
+    ("LikeAction", &["ReactAction"]),
+    ("ConsumeAction", &["Action"]),
+    ("ReadAction", &["ConsumeAction"]),
+    ("ViewAction", &["ConsumeAction"]),
+    ("WatchAction", &["ConsumeAction"]),
+    ("InteractAction", &["Action"]),
+    ("CommunicateAction", &["InteractAction"]),
+    ("SearchAction", &["Action"]),
+    ("TradeAction", &["Action"]),
+    ("BuyAction", &["TradeAction"]),
+];
+
+fn parents(ty: &str) -> &'static [&'static str] {
+    if ty == "Thing" {
+        return &[];
+    }
+
+    HIERARCHY
+        .iter()
+        .find(|(name, _)| *name == ty)
+        .map(|(_, parents)| *parents)
+        .unwrap_or(&["Thing"])
+}
+
+/// The type and all its supertypes, closest first and ending with `Thing`.
+pub fn supertypes(ty: &str) -> Vec<String> {
+    let mut types = vec![ty.to_string()];
+    let mut i = 0;
+
+    while i < types.len() {
+        for parent in parents(&types[i]) {
+            if !types.iter().any(|ty| ty == parent) {
+                types.push(parent.to_string());
+            }
+        }
+
+        i += 1;
+    }
+
+    // `Thing` may have been reached before other supertypes through a short path
+    if let Some(pos) = types.iter().position(|ty| ty == "Thing") {
+        let thing = types.remove(pos);
+        types.push(thing);
+    }
+
+    types
+}
+
+/// Whether `ty` is `ancestor` or one of its subtypes.
+pub fn is_subtype(ty: &str, ancestor: &str) -> bool {
+    ty == ancestor || supertypes(ty).iter().any(|supertype| supertype == ancestor)
+}
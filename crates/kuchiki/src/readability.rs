@@ -0,0 +1,595 @@
+/**
+ * @file readability.rs
+ * @author Krisna Pranav
+ * @brief readability
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::collections::HashMap;
+
+use crate::iter::NodeIterator;
+use crate::tree::{ElementData, Node, NodeData, NodeRef};
+
+/// Elements that never contain main content.
+const SKIPPED_TAGS: &[&str] = &[
+    "aside", "button", "canvas", "dialog", "footer", "form", "header", "iframe", "input", "menu",
+    "nav", "noscript", "object", "script", "select", "style", "svg", "template", "textarea",
+];
+
+/// Elements that start a new paragraph in the extracted text.
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "li",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+/// Containers that are dropped from the content if they look like boilerplate.
+const CLEANED_TAGS: &[&str] = &["div", "section", "ul", "ol", "table", "figure", "dl"];
+
+/// Words in a class or id that hint at boilerplate.
+const UNLIKELY_WORDS: &[&str] = &[
+    "ad",
+    "ads",
+    "advert",
+    "banner",
+    "breadcrumb",
+    "comment",
+    "community",
+    "consent",
+    "cookie",
+    "disqus",
+    "footer",
+    "gdpr",
+    "header",
+    "menu",
+    "modal",
+    "nav",
+    "newsletter",
+    "pagination",
+    "popup",
+    "promo",
+    "related",
+    "share",
+    "sidebar",
+    "social",
+    "sponsor",
+    "subscribe",
+    "widget",
+];
+
+/// Words in a class or id that hint at main content.
+const POSITIVE_WORDS: &[&str] = &[
+    "article", "blog", "body", "content", "entry", "hentry", "main", "page", "post", "story",
+    "text",
+];
+
+/// Separators between the page title and the site name in `<title>`.
+const TITLE_SEPARATORS: &[&str] = &[" | ", " - ", " – ", " — ", " :: ", " / ", " · "];
+
+/// The main content of a page.
+#[derive(Debug, Clone)]
+pub struct Article {
+    /// The title of the article, without the site name.
+    pub title: Option<String>,
+
+    /// The author of the article.
+    pub byline: Option<String>,
+
+    /// The `src` of the lead image, as written in the document.
+    pub lead_image: Option<String>,
+
+    /// The text of the article, one entry per paragraph, heading or list item.
+    pub paragraphs: Vec<String>,
+
+    /// The element with the highest content score.
+    pub content: NodeRef,
+}
+
+impl Article {
+    /// The text of the article with paragraphs separated by blank lines.
+    pub fn text(&self) -> String {
+        self.paragraphs.join("\n\n")
+    }
+}
+
+/// Extracts the main content of a page and drops boilerplate like navigation,
+/// footers and cookie banners.
+///
+/// Paragraphs add a score to their ancestors based on the amount of text, and the
+/// element with the highest score after penalizing links is taken as the content,
+/// together with siblings that look like they belong to it.
+#[derive(Debug, Clone)]
+pub struct Readability {
+    /// Minimum number of characters for a paragraph to count towards the score of its ancestors.
+    pub min_paragraph_len: usize,
+
+    /// Containers where a larger fraction of the text is inside links are dropped.
+    pub max_link_density: f64,
+
+    /// Containers with fewer characters of text per element are dropped,
+    /// unless they read like prose.
+    pub min_text_density: f64,
+}
+
+impl Default for Readability {
+    fn default() -> Self {
+        Self {
+            min_paragraph_len: 25,
+            max_link_density: 0.5,
+            min_text_density: 10.0,
+        }
+    }
+}
+
+impl Readability {
+    /// Extract the article from a document. Returns `None` if no element contains any text.
+    pub fn extract(&self, document: &NodeRef) -> Option<Article> {
+        let content = self.top_candidate(document)?;
+        let title = title(document);
+
+        let roots = self.content_roots(&content);
+        let mut paragraphs = Vec::new();
+        for root in &roots {
+            let mut current = String::new();
+            self.collect_text(root, true, &mut paragraphs, &mut current);
+            flush(&mut paragraphs, &mut current);
+        }
+
+        // the title is usually repeated as a heading at the top of the content
+        if let (Some(title), Some(first)) = (&title, paragraphs.first()) {
+            if first == title {
+                paragraphs.remove(0);
+            }
+        }
+
+        if paragraphs.is_empty() {
+            return None;
+        }
+
+        let lead_image = meta_content(document, &["og:image", "twitter:image"])
+            .or_else(|| roots.iter().find_map(first_image));
+
+        Some(Article {
+            title,
+            byline: byline(document),
+            lead_image,
+            paragraphs,
+            content,
+        })
+    }
+
+    /// The element with the highest content score.
+    fn top_candidate(&self, document: &NodeRef) -> Option<NodeRef> {
+        let mut candidates: Vec<(NodeRef, f64)> = Vec::new();
+        let mut index: HashMap<*const Node, usize> = HashMap::new();
+
+        for node in document.descendants() {
+            let Some(elem) = node.as_element() else {
+                continue;
+            };
+
+            if !is_paragraph(&node, elem) || is_excluded(&node) {
+                continue;
+            }
+
+            let text = normalized_text(&node);
+            let len = text.chars().count();
+            if len < self.min_paragraph_len {
+                continue;
+            }
+
+            let commas = text.matches([',', '،', '、', '，']).count();
+            let score = 1.0 + commas as f64 + (len as f64 / 100.0).min(3.0);
+
+            for (level, ancestor) in node.ancestors().take(3).enumerate() {
+                let Some(ancestor_elem) = ancestor.as_element() else {
+                    break;
+                };
+
+                let i = *index.entry(&*ancestor.0 as *const Node).or_insert_with(|| {
+                    let initial = tag_score(ancestor_elem) + class_weight(ancestor_elem);
+                    candidates.push((ancestor.clone(), initial));
+                    candidates.len() - 1
+                });
+
+                candidates[i].1 += score / (level + 1) as f64;
+            }
+        }
+
+        let top = candidates
+            .into_iter()
+            .map(|(node, score)| {
+                let score = score * (1.0 - link_density(&node));
+                (node, score)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(node, _)| node);
+
+        top.or_else(|| {
+            document
+                .select_first("body")
+                .map(|body| body.as_node().clone())
+                .filter(|body| !normalized_text(body).is_empty())
+        })
+    }
+
+    /// The top candidate and the siblings that look like they are part of the same article,
+    /// like paragraphs split into several containers.
+    fn content_roots(&self, top: &NodeRef) -> Vec<NodeRef> {
+        let Some(parent) = top.parent() else {
+            return vec![top.clone()];
+        };
+
+        let top_score = self.node_score(top);
+        let threshold = (top_score * 0.2).max(10.0);
+
+        parent
+            .children()
+            .filter(|sibling| {
+                if sibling == top {
+                    return true;
+                }
+
+                let Some(elem) = sibling.as_element() else {
+                    return false;
+                };
+
+                if is_excluded(sibling) {
+                    return false;
+                }
+
+                if self.node_score(sibling) >= threshold {
+                    return true;
+                }
+
+                if &*elem.name.local == "p" {
+                    let text = normalized_text(sibling);
+                    let len = text.chars().count();
+                    let link_density = link_density(sibling);
+
+                    return (len > 80 && link_density < 0.25)
+                        || (len > 0 && link_density == 0.0 && text.contains(". "));
+                }
+
+                false
+            })
+            .collect()
+    }
+
+    /// The score of a single element from its own paragraphs, used to compare siblings.
+    fn node_score(&self, node: &NodeRef) -> f64 {
+        let Some(elem) = node.as_element() else {
+            return 0.0;
+        };
+
+        let mut score = tag_score(elem) + class_weight(elem);
+
+        for paragraph in node.inclusive_descendants() {
+            let Some(paragraph_elem) = paragraph.as_element() else {
+                continue;
+            };
+
+            if !is_paragraph(&paragraph, paragraph_elem) {
+                continue;
+            }
+
+            let text = normalized_text(&paragraph);
+            let len = text.chars().count();
+            if len >= self.min_paragraph_len {
+                score += 1.0 + text.matches(',').count() as f64 + (len as f64 / 100.0).min(3.0);
+            }
+        }
+
+        score * (1.0 - link_density(node))
+    }
+
+    /// Whether a container inside the content looks like boilerplate: mostly links,
+    /// or little text spread over many elements like an image gallery or a button bar.
+    fn is_boilerplate(&self, node: &NodeRef, elem: &ElementData) -> bool {
+        if !CLEANED_TAGS.contains(&&*elem.name.local) {
+            return false;
+        }
+
+        if link_density(node) > self.max_link_density {
+            return true;
+        }
+
+        let text = normalized_text(node);
+        if text.matches(',').count() >= 10 {
+            return false;
+        }
+
+        let elements = node.descendants().elements().count();
+        let text_density = text.chars().count() as f64 / (elements + 1) as f64;
+
+        text_density < self.min_text_density
+    }
+
+    fn collect_text(
+        &self,
+        node: &NodeRef,
+        is_root: bool,
+        paragraphs: &mut Vec<String>,
+        current: &mut String,
+    ) {
+        match node.data() {
+            NodeData::Text(text) => {
+                for word in text.borrow().split_whitespace() {
+                    if !current.is_empty() {
+                        current.push(' ');
+                    }
+                    current.push_str(word);
+                }
+            }
+            NodeData::Element(elem) => {
+                if is_skipped(elem) || (!is_root && self.is_boilerplate(node, elem)) {
+                    return;
+                }
+
+                let tag = &*elem.name.local;
+
+                if tag == "br" {
+                    flush(paragraphs, current);
+                    return;
+                }
+
+                let is_block = BLOCK_TAGS.contains(&tag);
+                if is_block {
+                    flush(paragraphs, current);
+                }
+
+                for child in node.children() {
+                    self.collect_text(&child, false, paragraphs, current);
+                }
+
+                if is_block {
+                    flush(paragraphs, current);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn flush(paragraphs: &mut Vec<String>, current: &mut String) {
+    let paragraph = current.trim();
+
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph.to_string());
+    }
+
+    current.clear();
+}
+
+/// Elements whose text is scored as a paragraph. A `div` or `section` without
+/// block children is used as a paragraph, as many sites don't use `<p>`.
+fn is_paragraph(node: &NodeRef, elem: &ElementData) -> bool {
+    match &*elem.name.local {
+        "p" | "pre" | "td" | "blockquote" => true,
+        "div" | "section" => !node.children().any(|child| {
+            child
+                .as_element()
+                .is_some_and(|child| BLOCK_TAGS.contains(&&*child.name.local))
+        }),
+        _ => false,
+    }
+}
+
+fn tag_score(elem: &ElementData) -> f64 {
+    match &*elem.name.local {
+        "article" | "main" => 10.0,
+        "div" => 5.0,
+        "section" | "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    }
+}
+
+/// The lowercase words of the class and id attributes.
+fn class_words(elem: &ElementData) -> Vec<String> {
+    let attributes = elem.attributes.borrow();
+
+    ["class", "id"]
+        .iter()
+        .filter_map(|name| attributes.get(*name))
+        .flat_map(|value| value.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn matches_word(words: &[String], patterns: &[&str]) -> bool {
+    words.iter().any(|word| {
+        patterns
+            .iter()
+            .any(|pattern| word == pattern || (pattern.len() > 3 && word.contains(pattern)))
+    })
+}
+
+fn class_weight(elem: &ElementData) -> f64 {
+    let words = class_words(elem);
+    let mut weight = 0.0;
+
+    if matches_word(&words, UNLIKELY_WORDS) {
+        weight -= 25.0;
+    }
+
+    if matches_word(&words, POSITIVE_WORDS) {
+        weight += 25.0;
+    }
+
+    weight
+}
+
+/// Whether the element and its descendants are never part of the content.
+fn is_skipped(elem: &ElementData) -> bool {
+    let tag = &*elem.name.local;
+
+    if SKIPPED_TAGS.contains(&tag) {
+        return true;
+    }
+
+    if matches!(tag, "html" | "body" | "article" | "main") {
+        return false;
+    }
+
+    let words = class_words(elem);
+    matches_word(&words, UNLIKELY_WORDS) && !matches_word(&words, POSITIVE_WORDS)
+}
+
+fn is_excluded(node: &NodeRef) -> bool {
+    node.inclusive_ancestors()
+        .filter_map(|ancestor| ancestor.into_element_ref())
+        .any(|elem| is_skipped(&elem))
+}
+
+fn normalized_text(node: &NodeRef) -> String {
+    node.text_contents()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Fraction of the text that is inside links.
+fn link_density(node: &NodeRef) -> f64 {
+    let len = normalized_text(node).chars().count();
+
+    if len == 0 {
+        return 0.0;
+    }
+
+    let link_len: usize = node
+        .descendants()
+        .filter(|descendant| {
+            descendant
+                .as_element()
+                .is_some_and(|elem| &*elem.name.local == "a")
+        })
+        .map(|link| normalized_text(&link).chars().count())
+        .sum();
+
+    link_len as f64 / len as f64
+}
+
+fn meta_content(document: &NodeRef, names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| {
+        document
+            .select(&format!("meta[property='{name}'], meta[name='{name}']"))
+            .ok()?
+            .find_map(|meta| {
+                meta.attributes
+                    .borrow()
+                    .get("content")
+                    .map(str::trim)
+                    .filter(|content| !content.is_empty())
+                    .map(str::to_string)
+            })
+    })
+}
+
+/// The title from the OpenGraph tags, otherwise `<title>` without the site name,
+/// otherwise the first `<h1>`.
+fn title(document: &NodeRef) -> Option<String> {
+    if let Some(title) = meta_content(document, &["og:title", "twitter:title"]) {
+        return Some(title);
+    }
+
+    let title = document
+        .select_first("title")
+        .map(|title| normalized_text(title.as_node()))
+        .filter(|title| !title.is_empty());
+
+    if let Some(title) = title {
+        let title = TITLE_SEPARATORS
+            .iter()
+            .find(|sep| title.contains(**sep))
+            .and_then(|sep| {
+                title
+                    .split(sep)
+                    .max_by_key(|part| part.split_whitespace().count())
+            })
+            .unwrap_or(&title)
+            .trim()
+            .to_string();
+
+        return Some(title);
+    }
+
+    document
+        .select_first("h1")
+        .map(|h1| normalized_text(h1.as_node()))
+        .filter(|h1| !h1.is_empty())
+}
+
+fn byline(document: &NodeRef) -> Option<String> {
+    let author = meta_content(document, &["author", "article:author"])
+        .filter(|author| !author.starts_with("http"));
+
+    if author.is_some() {
+        return author;
+    }
+
+    document
+        .descendants()
+        .filter_map(|node| node.into_element_ref())
+        .filter(|elem| {
+            let attributes = elem.attributes.borrow();
+
+            attributes.get("rel") == Some("author")
+                || attributes.get("itemprop") == Some("author")
+                || matches_word(&class_words(elem), &["byline", "author"])
+        })
+        .map(|elem| normalized_text(elem.as_node()))
+        .find(|text| !text.is_empty() && text.chars().count() < 100)
+        .map(|text| {
+            match text.get(..3) {
+                Some(prefix) if prefix.eq_ignore_ascii_case("by ") => &text[3..],
+                _ => &text,
+            }
+            .trim()
+            .to_string()
+        })
+}
+
+fn first_image(node: &NodeRef) -> Option<String> {
+    node.inclusive_descendants()
+        .filter_map(|node| node.into_element_ref())
+        .filter(|elem| &*elem.name.local == "img")
+        .find_map(|img| {
+            img.attributes
+                .borrow()
+                .get("src")
+                .map(str::trim)
+                .filter(|src| !src.is_empty() && !src.starts_with("data:"))
+                .map(str::to_string)
+        })
+}
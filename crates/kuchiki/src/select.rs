@@ -154,7 +154,7 @@ impl AsRef<str> for AttrValue {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
-pub struct LocalName(html5ever::LocalName);
+pub struct LocalName(pub(crate) html5ever::LocalName);
 impl ToCss for LocalName {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result
     where
@@ -419,6 +419,14 @@ impl Selector {
     #[inline]
     #[must_use]
     pub fn matches(&self, element: &NodeDataRef<ElementData>) -> bool {
+        self.matches_element(element)
+    }
+
+    /// Returns whether any implementation of a selectors element matches this selector.
+    pub(crate) fn matches_element<E>(&self, element: &E) -> bool
+    where
+        E: selectors::Element<Impl = KuchikiSelectors>,
+    {
         let mut cache = NthIndexCache::default();
         let mut context = matching::MatchingContext::new(
             matching::MatchingMode::Normal,
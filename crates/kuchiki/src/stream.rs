@@ -0,0 +1,861 @@
+/**
+ * @file stream.rs
+ * @author Krisna Pranav
+ * @brief stream
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::rc::Rc;
+
+use html5ever::tendril::StrTendril;
+use html5ever::tokenizer::states::RawKind;
+use html5ever::tokenizer::{
+    BufferQueue, Tag, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer, TokenizerOpts,
+};
+use html5ever::{LocalName, Namespace, QualName};
+use selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstraint};
+use selectors::{matching, OpaqueElement};
+use url::Url;
+
+use crate::attributes::{self, Attributes, ExpandedName};
+use crate::select::{
+    AttrValue, KuchikiSelectors, LocalName as SelectorName, PseudoClass, PseudoElement, Selectors,
+};
+use crate::{Error, Result};
+
+/// Elements that never have content or an end tag.
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Attributes holding a URL that are resolved against the base URL when rewriting.
+const URL_ATTRIBUTES: &[&str] = &[
+    "action",
+    "background",
+    "cite",
+    "data",
+    "formaction",
+    "href",
+    "poster",
+    "src",
+];
+
+/// Selectors that depend on later siblings or on the content of an element,
+/// which is unknown when the start tag is streamed.
+const UNSUPPORTED_PSEUDO_CLASSES: &[&str] = &[
+    ":last-child",
+    ":last-of-type",
+    ":only-child",
+    ":only-of-type",
+    ":nth-last-child",
+    ":nth-last-of-type",
+    ":empty",
+    ":has(",
+];
+
+fn compile(selectors: &str) -> Result<Selectors> {
+    let compiled = Selectors::compile(selectors)?;
+    let serialized = compiled.to_string();
+
+    if UNSUPPORTED_PSEUDO_CLASSES
+        .iter()
+        .any(|pseudo| serialized.contains(pseudo))
+    {
+        return Err(Error::CssParseError);
+    }
+
+    Ok(compiled)
+}
+
+fn raw_kind(name: &str) -> Option<RawKind> {
+    match name {
+        "script" => Some(RawKind::ScriptData),
+        "style" | "xmp" | "iframe" | "noembed" | "noframes" | "noscript" => Some(RawKind::Rawtext),
+        "title" | "textarea" => Some(RawKind::Rcdata),
+        _ => None,
+    }
+}
+
+/// Open elements that are closed by a start tag without an end tag, like `<li>` closing
+/// the previous `<li>`. This approximates the tree builder for the common cases.
+fn implied_closes(name: &str) -> &'static [&'static str] {
+    match name {
+        "li" => &["li", "p"],
+        "dt" | "dd" => &["dt", "dd", "p"],
+        "option" => &["option"],
+        "tr" => &["tr", "td", "th"],
+        "td" | "th" => &["td", "th"],
+        "thead" | "tbody" | "tfoot" => &["tr", "td", "th", "thead", "tbody", "tfoot"],
+        "address" | "article" | "aside" | "blockquote" | "div" | "dl" | "fieldset" | "footer"
+        | "form" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "header" | "hr" | "main" | "nav"
+        | "ol" | "p" | "pre" | "section" | "table" | "ul" => &["p"],
+        _ => &[],
+    }
+}
+
+/// An element matched while streaming.
+#[derive(Debug, Clone)]
+pub struct StreamMatch {
+    /// The local name of the element.
+    pub name: String,
+
+    /// The attributes of the element, with URLs already rewritten by a [`Rewriter`].
+    pub attributes: Attributes,
+
+    /// The text content of the element. Empty for void elements like `<meta>`.
+    pub text: String,
+}
+
+#[derive(Debug)]
+struct ElementInfo {
+    name: LocalName,
+    attributes: Attributes,
+}
+
+struct Frame {
+    /// `None` for the document.
+    element: Option<Rc<ElementInfo>>,
+
+    /// The element children seen so far. The last one may still be open.
+    children: Vec<Rc<ElementInfo>>,
+
+    /// The handlers whose selectors matched the element.
+    matched: Vec<usize>,
+
+    /// The text content, only collected if a handler matched.
+    text: Option<String>,
+}
+
+/// An element as seen by the selectors: an open element or an earlier sibling of one.
+/// It is `frames[level].children[index]`.
+#[derive(Clone, Copy)]
+struct StreamElement<'a> {
+    frames: &'a [Frame],
+    level: usize,
+    index: usize,
+}
+
+impl std::fmt::Debug for StreamElement<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "<{}>", self.info().name)
+    }
+}
+
+impl StreamElement<'_> {
+    fn info(&self) -> &ElementInfo {
+        &self.frames[self.level].children[self.index]
+    }
+}
+
+impl selectors::Element for StreamElement<'_> {
+    type Impl = KuchikiSelectors;
+
+    fn opaque(&self) -> OpaqueElement {
+        OpaqueElement::new(self.info())
+    }
+
+    fn is_html_slot_element(&self) -> bool {
+        false
+    }
+
+    fn parent_node_is_shadow_root(&self) -> bool {
+        false
+    }
+
+    fn containing_shadow_host(&self) -> Option<Self> {
+        None
+    }
+
+    fn parent_element(&self) -> Option<Self> {
+        // the frame at `level` is the element that was opened last at `level - 1`
+        let level = self.level.checked_sub(1)?;
+
+        Some(StreamElement {
+            frames: self.frames,
+            level,
+            index: self.frames[level].children.len() - 1,
+        })
+    }
+
+    fn prev_sibling_element(&self) -> Option<Self> {
+        Some(StreamElement {
+            frames: self.frames,
+            level: self.level,
+            index: self.index.checked_sub(1)?,
+        })
+    }
+
+    fn next_sibling_element(&self) -> Option<Self> {
+        None
+    }
+
+    fn is_empty(&self) -> bool {
+        false
+    }
+
+    fn is_root(&self) -> bool {
+        self.level == 0
+    }
+
+    fn is_html_element_in_html_document(&self) -> bool {
+        true
+    }
+
+    fn has_local_name(&self, name: &SelectorName) -> bool {
+        self.info().name == name.0
+    }
+
+    fn has_namespace(&self, namespace: &Namespace) -> bool {
+        *namespace == ns!(html)
+    }
+
+    fn is_part(&self, _name: &SelectorName) -> bool {
+        false
+    }
+
+    fn imported_part(&self, _: &SelectorName) -> Option<SelectorName> {
+        None
+    }
+
+    fn is_pseudo_element(&self) -> bool {
+        false
+    }
+
+    fn is_same_type(&self, other: &Self) -> bool {
+        self.info().name == other.info().name
+    }
+
+    fn is_link(&self) -> bool {
+        let info = self.info();
+
+        matches!(
+            info.name,
+            local_name!("a") | local_name!("area") | local_name!("link")
+        ) && info.attributes.contains(local_name!("href"))
+    }
+
+    fn has_id(&self, id: &SelectorName, case_sensitivity: CaseSensitivity) -> bool {
+        self.info()
+            .attributes
+            .get(local_name!("id"))
+            .is_some_and(|id_attr| case_sensitivity.eq(id.0.as_bytes(), id_attr.as_bytes()))
+    }
+
+    fn has_class(&self, name: &SelectorName, case_sensitivity: CaseSensitivity) -> bool {
+        let name = name.0.as_bytes();
+
+        !name.is_empty()
+            && self
+                .info()
+                .attributes
+                .get(local_name!("class"))
+                .is_some_and(|class_attr| {
+                    class_attr
+                        .split_ascii_whitespace()
+                        .any(|class| case_sensitivity.eq(class.as_bytes(), name))
+                })
+    }
+
+    fn attr_matches(
+        &self,
+        ns: &NamespaceConstraint<&Namespace>,
+        local_name: &SelectorName,
+        operation: &AttrSelectorOperation<&AttrValue>,
+    ) -> bool {
+        let attrs = &self.info().attributes;
+
+        match *ns {
+            NamespaceConstraint::Any => attrs
+                .map
+                .iter()
+                .any(|(name, attr)| name.local == local_name.0 && operation.eval_str(&attr.value)),
+            NamespaceConstraint::Specific(ns_url) => attrs
+                .map
+                .get(&ExpandedName::new(ns_url, local_name.0.clone()))
+                .is_some_and(|attr| operation.eval_str(&attr.value)),
+        }
+    }
+
+    fn match_pseudo_element(
+        &self,
+        _pseudo: &PseudoElement,
+        _context: &mut matching::MatchingContext<KuchikiSelectors>,
+    ) -> bool {
+        true
+    }
+
+    fn match_non_ts_pseudo_class(
+        &self,
+        pseudo: &PseudoClass,
+        _context: &mut matching::MatchingContext<KuchikiSelectors>,
+    ) -> bool {
+        match pseudo {
+            PseudoClass::AnyLink | PseudoClass::Link => self.is_link(),
+            _ => false,
+        }
+    }
+
+    fn first_element_child(&self) -> Option<Self> {
+        None
+    }
+
+    fn apply_selector_flags(&self, _flags: matching::ElementSelectorFlags) {}
+}
+
+struct Handler<'h> {
+    selectors: Selectors,
+    callback: Box<dyn FnMut(StreamMatch) + 'h>,
+}
+
+struct StreamSink<'h> {
+    frames: Vec<Frame>,
+    handlers: Vec<Handler<'h>>,
+
+    drop: Option<Selectors>,
+    drop_comments: bool,
+    base_url: Option<Url>,
+    base_seen: bool,
+
+    /// The depth of the dropped element we are in, if any.
+    dropped_at: Option<usize>,
+
+    /// The serialized document, if rewriting.
+    output: Option<String>,
+}
+
+impl<'h> StreamSink<'h> {
+    fn new(output: Option<String>) -> Self {
+        Self {
+            frames: vec![Frame {
+                element: None,
+                children: Vec::new(),
+                matched: Vec::new(),
+                text: None,
+            }],
+            handlers: Vec::new(),
+            drop: None,
+            drop_comments: false,
+            base_url: None,
+            base_seen: false,
+            dropped_at: None,
+            output,
+        }
+    }
+
+    fn top_name(&self) -> Option<&LocalName> {
+        self.frames
+            .last()
+            .and_then(|frame| frame.element.as_ref())
+            .map(|element| &element.name)
+    }
+
+    fn emit(&mut self) -> Option<&mut String> {
+        if self.dropped_at.is_some() {
+            None
+        } else {
+            self.output.as_mut()
+        }
+    }
+
+    fn start_tag(&mut self, mut tag: Tag) -> TokenSinkResult<()> {
+        while let Some(top) = self.top_name() {
+            if implied_closes(&tag.name).contains(&&**top) {
+                self.pop();
+            } else {
+                break;
+            }
+        }
+
+        if &*tag.name == "base" && !self.base_seen {
+            self.base_seen = true;
+
+            if let Some(href) = tag.attrs.iter().find(|attr| &*attr.name.local == "href") {
+                let base = match &self.base_url {
+                    Some(base_url) => base_url.join(&href.value).ok(),
+                    None => Url::parse(&href.value).ok(),
+                };
+
+                if base.is_some() {
+                    self.base_url = base;
+                }
+            }
+        }
+
+        self.rewrite_urls(&mut tag);
+
+        let element = Rc::new(ElementInfo {
+            name: tag.name.clone(),
+            attributes: Attributes {
+                map: tag
+                    .attrs
+                    .iter()
+                    .map(|attr| {
+                        let QualName { prefix, ns, local } = attr.name.clone();
+                        (
+                            ExpandedName { ns, local },
+                            attributes::Attribute {
+                                prefix,
+                                value: attr.value.to_string(),
+                            },
+                        )
+                    })
+                    .collect(),
+            },
+        });
+
+        let level = self.frames.len() - 1;
+        self.frames[level].children.push(Rc::clone(&element));
+
+        let stream_element = StreamElement {
+            frames: &self.frames,
+            level,
+            index: self.frames[level].children.len() - 1,
+        };
+
+        let matched: Vec<usize> = self
+            .handlers
+            .iter()
+            .enumerate()
+            .filter(|(_, handler)| {
+                handler
+                    .selectors
+                    .0
+                    .iter()
+                    .any(|selector| selector.matches_element(&stream_element))
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        let dropped = self.dropped_at.is_none()
+            && self.drop.as_ref().is_some_and(|drop| {
+                drop.0
+                    .iter()
+                    .any(|selector| selector.matches_element(&stream_element))
+            });
+
+        if dropped {
+            self.dropped_at = Some(self.frames.len());
+        }
+
+        if let Some(out) = self.emit() {
+            write_start_tag(out, &tag);
+        }
+
+        let raw = raw_kind(&tag.name);
+        let is_void = VOID_TAGS.contains(&&*tag.name) || (tag.self_closing && raw.is_none());
+
+        if is_void {
+            for i in matched {
+                (self.handlers[i].callback)(StreamMatch {
+                    name: element.name.to_string(),
+                    attributes: element.attributes.clone(),
+                    text: String::new(),
+                });
+            }
+
+            if dropped {
+                self.dropped_at = None;
+            }
+
+            return TokenSinkResult::Continue;
+        }
+
+        let text = (!matched.is_empty()).then(String::new);
+        self.frames.push(Frame {
+            element: Some(element),
+            children: Vec::new(),
+            matched,
+            text,
+        });
+
+        match raw {
+            Some(kind) => TokenSinkResult::RawData(kind),
+            None => TokenSinkResult::Continue,
+        }
+    }
+
+    fn end_tag(&mut self, tag: Tag) {
+        let open = self.frames.iter().rposition(|frame| {
+            frame
+                .element
+                .as_ref()
+                .is_some_and(|element| element.name == tag.name)
+        });
+
+        // an end tag inside a dropped element is dropped, unless it closes an ancestor
+        // of the dropped element along with it
+        let emit = match (self.dropped_at, open) {
+            (None, _) => true,
+            (Some(dropped_at), Some(pos)) => pos < dropped_at,
+            (Some(_), None) => false,
+        };
+
+        if let Some(pos) = open {
+            while self.frames.len() > pos {
+                self.pop();
+            }
+        }
+
+        if emit {
+            if let Some(out) = self.output.as_mut() {
+                out.push_str("</");
+                out.push_str(&tag.name);
+                out.push('>');
+            }
+        }
+    }
+
+    fn pop(&mut self) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+
+        if self.dropped_at == Some(self.frames.len()) {
+            self.dropped_at = None;
+        }
+
+        let Some(element) = frame.element else {
+            return;
+        };
+
+        let text = frame.text.unwrap_or_default();
+
+        for i in frame.matched {
+            (self.handlers[i].callback)(StreamMatch {
+                name: element.name.to_string(),
+                attributes: element.attributes.clone(),
+                text: text.clone(),
+            });
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        for frame in &mut self.frames {
+            if let Some(frame_text) = &mut frame.text {
+                frame_text.push_str(text);
+            }
+        }
+
+        let raw = self
+            .top_name()
+            .and_then(|name| raw_kind(name))
+            .is_some_and(|kind| kind != RawKind::Rcdata);
+
+        if let Some(out) = self.emit() {
+            if raw {
+                out.push_str(text);
+            } else {
+                escape(out, text, false);
+            }
+        }
+    }
+
+    fn rewrite_urls(&self, tag: &mut Tag) {
+        let Some(base_url) = &self.base_url else {
+            return;
+        };
+
+        for attr in &mut tag.attrs {
+            let name = &*attr.name.local;
+
+            if URL_ATTRIBUTES.contains(&name) {
+                if let Some(url) = resolve(base_url, &attr.value) {
+                    attr.value = StrTendril::from(url);
+                }
+            } else if name == "srcset" {
+                let candidates: Vec<String> = attr
+                    .value
+                    .split(',')
+                    .map(|candidate| {
+                        let candidate = candidate.trim();
+                        let (url, descriptor) = candidate
+                            .split_once(char::is_whitespace)
+                            .unwrap_or((candidate, ""));
+
+                        let url = resolve(base_url, url).unwrap_or_else(|| url.to_string());
+
+                        if descriptor.is_empty() {
+                            url
+                        } else {
+                            format!("{url} {}", descriptor.trim())
+                        }
+                    })
+                    .collect();
+
+                attr.value = StrTendril::from(candidates.join(", "));
+            }
+        }
+    }
+}
+
+impl TokenSink for StreamSink<'_> {
+    type Handle = ();
+
+    fn process_token(&mut self, token: Token, _line_number: u64) -> TokenSinkResult<()> {
+        match token {
+            Token::TagToken(tag) => match tag.kind {
+                TagKind::StartTag => return self.start_tag(tag),
+                TagKind::EndTag => self.end_tag(tag),
+            },
+            Token::CharacterTokens(text) => self.text(&text),
+            Token::CommentToken(text) => {
+                let drop_comments = self.drop_comments;
+
+                if let Some(out) = self.emit().filter(|_| !drop_comments) {
+                    out.push_str("<!--");
+                    out.push_str(&text);
+                    out.push_str("-->");
+                }
+            }
+            Token::DoctypeToken(doctype) => {
+                if let Some(out) = self.emit() {
+                    out.push_str("<!DOCTYPE ");
+                    out.push_str(doctype.name.as_deref().unwrap_or("html"));
+                    out.push('>');
+                }
+            }
+            Token::NullCharacterToken | Token::EOFToken | Token::ParseError(_) => {}
+        }
+
+        TokenSinkResult::Continue
+    }
+
+    fn end(&mut self) {
+        while self.frames.len() > 1 {
+            self.pop();
+        }
+    }
+}
+
+fn resolve(base_url: &Url, value: &str) -> Option<String> {
+    let value = value.trim();
+
+    // fragments point into the document itself, wherever it is served from
+    if value.is_empty() || value.starts_with('#') {
+        return None;
+    }
+
+    base_url.join(value).ok().map(String::from)
+}
+
+fn write_start_tag(out: &mut String, tag: &Tag) {
+    out.push('<');
+    out.push_str(&tag.name);
+
+    for attr in &tag.attrs {
+        out.push(' ');
+
+        if let Some(prefix) = &attr.name.prefix {
+            out.push_str(prefix);
+            out.push(':');
+        }
+
+        out.push_str(&attr.name.local);
+        out.push_str("=\"");
+        escape(out, &attr.value, true);
+        out.push('"');
+    }
+
+    if tag.self_closing {
+        out.push('/');
+    }
+
+    out.push('>');
+}
+
+fn escape(out: &mut String, text: &str, attr_mode: bool) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '\u{00A0}' => out.push_str("&nbsp;"),
+            '"' if attr_mode => out.push_str("&quot;"),
+            '<' if !attr_mode => out.push_str("&lt;"),
+            '>' if !attr_mode => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Feeds chunks of a document to the tokenizer.
+struct Stream<'h> {
+    tokenizer: Tokenizer<StreamSink<'h>>,
+    input: BufferQueue,
+}
+
+impl<'h> Stream<'h> {
+    fn new(output: Option<String>) -> Self {
+        Self {
+            tokenizer: Tokenizer::new(StreamSink::new(output), TokenizerOpts::default()),
+            input: BufferQueue::new(),
+        }
+    }
+
+    fn sink(&mut self) -> &mut StreamSink<'h> {
+        &mut self.tokenizer.sink
+    }
+
+    fn on<F>(&mut self, selectors: &str, callback: F) -> Result<()>
+    where
+        F: FnMut(StreamMatch) + 'h,
+    {
+        let selectors = compile(selectors)?;
+
+        self.sink().handlers.push(Handler {
+            selectors,
+            callback: Box::new(callback),
+        });
+
+        Ok(())
+    }
+
+    fn feed(&mut self, chunk: &str) {
+        self.input.push_back(StrTendril::from_slice(chunk));
+        let _ = self.tokenizer.feed(&mut self.input);
+    }
+
+    fn finish(mut self) -> Option<String> {
+        let _ = self.tokenizer.feed(&mut self.input);
+        self.tokenizer.end();
+        self.tokenizer.sink.output.take()
+    }
+}
+
+/// Runs CSS selectors against the tokens of a document without building a tree,
+/// for when only a few elements like links or meta tags are needed from large pages.
+///
+/// Selectors can only look at the ancestors and earlier siblings of an element, so
+/// `:last-child`, `:only-child`, `:nth-last-child`, `:empty` and `:has()` are rejected.
+/// The tree is approximated from the tags, with implied end tags for the common
+/// cases like `<p>` and `<li>`.
+///
+/// ```rust
+/// let mut links = Vec::new();
+///
+/// kuchiki::Streamer::new()
+///     .on("a[href]", |m| links.push(m.attributes.get("href").unwrap().to_string()))
+///     .unwrap()
+///     .run("<p><a href='/a'>A</a><a>B</a>");
+/// ```
+pub struct Streamer<'h> {
+    stream: Stream<'h>,
+}
+
+impl Default for Streamer<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'h> Streamer<'h> {
+    /// Create a streamer without any handlers.
+    pub fn new() -> Self {
+        Self {
+            stream: Stream::new(None),
+        }
+    }
+
+    /// Call `callback` for every element matching the selectors, when the element ends.
+    pub fn on<F>(mut self, selectors: &str, callback: F) -> Result<Self>
+    where
+        F: FnMut(StreamMatch) + 'h,
+    {
+        self.stream.on(selectors, callback)?;
+        Ok(self)
+    }
+
+    /// Feed the next chunk of the document.
+    pub fn feed(&mut self, chunk: &str) {
+        self.stream.feed(chunk);
+    }
+
+    /// Signal the end of the document, which ends all open elements.
+    pub fn finish(self) {
+        self.stream.finish();
+    }
+
+    /// Stream a whole document.
+    pub fn run(mut self, html: &str) {
+        self.feed(html);
+        self.finish();
+    }
+}
+
+/// Rewrites a document while streaming it, writing the serialized HTML directly
+/// instead of building a tree. Elements matching the drop selectors are removed
+/// with their content, and URLs in attributes can be resolved against a base URL.
+///
+/// The same selector restrictions as for [`Streamer`] apply.
+pub struct Rewriter<'h> {
+    stream: Stream<'h>,
+}
+
+impl Default for Rewriter<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'h> Rewriter<'h> {
+    /// Create a rewriter that keeps the document as is.
+    pub fn new() -> Self {
+        Self {
+            stream: Stream::new(Some(String::new())),
+        }
+    }
+
+    /// Remove the elements matching the selectors, e.g. `script, style`.
+    pub fn drop_elements(mut self, selectors: &str) -> Result<Self> {
+        self.stream.sink().drop = Some(compile(selectors)?);
+        Ok(self)
+    }
+
+    /// Remove all comments.
+    pub fn drop_comments(mut self) -> Self {
+        self.stream.sink().drop_comments = true;
+        self
+    }
+
+    /// Resolve relative URLs in `href`, `src`, `srcset` and similar attributes against
+    /// `base_url`, or against the first `<base href>` of the document.
+    pub fn base_url(mut self, base_url: Url) -> Self {
+        self.stream.sink().base_url = Some(base_url);
+        self
+    }
+
+    /// Call `callback` for every element matching the selectors, like [`Streamer::on`].
+    pub fn on<F>(mut self, selectors: &str, callback: F) -> Result<Self>
+    where
+        F: FnMut(StreamMatch) + 'h,
+    {
+        self.stream.on(selectors, callback)?;
+        Ok(self)
+    }
+
+    /// Feed the next chunk of the document and return the HTML rewritten so far.
+    pub fn feed(&mut self, chunk: &str) -> String {
+        self.stream.feed(chunk);
+
+        self.stream
+            .sink()
+            .output
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Signal the end of the document and return the rest of the rewritten HTML.
+    pub fn finish(self) -> String {
+        self.stream.finish().unwrap_or_default()
+    }
+
+    /// Rewrite a whole document.
+    pub fn rewrite(mut self, html: &str) -> String {
+        let mut res = self.feed(html);
+        res.push_str(&self.finish());
+        res
+    }
+}
@@ -200,7 +200,7 @@ fn select_xpath() {
 ";
 
     let document = parse_html().one(html);
-    let nodes: Vec<_> = document.select_xpath("/html/body/p").collect();
+    let nodes: Vec<_> = document.select_xpath("/html/body/p").unwrap().collect();
 
     assert_eq!(nodes.len(), 3);
 
@@ -216,7 +216,7 @@ fn select_xpath() {
     assert_eq!(node.name.local.as_ref(), "p");
     assert_eq!(node.attributes.borrow().get("class"), Some("foo"));
 
-    let nodes: Vec<_> = document.select_xpath("/html/body/p[2]").collect();
+    let nodes: Vec<_> = document.select_xpath("/html/body/p[2]").unwrap().collect();
 
     assert_eq!(nodes.len(), 1);
 
@@ -224,7 +224,7 @@ fn select_xpath() {
     assert_eq!(node.name.local.as_ref(), "p");
     assert_eq!(node.attributes.borrow().get("class"), None);
 
-    let nodes: Vec<_> = document.select_xpath("/html/head/title").collect();
+    let nodes: Vec<_> = document.select_xpath("/html/head/title").unwrap().collect();
     assert_eq!(nodes.len(), 1);
 }
 
@@ -236,16 +236,32 @@ fn xpath_wildcard() {
 
     let document = parse_html().one(html);
 
-    let nodes: Vec<_> = document.select_xpath("/title").collect();
+    let nodes: Vec<_> = document.select_xpath("/title").unwrap().collect();
     assert_eq!(nodes.len(), 0);
 
-    let nodes: Vec<_> = document.select_xpath("./title").collect();
+    let nodes: Vec<_> = document.select_xpath(".//title").unwrap().collect();
     assert_eq!(nodes.len(), 1);
 
-    let nodes: Vec<_> = document.select_xpath("/./title").collect();
+    let nodes: Vec<_> = document.select_xpath("/.//title").unwrap().collect();
     assert_eq!(nodes.len(), 1);
 }
 
+#[test]
+fn select_xpath_root_and_errors() {
+    let document = parse_html().one("<p>Foo</p>");
+
+    let nodes: Vec<_> = document.select_xpath("/html[1]").unwrap().collect();
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(nodes[0].name.local.as_ref(), "html");
+
+    let html = document.select_first("html").unwrap();
+    let nodes: Vec<_> = html.as_node().select_xpath("self::*[1]").unwrap().collect();
+    assert_eq!(nodes.len(), 1);
+
+    assert!(document.select_xpath("//p[unknown()]").is_err());
+    assert!(document.select_xpath("//p[").is_err());
+}
+
 #[test]
 fn xpath_contains() {
     let html = r#"
@@ -255,17 +271,464 @@ fn xpath_contains() {
     let document = parse_html().one(html);
 
     let nodes: Vec<_> = document
-        .select_xpath("./p[contains(@class, 'foo')]")
+        .select_xpath(".//p[contains(@class, 'foo')]")
+        .unwrap()
         .collect();
     assert_eq!(nodes.len(), 1);
 
     let nodes: Vec<_> = document
-        .select_xpath("./p[contains(@class, 'bar')]")
+        .select_xpath(".//p[contains(@class, 'bar')]")
+        .unwrap()
         .collect();
     assert_eq!(nodes.len(), 1);
 
     let nodes: Vec<_> = document
-        .select_xpath("./p[contains(@class, 'baz')]")
+        .select_xpath(".//p[contains(@class, 'baz')]")
+        .unwrap()
         .collect();
     assert_eq!(nodes.len(), 0);
 }
+
+#[test]
+fn xpath_paths() {
+    let html = r#"
+<div id="main">
+    <a href="/one">One</a>
+    <div class="inner"><a href="/two">Two</a></div>
+    <!-- comment -->
+</div>
+<a href="/three">Three</a>
+"#;
+
+    let document = parse_html().one(html);
+
+    let divs = document.xpath("//div").unwrap();
+    assert_eq!(divs.nodes().len(), 2);
+
+    let hrefs = document.xpath("//div[@id='main']//a/@href").unwrap();
+    assert_eq!(hrefs.strings(), vec!["/one", "/two"]);
+
+    let texts = document.xpath("//a/text()").unwrap();
+    assert_eq!(texts.strings(), vec!["One", "Two", "Three"]);
+
+    let parent = document.xpath("//a[@href='/two']/../@class").unwrap();
+    assert_eq!(parent.string(), "inner");
+
+    let last = document.xpath("(//a)[last()]").unwrap();
+    assert_eq!(last.string(), "Three");
+
+    let count = document.xpath("count(//a) + count(//comment())").unwrap();
+    assert_eq!(count, crate::XPathValue::Number(4.0));
+}
+
+#[test]
+fn xpath_predicates() {
+    let html = r#"
+<ul>
+    <li class="item first">alpha</li>
+    <li class="item">beta</li>
+    <li class="other">  gamma   ray </li>
+</ul>
+"#;
+
+    let document = parse_html().one(html);
+
+    let strings = |expr: &str| document.xpath(expr).unwrap().strings();
+
+    assert_eq!(strings("//li[starts-with(@class, 'item')]").len(), 2);
+    assert_eq!(strings("//li[@class='item']"), vec!["beta"]);
+    assert_eq!(
+        strings("//li[contains(@class, 'item') and not(contains(@class, 'first'))]"),
+        vec!["beta"]
+    );
+    assert_eq!(strings("//li[. = 'alpha' or position() = 2]").len(), 2);
+    assert_eq!(
+        strings("//li[last()]/preceding-sibling::li[1]"),
+        vec!["beta"]
+    );
+    assert_eq!(strings("normalize-space(//li[3])"), vec!["gamma ray"]);
+    assert_eq!(strings("string-length(//li[1])"), vec!["5"]);
+    assert_eq!(
+        strings("10000000000000000000 * 10"),
+        vec!["100000000000000000000"]
+    );
+    assert_eq!(strings("-0.5 div 2"), vec!["-0.25"]);
+    assert_eq!(strings("0 * -1"), vec!["0"]);
+    assert_eq!(strings("number(' -1.5 ')"), vec!["-1.5"]);
+    assert_eq!(strings("number('.5') + number('2.')"), vec!["2.5"]);
+    assert_eq!(strings("number('1e3')"), vec!["NaN"]);
+    assert_eq!(strings("number('inf')"), vec!["NaN"]);
+    assert_eq!(strings("number('NaN')"), vec!["NaN"]);
+    assert_eq!(strings("number('+1')"), vec!["NaN"]);
+    assert_eq!(strings("number('.')"), vec!["NaN"]);
+    assert_eq!(
+        strings("//li[2]/@class | //li[1] | //li[1]/@class"),
+        vec!["alpha", "item first", "item"]
+    );
+}
+
+#[test]
+fn xpath_errors() {
+    let document = parse_html().one("<p>Test</p>");
+
+    assert!(matches!(
+        document.xpath("//p[unknown(@class)]"),
+        Err(crate::Error::XPathParseError { .. })
+    ));
+    assert!(matches!(
+        document.xpath("//p[@class"),
+        Err(crate::Error::XPathParseError { .. })
+    ));
+    assert!(matches!(
+        document.xpath("//p[contains(@class)]"),
+        Err(crate::Error::XPathParseError { .. })
+    ));
+    assert!(matches!(
+        document.xpath("string(//p)/b"),
+        Err(crate::Error::XPathEvalError(_))
+    ));
+}
+
+#[test]
+fn readability_news() {
+    let document = parse_html().one(include_str!("../test_data/readability/news.html"));
+    let article = crate::Readability::default().extract(&document).unwrap();
+
+    assert_eq!(
+        article.title.as_deref(),
+        Some("City council approves new bike lanes")
+    );
+    assert_eq!(article.byline.as_deref(), Some("Jane Doe"));
+    assert_eq!(
+        article.lead_image.as_deref(),
+        Some("https://example.com/images/bike-lanes.jpg")
+    );
+
+    assert_eq!(
+        article.paragraphs,
+        vec![
+            "By Jane Doe",
+            "The city council voted on Tuesday to approve a network of protected bike lanes, ending a debate that has lasted for more than two years.",
+            "The plan adds forty kilometres of lanes, separated from traffic by concrete curbs, and connects the university, the train station and the harbour.",
+            "Construction will start in the spring, and the first section, along the river, is expected to open before the end of the year.",
+            "Reactions",
+            "Cycling groups welcomed the decision, while some shop owners worried about the loss of parking spaces in the city centre.",
+        ]
+    );
+    assert!(article.text().contains("two years.\n\nThe plan"));
+}
+
+#[test]
+fn readability_blog() {
+    let document = parse_html().one(include_str!("../test_data/readability/blog.html"));
+    let article = crate::Readability::default().extract(&document).unwrap();
+
+    assert_eq!(
+        article.title.as_deref(),
+        Some("Home-made sourdough, step by step")
+    );
+    assert_eq!(article.byline.as_deref(), Some("Sam Baker"));
+    assert_eq!(article.lead_image.as_deref(), Some("/img/loaf.jpg"));
+
+    let text = article.text();
+    assert!(text.starts_with("Sourdough takes patience"));
+    assert!(text.contains("doubled in size.\n\nMix the flour"));
+    assert!(text.ends_with("in a very hot oven."));
+
+    for boilerplate in [
+        "Recipes",
+        "newsletter",
+        "Archive",
+        "Great recipe",
+        "rye flour",
+    ] {
+        assert!(!text.contains(boilerplate), "{boilerplate}");
+    }
+}
+
+#[test]
+fn readability_empty() {
+    let document = parse_html().one("<html><body><nav>Home</nav></body></html>");
+    assert!(crate::Readability::default().extract(&document).is_none());
+}
+
+#[test]
+fn metadata() {
+    use crate::metadata::{Property, Source};
+
+    let document = parse_html().one(include_str!("../test_data/metadata/recipe.html"));
+    let metadata = crate::Metadata::extract(&document);
+
+    assert_eq!(metadata.items.len(), 4);
+
+    let recipe = &metadata.items[0];
+    assert_eq!(recipe.source, Source::JsonLd);
+    assert_eq!(recipe.types, vec!["Recipe"]);
+    assert_eq!(
+        recipe.id.as_deref(),
+        Some("https://example.com/banana-bread#recipe")
+    );
+    assert_eq!(recipe.text("recipeYield"), Some("8"));
+    assert_eq!(recipe.properties["recipeIngredient"].len(), 2);
+    let author = recipe.get("author").and_then(Property::as_item).unwrap();
+    assert_eq!(author.text("name"), Some("Alex Baker"));
+    assert!(recipe.is_a("HowTo"));
+    assert!(recipe.is_a("CreativeWork"));
+    assert!(!recipe.is_a("Article"));
+
+    assert_eq!(metadata.items[1].text("name"), Some("Banana bread"));
+
+    let product = &metadata.items[2];
+    assert_eq!(product.source, Source::Microdata);
+    assert_eq!(product.types, vec!["Product"]);
+    assert_eq!(product.text("name"), Some("Loaf tin"));
+    assert_eq!(product.text("image"), Some("/tin.jpg"));
+    assert_eq!(product.text("url"), Some("https://example.com/tin"));
+    let offer = product.get("offers").and_then(Property::as_item).unwrap();
+    assert_eq!(offer.text("price"), Some("12.50"));
+    assert_eq!(offer.text("priceCurrency"), Some("EUR"));
+    assert_eq!(
+        offer.text("availability"),
+        Some("https://schema.org/InStock")
+    );
+    assert!(!product.properties.contains_key("price"));
+
+    let event = &metadata.items[3];
+    assert_eq!(event.source, Source::Rdfa);
+    assert_eq!(event.text("name"), Some("Baking class"));
+    assert_eq!(event.text("startDate"), Some("2024-12-01T10:00"));
+    let place = event.get("location").and_then(Property::as_item).unwrap();
+    assert_eq!(place.types, vec!["Place"]);
+    assert_eq!(place.text("name"), Some("Community kitchen"));
+
+    let people: Vec<_> = metadata.items_of_type("Person").collect();
+    assert_eq!(people.len(), 1);
+    assert_eq!(metadata.items_of_type("Intangible").count(), 2);
+
+    let types = metadata.schema_types();
+    for ty in [
+        "Recipe", "HowTo", "Offer", "Rating", "Event", "Place", "Thing",
+    ] {
+        assert!(types.iter().any(|t| t == ty), "{ty}");
+    }
+
+    assert_eq!(metadata.meta("og:title"), Some("Best banana bread"));
+    assert_eq!(metadata.meta["og:image"].len(), 2);
+    assert_eq!(metadata.meta("twitter:card"), Some("summary_large_image"));
+    assert!(metadata.meta("description").is_none());
+}
+
+#[test]
+fn schema_org_hierarchy() {
+    use crate::metadata::{is_subtype, supertypes};
+
+    assert_eq!(
+        supertypes("BlogPosting"),
+        vec![
+            "BlogPosting",
+            "SocialMediaPosting",
+            "Article",
+            "CreativeWork",
+            "Thing"
+        ]
+    );
+    assert_eq!(
+        supertypes("Restaurant"),
+        vec![
+            "Restaurant",
+            "FoodEstablishment",
+            "LocalBusiness",
+            "Organization",
+            "Place",
+            "Thing"
+        ]
+    );
+    assert_eq!(supertypes("UnknownType"), vec!["UnknownType", "Thing"]);
+
+    assert!(is_subtype("Hotel", "Place"));
+    assert!(is_subtype("Thing", "Thing"));
+    assert!(!is_subtype("Person", "Organization"));
+}
+
+#[test]
+fn stream_select() {
+    let html = r#"<!DOCTYPE html>
+<html><head>
+<title>Streaming &amp; more</title>
+<meta property="og:title" content="Streamed">
+</head>
+<body>
+<ul class="nav"><li><a href="/home">Home</a><li><a href="/about">About</a></ul>
+<p>Intro <a href="/intro" rel="nofollow">link</a>
+<p>Second paragraph</p>
+<script>if (a < b) { document.write("<a href='/fake'>") }</script>
+</body></html>"#;
+
+    let mut title = String::new();
+    let mut links = Vec::new();
+    let mut items = Vec::new();
+    let mut paragraphs = Vec::new();
+    let mut meta = Vec::new();
+
+    let mut streamer = crate::Streamer::new()
+        .on("title", |m| title = m.text)
+        .unwrap()
+        .on("a[href]", |m| {
+            links.push(m.attributes.get("href").unwrap().to_string())
+        })
+        .unwrap()
+        .on("ul.nav > li:first-child a, li + li", |m| items.push(m.text))
+        .unwrap()
+        .on("body > p", |m| paragraphs.push(m.text.trim().to_string()))
+        .unwrap()
+        .on("head meta[property^='og:']", |m| {
+            meta.push(m.attributes.get("content").unwrap().to_string())
+        })
+        .unwrap();
+
+    // chunk boundaries in the middle of tags and entities
+    for chunk in html.as_bytes().chunks(7) {
+        streamer.feed(std::str::from_utf8(chunk).unwrap());
+    }
+    streamer.finish();
+
+    assert_eq!(title, "Streaming & more");
+    assert_eq!(links, vec!["/home", "/about", "/intro"]);
+    assert_eq!(items, vec!["Home", "About"]);
+    assert_eq!(paragraphs, vec!["Intro link", "Second paragraph"]);
+    assert_eq!(meta, vec!["Streamed"]);
+
+    assert!(crate::Streamer::new().on("li:last-child", |_| {}).is_err());
+    assert!(crate::Streamer::new().on("div:has(p)", |_| {}).is_err());
+    assert!(crate::Streamer::new().on("[[", |_| {}).is_err());
+}
+
+#[test]
+fn stream_rewrite() {
+    let html = r##"<html><head><style>p { color: red }</style><script src="app.js"></script></head>
+<body><!-- ad slot --><p class="x">Fish &amp; chips &lt;3<img src="img/a.png" srcset="a.png 1x, /b.png 2x"></p>
+<a href="../up?q=1&amp;r=2">Up</a> <a href="#top">Top</a> <a href="https://other.com/">Other</a>
+<div class="ad"><p>Buy now</p></div></body></html>"##;
+
+    let base = url::Url::parse("https://example.com/blog/post/").unwrap();
+    let rewritten = crate::Rewriter::new()
+        .drop_elements("script, style, .ad")
+        .unwrap()
+        .drop_comments()
+        .base_url(base)
+        .rewrite(html);
+
+    assert_eq!(
+        rewritten,
+        r##"<html><head></head>
+<body><p class="x">Fish &amp; chips &lt;3<img src="https://example.com/blog/post/img/a.png" srcset="https://example.com/blog/post/a.png 1x, https://example.com/b.png 2x"></p>
+<a href="https://example.com/blog/up?q=1&amp;r=2">Up</a> <a href="#top">Top</a> <a href="https://other.com/">Other</a>
+</body></html>"##
+    );
+
+    let with_base = crate::Rewriter::new()
+        .base_url(url::Url::parse("https://example.com/").unwrap())
+        .rewrite(r#"<base href="/docs/"><a href="intro.html">Intro</a>"#);
+    assert_eq!(
+        with_base,
+        r#"<base href="https://example.com/docs/"><a href="https://example.com/docs/intro.html">Intro</a>"#
+    );
+
+    // unclosed dropped elements are closed by the end tags of their ancestors
+    let unclosed = crate::Rewriter::new()
+        .drop_elements("p")
+        .unwrap()
+        .rewrite("<div><p>text</div><span>x</span>");
+    assert_eq!(unclosed, "<div></div><span>x</span>");
+
+    let nested = crate::Rewriter::new()
+        .drop_elements("nav")
+        .unwrap()
+        .rewrite("<section><nav><a>x</a></section>after");
+    assert_eq!(nested, "<section></section>after");
+
+    // unchanged documents round-trip
+    let plain = r#"<!DOCTYPE html><p title="a &quot;b&quot;">x<br/>y</p>"#;
+    assert_eq!(crate::Rewriter::new().rewrite(plain), plain);
+}
+
+#[test]
+fn links() {
+    use crate::{LinkExtractor, LinkKind, Rel};
+
+    let html = r##"<html><head>
+<base href="/docs/">
+<link rel="canonical" href="https://example.com/docs/page">
+<link rel="alternate" hreflang="de" href="/de/docs/page">
+<link rel="next" href="page?p=2">
+<link rel="stylesheet" href="style.css">
+</head><body>
+<a href="intro.html#setup">Getting   started</a>
+<a href="#top">Top</a>
+<a href="mailto:team@example.com">Mail</a>
+<a>No href</a>
+<a href="https://other.com/" rel="nofollow noopener">Other</a>
+<a href="intro.html"><img src="i.png" alt="Intro"></a>
+<a href="/forum/1" rel="ugc" title="Thread">  </a>
+<map><area href="map.html" alt="Map"></map>
+</body></html>"##;
+
+    let document = parse_html().one(html);
+    let base = url::Url::parse("https://example.com/blog/post").unwrap();
+    let links = LinkExtractor::new()
+        .base_url(base.clone())
+        .extract(&document);
+
+    assert_eq!(
+        links.base_url.as_ref().unwrap().as_str(),
+        "https://example.com/docs/"
+    );
+    assert_eq!(
+        links.canonical().unwrap().as_str(),
+        "https://example.com/docs/page"
+    );
+
+    let alternate = links.head_links(&Rel::Alternate).next().unwrap();
+    assert_eq!(alternate.url.as_str(), "https://example.com/de/docs/page");
+    assert_eq!(alternate.hreflang.as_deref(), Some("de"));
+    assert_eq!(
+        links.head_links(&Rel::Next).next().unwrap().url.as_str(),
+        "https://example.com/docs/page?p=2"
+    );
+
+    let anchors: Vec<_> = links
+        .anchors()
+        .map(|link| (link.url.as_str(), link.text.as_str(), link.position))
+        .collect();
+    assert_eq!(
+        anchors,
+        vec![
+            ("https://example.com/docs/intro.html", "Getting started", 4),
+            ("https://other.com/", "Other", 7),
+            ("https://example.com/forum/1", "Thread", 9),
+            ("https://example.com/docs/map.html", "Map", 10),
+        ]
+    );
+
+    let other = links.anchors().nth(1).unwrap();
+    assert!(other.has_rel(&Rel::Noopener));
+    assert!(other.is_nofollow());
+    assert!(links.anchors().nth(2).unwrap().is_nofollow());
+    assert!(!links.anchors().next().unwrap().is_nofollow());
+    assert_eq!(links.anchors().nth(3).unwrap().kind, LinkKind::Area);
+    assert_eq!(
+        links.links[3].rel,
+        vec![Rel::Other("stylesheet".to_string())]
+    );
+
+    let all = LinkExtractor::new()
+        .base_url(base)
+        .dedup(false)
+        .extract(&document);
+    assert_eq!(all.anchors().count(), 5);
+    assert_eq!(all.anchors().nth(2).unwrap().text, "Intro");
+
+    // without a document URL, only absolute links can be resolved
+    let absolute = LinkExtractor::new().extract(&document);
+    assert_eq!(absolute.links.len(), 2);
+}
@@ -0,0 +1,640 @@
+/**
+ * @file eval.rs
+ * @author Krisna Pranav
+ * @brief xpath[eval]
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::cell::OnceCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::parser::{ArithmeticOp, Axis, CompareOp, Expr, Function, NodeTest, Path, Step};
+use crate::tree::Node;
+use crate::{Error, NodeData, NodeRef, Result};
+
+/// A node in the result of an XPath expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum XPathNode {
+    /// A node of the tree.
+    Node(NodeRef),
+
+    /// An attribute of an element.
+    Attribute {
+        /// The element the attribute belongs to.
+        element: NodeRef,
+
+        /// The local name of the attribute.
+        name: String,
+
+        /// The value of the attribute.
+        value: String,
+    },
+}
+
+impl XPathNode {
+    /// The tree node, or the element an attribute belongs to.
+    pub fn node(&self) -> &NodeRef {
+        match self {
+            XPathNode::Node(node) => node,
+            XPathNode::Attribute { element, .. } => element,
+        }
+    }
+
+    /// The XPath string-value of the node: the text content of elements and documents,
+    /// and the value of text, comment and attribute nodes.
+    pub fn string_value(&self) -> String {
+        match self {
+            XPathNode::Node(node) => match node.data() {
+                NodeData::Text(text) | NodeData::Comment(text) => text.borrow().clone(),
+                NodeData::ProcessingInstruction(contents) => contents.borrow().1.clone(),
+                _ => node.text_contents(),
+            },
+            XPathNode::Attribute { value, .. } => value.clone(),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            XPathNode::Node(node) => node
+                .as_element()
+                .map(|elem| elem.name.local.to_string())
+                .unwrap_or_default(),
+            XPathNode::Attribute { name, .. } => name.clone(),
+        }
+    }
+
+    fn parent(&self) -> Option<NodeRef> {
+        match self {
+            XPathNode::Node(node) => node.parent(),
+            XPathNode::Attribute { element, .. } => Some(element.clone()),
+        }
+    }
+}
+
+/// The result of evaluating an XPath expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum XPathValue {
+    /// A set of nodes in document order.
+    Nodes(Vec<XPathNode>),
+
+    /// A boolean, like the result of `not(...)` or a comparison.
+    Boolean(bool),
+
+    /// A number, like the result of `count(...)`.
+    Number(f64),
+
+    /// A string, like the result of `normalize-space(...)`.
+    String(String),
+}
+
+impl XPathValue {
+    /// The nodes of a node-set. Other values have no nodes.
+    pub fn nodes(&self) -> &[XPathNode] {
+        match self {
+            XPathValue::Nodes(nodes) => nodes,
+            _ => &[],
+        }
+    }
+
+    /// The string-value of every node of a node-set, or the value converted to a string.
+    pub fn strings(&self) -> Vec<String> {
+        match self {
+            XPathValue::Nodes(nodes) => nodes.iter().map(XPathNode::string_value).collect(),
+            value => vec![value.string()],
+        }
+    }
+
+    /// The value converted with the XPath `string()` function.
+    /// A node-set converts to the string-value of its first node.
+    pub fn string(&self) -> String {
+        match self {
+            XPathValue::Nodes(nodes) => nodes
+                .first()
+                .map(XPathNode::string_value)
+                .unwrap_or_default(),
+            XPathValue::Boolean(b) => b.to_string(),
+            XPathValue::Number(n) => number_to_string(*n),
+            XPathValue::String(s) => s.clone(),
+        }
+    }
+
+    /// The value converted with the XPath `boolean()` function.
+    pub fn boolean(&self) -> bool {
+        match self {
+            XPathValue::Nodes(nodes) => !nodes.is_empty(),
+            XPathValue::Boolean(b) => *b,
+            XPathValue::Number(n) => *n != 0.0 && !n.is_nan(),
+            XPathValue::String(s) => !s.is_empty(),
+        }
+    }
+
+    /// The value converted with the XPath `number()` function.
+    pub fn number(&self) -> f64 {
+        match self {
+            XPathValue::Number(n) => *n,
+            XPathValue::Boolean(b) => f64::from(u8::from(*b)),
+            value => string_to_number(&value.string()),
+        }
+    }
+}
+
+fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".to_string()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if n == 0.0 {
+        // negative zero is "0" too
+        "0".to_string()
+    } else {
+        // `Display` never uses an exponent and drops the fraction of integral values
+        n.to_string()
+    }
+}
+
+/// Numbers are `-? (Digits ('.' Digits?)? | '.' Digits)` surrounded by optional
+/// whitespace; everything else, including exponents and `inf`, is NaN.
+fn string_to_number(s: &str) -> f64 {
+    let s = s.trim_matches([' ', '\t', '\r', '\n']);
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+
+    if (int.is_empty() && frac.is_empty()) || !is_digits(int) || !is_digits(frac) {
+        return f64::NAN;
+    }
+
+    s.parse().unwrap_or(f64::NAN)
+}
+
+struct Context<'a> {
+    node: XPathNode,
+    position: usize,
+    size: usize,
+    order: &'a DocumentOrder,
+}
+
+/// The pre-order index of every node of the tree, built on the first sort and shared by
+/// the whole evaluation.
+struct DocumentOrder {
+    root: NodeRef,
+    index: OnceCell<HashMap<*const Node, usize>>,
+}
+
+impl DocumentOrder {
+    fn new(node: &NodeRef) -> Self {
+        let mut root = node.clone();
+        while let Some(parent) = root.parent() {
+            root = parent;
+        }
+
+        Self {
+            root,
+            index: OnceCell::new(),
+        }
+    }
+
+    /// A key that sorts nodes in document order: the index of the tree node, followed by
+    /// one plus the attribute index for attributes.
+    fn key(&self, node: &XPathNode) -> (usize, usize) {
+        let index = self.index.get_or_init(|| {
+            self.root
+                .inclusive_descendants()
+                .enumerate()
+                .map(|(i, node)| (Rc::as_ptr(&node.0), i))
+                .collect()
+        });
+
+        let position = index
+            .get(&Rc::as_ptr(&node.node().0))
+            .copied()
+            .unwrap_or(usize::MAX);
+
+        match node {
+            XPathNode::Node(_) => (position, 0),
+            XPathNode::Attribute { element, name, .. } => {
+                let attribute = element
+                    .as_element()
+                    .and_then(|elem| {
+                        elem.attributes
+                            .borrow()
+                            .map
+                            .keys()
+                            .position(|key| key.local.as_ref() == name)
+                    })
+                    .unwrap_or_default();
+
+                (position, attribute + 1)
+            }
+        }
+    }
+
+    /// Sort the nodes in document order and remove duplicates.
+    fn sort(&self, nodes: Vec<XPathNode>) -> Vec<XPathNode> {
+        let mut keyed: Vec<_> = nodes
+            .into_iter()
+            .map(|node| (self.key(&node), node))
+            .collect();
+
+        keyed.sort_by_key(|(key, _)| *key);
+        keyed.dedup_by(|(a, _), (b, _)| a == b);
+
+        keyed.into_iter().map(|(_, node)| node).collect()
+    }
+}
+
+fn eval_error(message: impl Into<String>) -> Error {
+    Error::XPathEvalError(message.into())
+}
+
+pub(crate) fn evaluate(expr: &Expr, node: &NodeRef) -> Result<XPathValue> {
+    let order = DocumentOrder::new(node);
+    let ctx = Context {
+        node: XPathNode::Node(node.clone()),
+        position: 1,
+        size: 1,
+        order: &order,
+    };
+
+    eval(expr, &ctx)
+}
+
+fn eval(expr: &Expr, ctx: &Context) -> Result<XPathValue> {
+    Ok(match expr {
+        Expr::Or(a, b) => XPathValue::Boolean(eval(a, ctx)?.boolean() || eval(b, ctx)?.boolean()),
+        Expr::And(a, b) => XPathValue::Boolean(eval(a, ctx)?.boolean() && eval(b, ctx)?.boolean()),
+        Expr::Compare(op, a, b) => {
+            XPathValue::Boolean(compare(*op, &eval(a, ctx)?, &eval(b, ctx)?))
+        }
+        Expr::Arithmetic(op, a, b) => {
+            let (a, b) = (eval(a, ctx)?.number(), eval(b, ctx)?.number());
+
+            XPathValue::Number(match op {
+                ArithmeticOp::Add => a + b,
+                ArithmeticOp::Sub => a - b,
+                ArithmeticOp::Mul => a * b,
+                ArithmeticOp::Div => a / b,
+                ArithmeticOp::Mod => a % b,
+            })
+        }
+        Expr::Negate(expr) => XPathValue::Number(-eval(expr, ctx)?.number()),
+        Expr::Union(a, b) => {
+            let mut nodes = into_nodes(eval(a, ctx)?)?;
+            nodes.extend(into_nodes(eval(b, ctx)?)?);
+            XPathValue::Nodes(ctx.order.sort(nodes))
+        }
+        Expr::Path(path) => XPathValue::Nodes(eval_path(path, ctx)?),
+        Expr::Filter {
+            primary,
+            predicates,
+            steps,
+        } => {
+            let mut nodes = into_nodes(eval(primary, ctx)?)?;
+
+            for predicate in predicates {
+                nodes = filter(nodes, predicate, ctx.order)?;
+            }
+
+            XPathValue::Nodes(eval_steps(nodes, steps, ctx.order)?)
+        }
+        Expr::Literal(literal) => XPathValue::String(literal.clone()),
+        Expr::Number(number) => XPathValue::Number(*number),
+        Expr::Function(function, args) => call(*function, args, ctx)?,
+    })
+}
+
+fn into_nodes(value: XPathValue) -> Result<Vec<XPathNode>> {
+    match value {
+        XPathValue::Nodes(nodes) => Ok(nodes),
+        value => Err(eval_error(format!("expected a node-set, found {value:?}"))),
+    }
+}
+
+fn eval_path(path: &Path, ctx: &Context) -> Result<Vec<XPathNode>> {
+    let start = if path.absolute {
+        let mut root = ctx.node.node().clone();
+        while let Some(parent) = root.parent() {
+            root = parent;
+        }
+        XPathNode::Node(root)
+    } else {
+        ctx.node.clone()
+    };
+
+    eval_steps(vec![start], &path.steps, ctx.order)
+}
+
+fn eval_steps(
+    mut nodes: Vec<XPathNode>,
+    steps: &[Step],
+    order: &DocumentOrder,
+) -> Result<Vec<XPathNode>> {
+    for step in steps {
+        let mut next = Vec::new();
+
+        for node in &nodes {
+            let mut candidates: Vec<XPathNode> = axis_nodes(node, step.axis)
+                .into_iter()
+                .filter(|candidate| node_test(candidate, &step.test, step.axis))
+                .collect();
+
+            for predicate in &step.predicates {
+                candidates = filter(candidates, predicate, order)?;
+            }
+
+            next.extend(candidates);
+        }
+
+        nodes = order.sort(next);
+    }
+
+    Ok(nodes)
+}
+
+/// Keep the nodes for which the predicate holds. `nodes` must be in axis order.
+fn filter(
+    nodes: Vec<XPathNode>,
+    predicate: &Expr,
+    order: &DocumentOrder,
+) -> Result<Vec<XPathNode>> {
+    let size = nodes.len();
+    let mut res = Vec::new();
+
+    for (i, node) in nodes.into_iter().enumerate() {
+        let ctx = Context {
+            node,
+            position: i + 1,
+            size,
+            order,
+        };
+
+        let keep = match eval(predicate, &ctx)? {
+            XPathValue::Number(n) => n == ctx.position as f64,
+            value => value.boolean(),
+        };
+
+        if keep {
+            res.push(ctx.node);
+        }
+    }
+
+    Ok(res)
+}
+
+/// The nodes on the axis, ordered by their proximity to the context node.
+fn axis_nodes(node: &XPathNode, axis: Axis) -> Vec<XPathNode> {
+    let tree_node = match node {
+        XPathNode::Node(node) => Some(node),
+        XPathNode::Attribute { .. } => None,
+    };
+
+    match axis {
+        Axis::SelfNode => vec![node.clone()],
+        Axis::Parent => node.parent().map(XPathNode::Node).into_iter().collect(),
+        Axis::Ancestor | Axis::AncestorOrSelf => {
+            let mut res = Vec::new();
+
+            if axis == Axis::AncestorOrSelf {
+                res.push(node.clone());
+            }
+
+            let mut parent = node.parent();
+            while let Some(current) = parent {
+                parent = current.parent();
+                res.push(XPathNode::Node(current));
+            }
+
+            res
+        }
+        Axis::Child => tree_node
+            .map(|node| node.children().map(XPathNode::Node).collect())
+            .unwrap_or_default(),
+        Axis::Descendant => tree_node
+            .map(|node| node.descendants().map(XPathNode::Node).collect())
+            .unwrap_or_default(),
+        Axis::DescendantOrSelf => match tree_node {
+            Some(tree_node) => tree_node
+                .inclusive_descendants()
+                .map(XPathNode::Node)
+                .collect(),
+            None => vec![node.clone()],
+        },
+        Axis::FollowingSibling => tree_node
+            .map(|node| node.following_siblings().map(XPathNode::Node).collect())
+            .unwrap_or_default(),
+        Axis::PrecedingSibling => tree_node
+            .map(|node| {
+                node.preceding_siblings()
+                    .map(XPathNode::Node)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default(),
+        Axis::Attribute => tree_node
+            .and_then(|node| node.as_element())
+            .map(|elem| {
+                elem.attributes
+                    .borrow()
+                    .map
+                    .iter()
+                    .map(|(name, attr)| XPathNode::Attribute {
+                        element: tree_node.unwrap().clone(),
+                        name: name.local.to_string(),
+                        value: attr.value.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+fn node_test(node: &XPathNode, test: &NodeTest, axis: Axis) -> bool {
+    match (node, test) {
+        (XPathNode::Attribute { .. }, NodeTest::Any | NodeTest::Node) => true,
+        (XPathNode::Attribute { name, .. }, NodeTest::Name(test)) => {
+            axis == Axis::Attribute && name.eq_ignore_ascii_case(test)
+        }
+        (XPathNode::Attribute { .. }, _) => false,
+        (XPathNode::Node(node), test) => match test {
+            NodeTest::Node => true,
+            NodeTest::Any => node.as_element().is_some(),
+            NodeTest::Name(name) => node
+                .as_element()
+                .is_some_and(|elem| elem.name.local.as_ref().eq_ignore_ascii_case(name)),
+            NodeTest::Text => node.as_text().is_some(),
+            NodeTest::Comment => node.as_comment().is_some(),
+        },
+    }
+}
+
+fn compare(op: CompareOp, a: &XPathValue, b: &XPathValue) -> bool {
+    match (a, b) {
+        (XPathValue::Nodes(a), XPathValue::Nodes(b)) => a.iter().any(|a| {
+            let a = XPathValue::String(a.string_value());
+            b.iter()
+                .any(|b| compare(op, &a, &XPathValue::String(b.string_value())))
+        }),
+        (XPathValue::Nodes(nodes), other) => nodes
+            .iter()
+            .any(|node| compare_atomic(op, &node_as(node, other), other)),
+        (other, XPathValue::Nodes(nodes)) => nodes
+            .iter()
+            .any(|node| compare_atomic(op, other, &node_as(node, other))),
+        (a, b) => compare_atomic(op, a, b),
+    }
+}
+
+/// Convert a node to the type of `other`, for comparing a node-set with another value.
+fn node_as(node: &XPathNode, other: &XPathValue) -> XPathValue {
+    match other {
+        XPathValue::Number(_) => XPathValue::Number(string_to_number(&node.string_value())),
+        XPathValue::Boolean(_) => XPathValue::Boolean(true),
+        _ => XPathValue::String(node.string_value()),
+    }
+}
+
+fn compare_atomic(op: CompareOp, a: &XPathValue, b: &XPathValue) -> bool {
+    match op {
+        CompareOp::Eq | CompareOp::Ne => {
+            let eq = match (a, b) {
+                (XPathValue::Boolean(_), _) | (_, XPathValue::Boolean(_)) => {
+                    a.boolean() == b.boolean()
+                }
+                (XPathValue::Number(_), _) | (_, XPathValue::Number(_)) => a.number() == b.number(),
+                _ => a.string() == b.string(),
+            };
+
+            (op == CompareOp::Eq) == eq
+        }
+        _ => {
+            let ordering = a.number().partial_cmp(&b.number());
+
+            match op {
+                CompareOp::Lt => ordering == Some(Ordering::Less),
+                CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                CompareOp::Gt => ordering == Some(Ordering::Greater),
+                _ => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            }
+        }
+    }
+}
+
+fn call(function: Function, args: &[Expr], ctx: &Context) -> Result<XPathValue> {
+    let arg = |i: usize| -> Result<XPathValue> { eval(&args[i], ctx) };
+
+    // the string argument, defaulting to the context node
+    let string_arg = |i: usize| -> Result<String> {
+        match args.get(i) {
+            Some(_) => Ok(arg(i)?.string()),
+            None => Ok(ctx.node.string_value()),
+        }
+    };
+
+    Ok(match function {
+        Function::Last => XPathValue::Number(ctx.size as f64),
+        Function::Position => XPathValue::Number(ctx.position as f64),
+        Function::Count => XPathValue::Number(into_nodes(arg(0)?)?.len() as f64),
+        Function::Name | Function::LocalName => {
+            let name = match args.first() {
+                Some(_) => into_nodes(arg(0)?)?
+                    .first()
+                    .map(XPathNode::name)
+                    .unwrap_or_default(),
+                None => ctx.node.name(),
+            };
+
+            XPathValue::String(name)
+        }
+        Function::String => XPathValue::String(string_arg(0)?),
+        Function::Concat => {
+            let mut res = String::new();
+            for i in 0..args.len() {
+                res.push_str(&arg(i)?.string());
+            }
+            XPathValue::String(res)
+        }
+        Function::StartsWith => {
+            XPathValue::Boolean(arg(0)?.string().starts_with(&arg(1)?.string()))
+        }
+        Function::EndsWith => XPathValue::Boolean(arg(0)?.string().ends_with(&arg(1)?.string())),
+        Function::Contains => XPathValue::Boolean(arg(0)?.string().contains(&arg(1)?.string())),
+        Function::SubstringBefore => {
+            let (s, pattern) = (arg(0)?.string(), arg(1)?.string());
+            XPathValue::String(
+                s.split_once(&pattern)
+                    .map(|(before, _)| before.to_string())
+                    .unwrap_or_default(),
+            )
+        }
+        Function::SubstringAfter => {
+            let (s, pattern) = (arg(0)?.string(), arg(1)?.string());
+            XPathValue::String(
+                s.split_once(&pattern)
+                    .map(|(_, after)| after.to_string())
+                    .unwrap_or_default(),
+            )
+        }
+        Function::Substring => {
+            let s = arg(0)?.string();
+            let start = arg(1)?.number().round();
+            let end = match args.get(2) {
+                Some(_) => start + arg(2)?.number().round(),
+                None => f64::INFINITY,
+            };
+
+            // positions are 1-based and a character is included if start <= pos < end
+            XPathValue::String(
+                s.chars()
+                    .enumerate()
+                    .filter(|(i, _)| {
+                        let pos = (*i + 1) as f64;
+                        pos >= start && pos < end
+                    })
+                    .map(|(_, c)| c)
+                    .collect(),
+            )
+        }
+        Function::StringLength => XPathValue::Number(string_arg(0)?.chars().count() as f64),
+        Function::NormalizeSpace => XPathValue::String(
+            string_arg(0)?
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        Function::Translate => {
+            let s = arg(0)?.string();
+            let from: Vec<char> = arg(1)?.string().chars().collect();
+            let to: Vec<char> = arg(2)?.string().chars().collect();
+
+            XPathValue::String(
+                s.chars()
+                    .filter_map(|c| match from.iter().position(|f| *f == c) {
+                        Some(i) => to.get(i).copied(),
+                        None => Some(c),
+                    })
+                    .collect(),
+            )
+        }
+        Function::Boolean => XPathValue::Boolean(arg(0)?.boolean()),
+        Function::Not => XPathValue::Boolean(!arg(0)?.boolean()),
+        Function::True => XPathValue::Boolean(true),
+        Function::False => XPathValue::Boolean(false),
+        Function::Number => XPathValue::Number(match args.first() {
+            Some(_) => arg(0)?.number(),
+            None => string_to_number(&ctx.node.string_value()),
+        }),
+        Function::Sum => XPathValue::Number(
+            into_nodes(arg(0)?)?
+                .iter()
+                .map(|node| string_to_number(&node.string_value()))
+                .sum(),
+        ),
+        Function::Floor => XPathValue::Number(arg(0)?.number().floor()),
+        Function::Ceiling => XPathValue::Number(arg(0)?.number().ceil()),
+        Function::Round => XPathValue::Number((arg(0)?.number() + 0.5).floor()),
+    })
+}
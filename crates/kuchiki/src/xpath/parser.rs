@@ -0,0 +1,835 @@
+/**
+ * @file parser.rs
+ * @author Krisna Pranav
+ * @brief xpath[parser]
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    Arithmetic(ArithmeticOp, Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Union(Box<Expr>, Box<Expr>),
+    Path(Path),
+    Filter {
+        primary: Box<Expr>,
+        predicates: Vec<Expr>,
+        steps: Vec<Step>,
+    },
+    Literal(String),
+    Number(f64),
+    Function(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ArithmeticOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Path {
+    pub absolute: bool,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Step {
+    pub axis: Axis,
+    pub test: NodeTest,
+    pub predicates: Vec<Expr>,
+}
+
+impl Step {
+    /// The step that `//` abbreviates.
+    fn descendant_or_self() -> Self {
+        Self {
+            axis: Axis::DescendantOrSelf,
+            test: NodeTest::Node,
+            predicates: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Axis {
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Parent,
+    Ancestor,
+    AncestorOrSelf,
+    FollowingSibling,
+    PrecedingSibling,
+    Attribute,
+    SelfNode,
+}
+
+impl Axis {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "child" => Axis::Child,
+            "descendant" => Axis::Descendant,
+            "descendant-or-self" => Axis::DescendantOrSelf,
+            "parent" => Axis::Parent,
+            "ancestor" => Axis::Ancestor,
+            "ancestor-or-self" => Axis::AncestorOrSelf,
+            "following-sibling" => Axis::FollowingSibling,
+            "preceding-sibling" => Axis::PrecedingSibling,
+            "attribute" => Axis::Attribute,
+            "self" => Axis::SelfNode,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum NodeTest {
+    /// `*`, any node of the axis' principal type.
+    Any,
+    Name(String),
+    Node,
+    Text,
+    Comment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Function {
+    Last,
+    Position,
+    Count,
+    Name,
+    LocalName,
+    String,
+    Concat,
+    StartsWith,
+    EndsWith,
+    Contains,
+    SubstringBefore,
+    SubstringAfter,
+    Substring,
+    StringLength,
+    NormalizeSpace,
+    Translate,
+    Boolean,
+    Not,
+    True,
+    False,
+    Number,
+    Sum,
+    Floor,
+    Ceiling,
+    Round,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "last" => Function::Last,
+            "position" => Function::Position,
+            "count" => Function::Count,
+            "name" => Function::Name,
+            "local-name" => Function::LocalName,
+            "string" => Function::String,
+            "concat" => Function::Concat,
+            "starts-with" => Function::StartsWith,
+            "ends-with" => Function::EndsWith,
+            "contains" => Function::Contains,
+            "substring-before" => Function::SubstringBefore,
+            "substring-after" => Function::SubstringAfter,
+            "substring" => Function::Substring,
+            "string-length" => Function::StringLength,
+            "normalize-space" => Function::NormalizeSpace,
+            "translate" => Function::Translate,
+            "boolean" => Function::Boolean,
+            "not" => Function::Not,
+            "true" => Function::True,
+            "false" => Function::False,
+            "number" => Function::Number,
+            "sum" => Function::Sum,
+            "floor" => Function::Floor,
+            "ceiling" => Function::Ceiling,
+            "round" => Function::Round,
+            _ => return None,
+        })
+    }
+
+    /// The allowed number of arguments, as an inclusive range.
+    fn arity(self) -> (usize, usize) {
+        match self {
+            Function::Last | Function::Position | Function::True | Function::False => (0, 0),
+            Function::Name
+            | Function::LocalName
+            | Function::String
+            | Function::StringLength
+            | Function::NormalizeSpace
+            | Function::Number => (0, 1),
+            Function::Count
+            | Function::Boolean
+            | Function::Not
+            | Function::Sum
+            | Function::Floor
+            | Function::Ceiling
+            | Function::Round => (1, 1),
+            Function::StartsWith
+            | Function::EndsWith
+            | Function::Contains
+            | Function::SubstringBefore
+            | Function::SubstringAfter => (2, 2),
+            Function::Substring => (2, 3),
+            Function::Translate => (3, 3),
+            Function::Concat => (2, usize::MAX),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Slash,
+    DoubleSlash,
+    LeftBracket,
+    RightBracket,
+    LeftParen,
+    RightParen,
+    At,
+    Comma,
+    DoubleColon,
+    Dot,
+    DoubleDot,
+    Pipe,
+    Plus,
+    Minus,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// `*` as a name test.
+    Star,
+    /// `*`, `and`, `or`, `div` and `mod` as operators.
+    Operator(&'static str),
+    Name(String),
+    Literal(String),
+    Number(f64),
+}
+
+fn error(position: usize, message: impl Into<String>) -> Error {
+    Error::XPathParseError {
+        position,
+        message: message.into(),
+    }
+}
+
+/// Split the expression into tokens, with their byte offsets.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens: Vec<(usize, Token)> = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(pos, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        // A `*` or an operator name is an operator if there is a preceding token
+        // that isn't itself an operator or an opening token (XPath 1.0 section 3.7).
+        let operator_context = match tokens.last() {
+            None => false,
+            Some((_, token)) => !matches!(
+                token,
+                Token::At
+                    | Token::DoubleColon
+                    | Token::LeftParen
+                    | Token::LeftBracket
+                    | Token::Comma
+                    | Token::Slash
+                    | Token::DoubleSlash
+                    | Token::Pipe
+                    | Token::Plus
+                    | Token::Minus
+                    | Token::Eq
+                    | Token::Ne
+                    | Token::Lt
+                    | Token::Le
+                    | Token::Gt
+                    | Token::Ge
+                    | Token::Operator(_)
+            ),
+        };
+
+        let token = match c {
+            '/' => {
+                chars.next();
+                if chars.next_if(|&(_, c)| c == '/').is_some() {
+                    Token::DoubleSlash
+                } else {
+                    Token::Slash
+                }
+            }
+            '[' => {
+                chars.next();
+                Token::LeftBracket
+            }
+            ']' => {
+                chars.next();
+                Token::RightBracket
+            }
+            '(' => {
+                chars.next();
+                Token::LeftParen
+            }
+            ')' => {
+                chars.next();
+                Token::RightParen
+            }
+            '@' => {
+                chars.next();
+                Token::At
+            }
+            ',' => {
+                chars.next();
+                Token::Comma
+            }
+            '|' => {
+                chars.next();
+                Token::Pipe
+            }
+            '+' => {
+                chars.next();
+                Token::Plus
+            }
+            '-' => {
+                chars.next();
+                Token::Minus
+            }
+            '=' => {
+                chars.next();
+                Token::Eq
+            }
+            '!' => {
+                chars.next();
+                if chars.next_if(|&(_, c)| c == '=').is_none() {
+                    return Err(error(pos, "expected `!=`"));
+                }
+                Token::Ne
+            }
+            '<' => {
+                chars.next();
+                if chars.next_if(|&(_, c)| c == '=').is_some() {
+                    Token::Le
+                } else {
+                    Token::Lt
+                }
+            }
+            '>' => {
+                chars.next();
+                if chars.next_if(|&(_, c)| c == '=').is_some() {
+                    Token::Ge
+                } else {
+                    Token::Gt
+                }
+            }
+            ':' => {
+                chars.next();
+                if chars.next_if(|&(_, c)| c == ':').is_none() {
+                    return Err(error(pos, "namespace prefixes are not supported"));
+                }
+                Token::DoubleColon
+            }
+            '*' => {
+                chars.next();
+                if operator_context {
+                    Token::Operator("*")
+                } else {
+                    Token::Star
+                }
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut literal = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some((_, end)) if end == c => break,
+                        Some((_, c)) => literal.push(c),
+                        None => return Err(error(pos, "unterminated string literal")),
+                    }
+                }
+
+                Token::Literal(literal)
+            }
+            '.' | '0'..='9' => {
+                let mut end = pos;
+                let mut seen_dot = false;
+
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_ascii_digit() || (c == '.' && !seen_dot) {
+                        seen_dot |= c == '.';
+                        end = i + c.len_utf8();
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                match &input[pos..end] {
+                    "." => {
+                        if chars.next_if(|&(_, c)| c == '.').is_some() {
+                            Token::DoubleDot
+                        } else {
+                            Token::Dot
+                        }
+                    }
+                    number => Token::Number(
+                        number
+                            .parse()
+                            .map_err(|_| error(pos, format!("invalid number `{number}`")))?,
+                    ),
+                }
+            }
+            c if is_name_start(c) => {
+                let mut end = pos;
+
+                while let Some(&(i, c)) = chars.peek() {
+                    if is_name_char(c) {
+                        end = i + c.len_utf8();
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                let name = &input[pos..end];
+
+                match name {
+                    "and" | "or" | "div" | "mod" if operator_context => {
+                        Token::Operator(match name {
+                            "and" => "and",
+                            "or" => "or",
+                            "div" => "div",
+                            _ => "mod",
+                        })
+                    }
+                    _ => Token::Name(name.to_string()),
+                }
+            }
+            c => return Err(error(pos, format!("unexpected character `{c}`"))),
+        };
+
+        tokens.push((pos, token));
+    }
+
+    Ok(tokens)
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n).map(|(_, token)| token)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|(offset, _)| *offset)
+            .unwrap_or(self.len)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(_, token)| token.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token, what: &str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(error(self.offset(), format!("expected {what}")))
+        }
+    }
+
+    fn eat_operator(&mut self, op: &str) -> bool {
+        self.eat(&Token::Operator(match op {
+            "and" => "and",
+            "or" => "or",
+            "div" => "div",
+            "mod" => "mod",
+            _ => "*",
+        }))
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+
+        while self.eat_operator("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_equality()?;
+
+        while self.eat_operator("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_equality()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_equality(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_relational()?;
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Eq) => CompareOp::Eq,
+                Some(Token::Ne) => CompareOp::Ne,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+
+            expr = Expr::Compare(op, Box::new(expr), Box::new(self.parse_relational()?));
+        }
+    }
+
+    fn parse_relational(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_additive()?;
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Lt) => CompareOp::Lt,
+                Some(Token::Le) => CompareOp::Le,
+                Some(Token::Gt) => CompareOp::Gt,
+                Some(Token::Ge) => CompareOp::Ge,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+
+            expr = Expr::Compare(op, Box::new(expr), Box::new(self.parse_additive()?));
+        }
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_multiplicative()?;
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => ArithmeticOp::Add,
+                Some(Token::Minus) => ArithmeticOp::Sub,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+
+            expr = Expr::Arithmetic(op, Box::new(expr), Box::new(self.parse_multiplicative()?));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+
+        loop {
+            let op = if self.eat_operator("*") {
+                ArithmeticOp::Mul
+            } else if self.eat_operator("div") {
+                ArithmeticOp::Div
+            } else if self.eat_operator("mod") {
+                ArithmeticOp::Mod
+            } else {
+                return Ok(expr);
+            };
+
+            expr = Expr::Arithmetic(op, Box::new(expr), Box::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.eat(&Token::Minus) {
+            Ok(Expr::Negate(Box::new(self.parse_unary()?)))
+        } else {
+            self.parse_union()
+        }
+    }
+
+    fn parse_union(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_path()?;
+
+        while self.eat(&Token::Pipe) {
+            expr = Expr::Union(Box::new(expr), Box::new(self.parse_path()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn starts_primary(&self) -> bool {
+        match self.peek() {
+            Some(Token::LeftParen | Token::Literal(_) | Token::Number(_)) => true,
+            Some(Token::Name(name)) => {
+                self.peek_nth(1) == Some(&Token::LeftParen)
+                    && !matches!(name.as_str(), "node" | "text" | "comment")
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_path(&mut self) -> Result<Expr> {
+        if !self.starts_primary() {
+            return Ok(Expr::Path(self.parse_location_path()?));
+        }
+
+        let primary = self.parse_primary()?;
+        let predicates = self.parse_predicates()?;
+        let mut steps = Vec::new();
+
+        loop {
+            if self.eat(&Token::Slash) {
+                steps.push(self.parse_step()?);
+            } else if self.eat(&Token::DoubleSlash) {
+                steps.push(Step::descendant_or_self());
+                steps.push(self.parse_step()?);
+            } else {
+                break;
+            }
+        }
+
+        if predicates.is_empty() && steps.is_empty() {
+            Ok(primary)
+        } else {
+            Ok(Expr::Filter {
+                primary: Box::new(primary),
+                predicates,
+                steps,
+            })
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        let offset = self.offset();
+
+        match self.next() {
+            Some(Token::LeftParen) => {
+                let expr = self.parse_or()?;
+                self.expect(&Token::RightParen, "`)`")?;
+                Ok(expr)
+            }
+            Some(Token::Literal(literal)) => Ok(Expr::Literal(literal)),
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::Name(name)) => {
+                let function = Function::from_name(&name)
+                    .ok_or_else(|| error(offset, format!("unknown function `{name}()`")))?;
+                self.expect(&Token::LeftParen, "`(`")?;
+
+                let mut args = Vec::new();
+
+                if !self.eat(&Token::RightParen) {
+                    loop {
+                        args.push(self.parse_or()?);
+
+                        if self.eat(&Token::RightParen) {
+                            break;
+                        }
+
+                        self.expect(&Token::Comma, "`,` or `)`")?;
+                    }
+                }
+
+                let (min, max) = function.arity();
+                if args.len() < min || args.len() > max {
+                    return Err(error(
+                        offset,
+                        format!("wrong number of arguments for `{name}()`"),
+                    ));
+                }
+
+                Ok(Expr::Function(function, args))
+            }
+            _ => Err(error(offset, "expected an expression")),
+        }
+    }
+
+    fn parse_predicates(&mut self) -> Result<Vec<Expr>> {
+        let mut predicates = Vec::new();
+
+        while self.eat(&Token::LeftBracket) {
+            predicates.push(self.parse_or()?);
+            self.expect(&Token::RightBracket, "`]`")?;
+        }
+
+        Ok(predicates)
+    }
+
+    fn parse_location_path(&mut self) -> Result<Path> {
+        let mut steps = Vec::new();
+
+        let absolute = match self.peek() {
+            Some(Token::Slash) => {
+                self.pos += 1;
+
+                if !self.starts_step() {
+                    return Ok(Path {
+                        absolute: true,
+                        steps,
+                    });
+                }
+
+                true
+            }
+            Some(Token::DoubleSlash) => {
+                self.pos += 1;
+                steps.push(Step::descendant_or_self());
+                true
+            }
+            _ => false,
+        };
+
+        steps.push(self.parse_step()?);
+
+        loop {
+            if self.eat(&Token::Slash) {
+                steps.push(self.parse_step()?);
+            } else if self.eat(&Token::DoubleSlash) {
+                steps.push(Step::descendant_or_self());
+                steps.push(self.parse_step()?);
+            } else {
+                break;
+            }
+        }
+
+        Ok(Path { absolute, steps })
+    }
+
+    fn starts_step(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Dot | Token::DoubleDot | Token::At | Token::Star | Token::Name(_))
+        )
+    }
+
+    fn parse_step(&mut self) -> Result<Step> {
+        if self.eat(&Token::Dot) {
+            return Ok(Step {
+                axis: Axis::SelfNode,
+                test: NodeTest::Node,
+                predicates: Vec::new(),
+            });
+        }
+
+        if self.eat(&Token::DoubleDot) {
+            return Ok(Step {
+                axis: Axis::Parent,
+                test: NodeTest::Node,
+                predicates: Vec::new(),
+            });
+        }
+
+        let axis = if self.eat(&Token::At) {
+            Axis::Attribute
+        } else if let (Some(Token::Name(name)), Some(Token::DoubleColon)) =
+            (self.peek(), self.peek_nth(1))
+        {
+            let offset = self.offset();
+            let axis = Axis::from_name(name)
+                .ok_or_else(|| error(offset, format!("unsupported axis `{name}`")))?;
+            self.pos += 2;
+            axis
+        } else {
+            Axis::Child
+        };
+
+        let offset = self.offset();
+        let test = match self.next() {
+            Some(Token::Star) => NodeTest::Any,
+            Some(Token::Name(name)) => {
+                if self.eat(&Token::LeftParen) {
+                    let test = match name.as_str() {
+                        "node" => NodeTest::Node,
+                        "text" => NodeTest::Text,
+                        "comment" => NodeTest::Comment,
+                        _ => return Err(error(offset, format!("unknown node test `{name}()`"))),
+                    };
+                    self.expect(&Token::RightParen, "`)`")?;
+                    test
+                } else {
+                    NodeTest::Name(name)
+                }
+            }
+            _ => return Err(error(offset, "expected a node test")),
+        };
+
+        Ok(Step {
+            axis,
+            test,
+            predicates: self.parse_predicates()?,
+        })
+    }
+}
+
+/// Parse an XPath 1.0 expression.
+pub(crate) fn parse(input: &str) -> Result<Expr> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+        len: input.len(),
+    };
+
+    if parser.peek().is_none() {
+        return Err(error(0, "empty expression"));
+    }
+
+    let expr = parser.parse_or()?;
+
+    if parser.peek().is_some() {
+        return Err(error(parser.offset(), "unexpected token"));
+    }
+
+    Ok(expr)
+}
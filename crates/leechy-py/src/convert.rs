@@ -0,0 +1,137 @@
+/**
+ * @file convert.rs
+ * @author Krisna Pranav
+ * @brief convert
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use pyo3::create_exception;
+use pyo3::exceptions::{PyConnectionError, PyException, PyIOError, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+
+create_exception!(leechy, SearchError, PyException, "A search failed.");
+create_exception!(
+    leechy,
+    HTTPStatusError,
+    SearchError,
+    "The engine responded with an error status. The status code is the second argument."
+);
+
+/// A search result as seen from Python.
+#[pyclass(frozen, get_all, module = "leechy")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub url: String,
+    pub title: Option<String>,
+    pub snippet: Option<String>,
+    pub rank: usize,
+}
+
+#[pymethods]
+impl SearchResult {
+    fn __repr__(&self) -> String {
+        format!("SearchResult(rank={}, url={:?})", self.rank, self.url)
+    }
+}
+
+impl From<::leechy::SearchResult> for SearchResult {
+    fn from(result: ::leechy::SearchResult) -> Self {
+        Self {
+            url: result.url.to_string(),
+            title: result.title,
+            snippet: result.snippet,
+            rank: result.rank,
+        }
+    }
+}
+
+/// Map an error to the closest Python exception.
+pub fn to_py_err(err: ::leechy::Error) -> PyErr {
+    use ::leechy::Error;
+
+    let message = err.to_string();
+
+    match err {
+        Error::Request(err) if err.is_timeout() => PyTimeoutError::new_err(message),
+        Error::Request(err) if err.is_connect() => PyConnectionError::new_err(message),
+        Error::Request(err) => match err.status() {
+            Some(status) => HTTPStatusError::new_err((message, status.as_u16())),
+            None => SearchError::new_err(message),
+        },
+        Error::Io(_) => PyIOError::new_err(message),
+        Error::Config(_)
+        | Error::Json(_)
+        | Error::XPath(_)
+        | Error::InvalidTemplate { .. }
+        | Error::UnknownEngine(_) => PyValueError::new_err(message),
+        Error::NotCached { .. } => SearchError::new_err(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_result() {
+        pyo3::prepare_freethreaded_python();
+
+        let engine = ::leechy::Engine::by_name("startpage").unwrap();
+        let page_url = engine.search_url("rust", 1).unwrap();
+        let results = engine
+            .parse(
+                r#"<div class="result"><a class="result-link" href="/docs"><h2>Docs</h2></a></div>"#,
+                &page_url,
+            )
+            .unwrap();
+
+        Python::with_gil(|py| {
+            let result = Bound::new(py, SearchResult::from(results[0].clone())).unwrap();
+
+            let url: String = result.getattr("url").unwrap().extract().unwrap();
+            assert_eq!(url, "https://www.startpage.com/docs");
+
+            let title: Option<String> = result.getattr("title").unwrap().extract().unwrap();
+            assert_eq!(title.as_deref(), Some("Docs"));
+
+            assert!(result.getattr("snippet").unwrap().is_none());
+            assert_eq!(
+                result.getattr("rank").unwrap().extract::<usize>().unwrap(),
+                1
+            );
+        });
+    }
+
+    #[test]
+    fn exceptions() {
+        pyo3::prepare_freethreaded_python();
+
+        let connect = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(reqwest::get("http://127.0.0.1:1/"))
+            .unwrap_err();
+
+        Python::with_gil(|py| {
+            let err = to_py_err(::leechy::Error::UnknownEngine("bing".to_string()));
+            assert!(err.is_instance_of::<PyValueError>(py));
+            assert_eq!(err.value_bound(py).to_string(), "unknown engine 'bing'");
+
+            let err = to_py_err(connect.into());
+            assert!(err.is_instance_of::<PyConnectionError>(py));
+
+            let err = to_py_err(::leechy::Error::NotCached {
+                engine: "google".to_string(),
+                query: "rust".to_string(),
+                page: 1,
+            });
+            assert!(err.is_instance_of::<SearchError>(py));
+            assert!(!err.is_instance_of::<HTTPStatusError>(py));
+
+            let err = to_py_err(std::io::Error::other("disk").into());
+            assert!(err.is_instance_of::<PyIOError>(py));
+        });
+    }
+}
@@ -8,25 +8,110 @@
  * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
  *
  */
+use std::sync::Arc;
 
 use pyo3::prelude::*;
 
+mod convert;
+mod runtime;
+
+use convert::to_py_err;
+
+fn load_config(config: Option<&str>) -> PyResult<::leechy::Config> {
+    match config {
+        Some(path) => ::leechy::Config::open(path).map_err(to_py_err),
+        None => Ok(::leechy::Config::new()),
+    }
+}
+
+// the code generated by pyo3 for functions returning `PyResult` converts the error into itself
+#[allow(clippy::useless_conversion)]
 #[pymodule]
 mod leechy {
     use super::*;
     use ::leechy as lchy;
 
-    #[pyclass]
+    #[pymodule_export]
+    use super::convert::SearchResult;
+
+    #[pymodule_init]
+    fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+        let py = m.py();
+        m.add("SearchError", py.get_type_bound::<convert::SearchError>())?;
+        m.add(
+            "HTTPStatusError",
+            py.get_type_bound::<convert::HTTPStatusError>(),
+        )?;
+        runtime::register_atexit(m)
+    }
+
+    /// The names of the bundled engines, or of the engines in a TOML config file.
+    #[pyfunction]
+    #[pyo3(signature = (config = None))]
+    fn engines(config: Option<&str>) -> PyResult<Vec<String>> {
+        Ok(load_config(config)?.names().map(str::to_string).collect())
+    }
+
+    #[pyclass(frozen)]
     struct Engine {
-        inner: lchy::Engine,
+        inner: Arc<lchy::Engine>,
     }
 
     #[pymethods]
     impl Engine {
+        /// A bundled engine, or an engine from a TOML config file.
         #[new]
-        #[pyo3(signature = (name = "google"))]
-        fn new(name: &str) -> PyResult<Self> {
-            match lchy::Engine
+        #[pyo3(signature = (name = "google", config = None))]
+        fn new(name: &str, config: Option<&str>) -> PyResult<Self> {
+            match load_config(config)?.engine(name) {
+                Some(engine) => Ok(Self {
+                    inner: Arc::new(engine),
+                }),
+                None => Err(to_py_err(lchy::Error::UnknownEngine(name.to_string()))),
+            }
+        }
+
+        /// An engine from a TOML config given as a string.
+        #[staticmethod]
+        fn from_toml(toml: &str, name: &str) -> PyResult<Self> {
+            let config = lchy::Config::from_toml(toml).map_err(to_py_err)?;
+
+            match config.engine(name) {
+                Some(engine) => Ok(Self {
+                    inner: Arc::new(engine),
+                }),
+                None => Err(to_py_err(lchy::Error::UnknownEngine(name.to_string()))),
+            }
+        }
+
+        #[getter]
+        fn name(&self) -> &str {
+            self.inner.name()
+        }
+
+        #[pyo3(signature = (query, page = 1))]
+        fn search_url(&self, query: &str, page: usize) -> PyResult<String> {
+            Ok(self
+                .inner
+                .search_url(query, page)
+                .map_err(to_py_err)?
+                .to_string())
+        }
+
+        /// Fetch the first `pages` result pages of the query.
+        #[pyo3(signature = (query, pages = 1))]
+        async fn search(&self, query: String, pages: usize) -> PyResult<Vec<SearchResult>> {
+            let engine = Arc::clone(&self.inner);
+
+            let results = runtime::spawn(async move { engine.search_pages(&query, pages).await })
+                .await
+                .map_err(to_py_err)?;
+
+            Ok(results.into_iter().map(SearchResult::from).collect())
+        }
+
+        fn __repr__(&self) -> String {
+            format!("Engine(name={:?})", self.inner.name())
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,110 @@
+/**
+ * @file runtime.rs
+ * @author Krisna Pranav
+ * @brief runtime
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use pyo3::prelude::*;
+
+/// reqwest needs tokio, while python awaits our coroutines on asyncio.
+static RUNTIME: LazyLock<tokio::runtime::Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+});
+
+/// Waking a coroutine from a tokio thread calls into the interpreter, which aborts if it
+/// finalizes meanwhile. The interpreter waits for running wakes at exit and later
+/// completions don't wake anything.
+static WAKING: AtomicUsize = AtomicUsize::new(0);
+static EXITING: AtomicBool = AtomicBool::new(false);
+
+struct Slot<T> {
+    output: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// The output of a future spawned on the runtime.
+pub struct Spawned<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> Future for Spawned<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut slot = self.slot.lock().unwrap();
+
+        match slot.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+pub fn spawn<F>(future: F) -> Spawned<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let slot = Arc::new(Mutex::new(Slot {
+        output: None,
+        waker: None,
+    }));
+
+    let spawned = Spawned { slot: slot.clone() };
+
+    RUNTIME.spawn(async move {
+        let output = future.await;
+
+        let waker = {
+            let mut slot = slot.lock().unwrap();
+            slot.output = Some(output);
+            slot.waker.take()
+        };
+
+        WAKING.fetch_add(1, Ordering::SeqCst);
+
+        if let Some(waker) = waker {
+            if !EXITING.load(Ordering::SeqCst) {
+                waker.wake();
+            }
+        }
+
+        WAKING.fetch_sub(1, Ordering::SeqCst);
+    });
+
+    spawned
+}
+
+#[pyfunction]
+fn wait_for_wakes(py: Python<'_>) {
+    EXITING.store(true, Ordering::SeqCst);
+
+    py.allow_threads(|| {
+        while WAKING.load(Ordering::SeqCst) > 0 {
+            std::thread::yield_now();
+        }
+    });
+}
+
+/// Make the interpreter wait for running wakes at exit.
+pub fn register_atexit(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let atexit = m.py().import_bound("atexit")?;
+    atexit.call_method1("register", (wrap_pyfunction_bound!(wait_for_wakes, m)?,))?;
+    Ok(())
+}
@@ -0,0 +1,153 @@
+/**
+ * @file cache.rs
+ * @author Krisna Pranav
+ * @brief cache
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+
+use crate::fetch::{slug, Fetcher, Request};
+use crate::{Error, Result};
+
+/// Caches the result pages of another fetcher on disk, so repeated runs see the same
+/// pages and can run without network access.
+///
+/// Pages are stored in `<dir>/<engine>/<query>-<hash>-<page>.html`, where the hash of the
+/// exact query keeps queries that only differ in punctuation or case apart.
+pub struct CachedFetcher<F> {
+    inner: F,
+    dir: PathBuf,
+    offline: bool,
+}
+
+impl<F: Fetcher> CachedFetcher<F> {
+    pub fn new<P: Into<PathBuf>>(inner: F, dir: P) -> Self {
+        Self {
+            inner,
+            dir: dir.into(),
+            offline: false,
+        }
+    }
+
+    /// Only serve cached pages and fail on a miss instead of fetching.
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn path(&self, request: &Request<'_>) -> PathBuf {
+        let name = format!(
+            "{}-{:016x}-{}.html",
+            slug(request.query),
+            fnv1a(request.query.as_bytes()),
+            request.page
+        );
+
+        self.dir.join(slug(request.engine)).join(name)
+    }
+}
+
+#[async_trait]
+impl<F: Fetcher> Fetcher for CachedFetcher<F> {
+    async fn fetch(&self, request: &Request<'_>) -> Result<String> {
+        let path = self.path(request);
+
+        match std::fs::read_to_string(&path) {
+            Ok(body) => return Ok(body),
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+            Err(_) if self.offline => {
+                return Err(Error::NotCached {
+                    engine: request.engine.to_string(),
+                    query: request.query.to_string(),
+                    page: request.page,
+                })
+            }
+            Err(_) => {}
+        }
+
+        let body = self.inner.fetch(request).await?;
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        // write to a temporary file first, so an interrupted run never leaves a truncated page
+        let tmp = path.with_extension("html.tmp");
+        std::fs::write(&tmp, &body)?;
+        std::fs::rename(&tmp, &path)?;
+
+        Ok(body)
+    }
+}
+
+/// 64-bit FNV-1a, which unlike the std hasher is stable across releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    struct Counting(AtomicUsize);
+
+    #[async_trait]
+    impl Fetcher for Counting {
+        async fn fetch(&self, request: &Request<'_>) -> Result<String> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(format!("<p>{} {}</p>", request.query, request.page))
+        }
+    }
+
+    fn request<'a>(query: &'a str, page: usize) -> Request<'a> {
+        Request {
+            engine: "google",
+            query,
+            page,
+            url: url::Url::parse("https://example.com/").unwrap(),
+            rate_limit: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let fetcher = CachedFetcher::new(Counting(AtomicUsize::new(0)), dir.path());
+
+        assert_eq!(
+            fetcher.fetch(&request("Rust", 1)).await.unwrap(),
+            "<p>Rust 1</p>"
+        );
+        assert_eq!(
+            fetcher.fetch(&request("Rust", 1)).await.unwrap(),
+            "<p>Rust 1</p>"
+        );
+        assert_eq!(fetcher.inner.0.load(Ordering::SeqCst), 1);
+
+        fetcher.fetch(&request("rust", 1)).await.unwrap();
+        fetcher.fetch(&request("Rust", 2)).await.unwrap();
+        assert_eq!(fetcher.inner.0.load(Ordering::SeqCst), 3);
+
+        let offline = CachedFetcher::new(Counting(AtomicUsize::new(0)), dir.path()).offline(true);
+
+        assert_eq!(
+            offline.fetch(&request("Rust", 2)).await.unwrap(),
+            "<p>Rust 2</p>"
+        );
+        assert!(matches!(
+            offline.fetch(&request("Rust", 3)).await,
+            Err(Error::NotCached { page: 3, .. })
+        ));
+        assert_eq!(offline.inner.0.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,402 @@
+/**
+ * @file compare.rs
+ * @author Krisna Pranav
+ * @brief compare
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::fetch::Fetcher;
+use crate::{Engine, Result};
+
+/// A query and our own results for it, best first.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct Query {
+    pub query: String,
+    pub results: Vec<String>,
+}
+
+/// Read a JSON array of queries with our own results.
+pub fn load_queries<P: AsRef<Path>>(path: P) -> Result<Vec<Query>> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Scores {
+    pub overlap: f64,
+    pub rbo: f64,
+    pub ndcg: f64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct EngineScores {
+    pub engine: String,
+    pub results: Vec<String>,
+    pub scores: Option<Scores>,
+    pub error: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct QueryReport {
+    pub query: String,
+    pub engines: Vec<EngineScores>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct EngineSummary {
+    pub engine: String,
+
+    /// Number of queries the engine was scored on.
+    pub queries: usize,
+
+    /// Number of queries where fetching or parsing the results failed.
+    pub failed: usize,
+
+    /// Mean scores over the scored queries.
+    pub mean: Scores,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct Report {
+    pub k: usize,
+    pub rbo_p: f64,
+    pub engines: Vec<EngineSummary>,
+    pub queries: Vec<QueryReport>,
+}
+
+impl Report {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+/// Compares the results of engines with our own results.
+///
+/// URLs are compared without scheme, `www.`, trailing slash and fragment.
+pub struct Comparison {
+    engines: Vec<Engine>,
+    k: usize,
+    pages: usize,
+    rbo_p: f64,
+}
+
+impl Comparison {
+    pub fn new(engines: Vec<Engine>) -> Self {
+        Self {
+            engines,
+            k: 10,
+            pages: 1,
+            rbo_p: 0.9,
+        }
+    }
+
+    /// Cutoff for overlap and NDCG. Defaults to 10.
+    pub fn k(mut self, k: usize) -> Self {
+        self.k = k;
+        self
+    }
+
+    /// Number of result pages to fetch from each engine. Defaults to 1.
+    pub fn pages(mut self, pages: usize) -> Self {
+        self.pages = pages;
+        self
+    }
+
+    /// Persistence of rank-biased overlap. Defaults to 0.9, where the top 10
+    /// results carry about 86% of the weight.
+    pub fn rbo_p(mut self, p: f64) -> Self {
+        self.rbo_p = p;
+        self
+    }
+
+    /// Fetch the results of every engine for every query and score them. An engine
+    /// failing on a query is recorded in the report and does not stop the comparison.
+    pub async fn run<F: Fetcher + ?Sized>(&self, fetcher: &F, queries: &[Query]) -> Report {
+        let mut reports = Vec::with_capacity(queries.len());
+
+        for query in queries {
+            let ours = normalize_all(&query.results);
+            let mut engines = Vec::with_capacity(self.engines.len());
+
+            for engine in &self.engines {
+                let scores = match engine.search_with(fetcher, &query.query, self.pages).await {
+                    Ok(results) => {
+                        let theirs =
+                            normalize_all(results.iter().map(|result| result.url.as_ref()));
+
+                        EngineScores {
+                            engine: engine.name().to_string(),
+                            scores: Some(self.score(&ours, &theirs)),
+                            results: results
+                                .into_iter()
+                                .map(|result| result.url.to_string())
+                                .collect(),
+                            error: None,
+                        }
+                    }
+                    Err(err) => EngineScores {
+                        engine: engine.name().to_string(),
+                        results: Vec::new(),
+                        scores: None,
+                        error: Some(err.to_string()),
+                    },
+                };
+
+                engines.push(scores);
+            }
+
+            reports.push(QueryReport {
+                query: query.query.clone(),
+                engines,
+            });
+        }
+
+        Report {
+            k: self.k,
+            rbo_p: self.rbo_p,
+            engines: self.summarize(&reports),
+            queries: reports,
+        }
+    }
+
+    fn score(&self, ours: &[String], theirs: &[String]) -> Scores {
+        Scores {
+            overlap: overlap_at_k(ours, theirs, self.k),
+            rbo: rbo(ours, theirs, self.rbo_p),
+            ndcg: ndcg_at_k(ours, theirs, self.k),
+        }
+    }
+
+    fn summarize(&self, reports: &[QueryReport]) -> Vec<EngineSummary> {
+        self.engines
+            .iter()
+            .map(|engine| {
+                let scores = reports
+                    .iter()
+                    .flat_map(|report| &report.engines)
+                    .filter(|scores| scores.engine == engine.name());
+
+                let mut summary = EngineSummary {
+                    engine: engine.name().to_string(),
+                    queries: 0,
+                    failed: 0,
+                    mean: Scores::default(),
+                };
+
+                for scores in scores {
+                    match &scores.scores {
+                        Some(scores) => {
+                            summary.queries += 1;
+                            summary.mean.overlap += scores.overlap;
+                            summary.mean.rbo += scores.rbo;
+                            summary.mean.ndcg += scores.ndcg;
+                        }
+                        None => summary.failed += 1,
+                    }
+                }
+
+                if summary.queries > 0 {
+                    let n = summary.queries as f64;
+                    summary.mean.overlap /= n;
+                    summary.mean.rbo /= n;
+                    summary.mean.ndcg /= n;
+                }
+
+                summary
+            })
+            .collect()
+    }
+}
+
+/// Normalize a URL for comparison: drop the scheme, `www.`, a trailing slash and the fragment.
+pub fn normalize_url(url: &str) -> String {
+    let Ok(parsed) = url::Url::parse(url.trim()) else {
+        return url.trim().to_lowercase();
+    };
+
+    let host = parsed.host_str().unwrap_or_default();
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    let mut normalized = format!("{host}{}", parsed.path().trim_end_matches('/'));
+
+    if let Some(query) = parsed.query() {
+        normalized.push('?');
+        normalized.push_str(query);
+    }
+
+    normalized
+}
+
+fn normalize_all<'a, I: IntoIterator<Item = S>, S: AsRef<str> + 'a>(urls: I) -> Vec<String> {
+    let mut seen = HashSet::new();
+
+    urls.into_iter()
+        .map(|url| normalize_url(url.as_ref()))
+        .filter(|url| seen.insert(url.clone()))
+        .collect()
+}
+
+/// The fraction of the top `k` results of `ours` that are also in the top `k` of `theirs`.
+pub fn overlap_at_k<T: Eq + std::hash::Hash>(ours: &[T], theirs: &[T], k: usize) -> f64 {
+    if k == 0 {
+        return 0.0;
+    }
+
+    let ours: HashSet<_> = ours.iter().take(k).collect();
+
+    theirs
+        .iter()
+        .take(k)
+        .filter(|result| ours.contains(result))
+        .count() as f64
+        / k as f64
+}
+
+/// Extrapolated rank-biased overlap (Webber et al., 2010) of the two rankings,
+/// evaluated to the depth of the shorter one. 1 for identical rankings and 0 for
+/// disjoint ones. `p` must be in `(0, 1)`.
+pub fn rbo<T: Eq + std::hash::Hash>(ours: &[T], theirs: &[T], p: f64) -> f64 {
+    let depth = ours.len().min(theirs.len());
+
+    if depth == 0 {
+        return 0.0;
+    }
+
+    let mut seen_ours = HashSet::new();
+    let mut seen_theirs = HashSet::new();
+    let mut overlap = 0;
+    let mut sum = 0.0;
+
+    for d in 1..=depth {
+        let (a, b) = (&ours[d - 1], &theirs[d - 1]);
+
+        if a == b {
+            overlap += 1;
+        } else {
+            overlap += usize::from(seen_theirs.contains(a));
+            overlap += usize::from(seen_ours.contains(b));
+        }
+
+        seen_ours.insert(a);
+        seen_theirs.insert(b);
+
+        sum += overlap as f64 / d as f64 * p.powi(d as i32);
+    }
+
+    let agreement = overlap as f64 / depth as f64;
+
+    agreement * p.powi(depth as i32) + (1.0 - p) / p * sum
+}
+
+/// NDCG of the top `k` results of `theirs`, where the relevance of a result is graded by
+/// its position in `ours`: the first of `n` results has relevance `n`, the last `1` and
+/// results that are not in `ours` have relevance 0.
+pub fn ndcg_at_k<T: Eq + std::hash::Hash>(ours: &[T], theirs: &[T], k: usize) -> f64 {
+    let relevance = |result: &T| {
+        ours.iter()
+            .position(|own| own == result)
+            .map_or(0.0, |pos| (ours.len() - pos) as f64)
+    };
+
+    let dcg = |results: &[T]| -> f64 {
+        results
+            .iter()
+            .take(k)
+            .enumerate()
+            .map(|(i, result)| relevance(result) / (i as f64 + 2.0).log2())
+            .sum()
+    };
+
+    let ideal = dcg(ours);
+
+    if ideal == 0.0 {
+        return 0.0;
+    }
+
+    dcg(theirs) / ideal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetch::FixtureFetcher;
+    use crate::Config;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn metrics() {
+        let ours = ["a", "b", "c", "d"];
+
+        assert!(close(overlap_at_k(&ours, &ours, 4), 1.0));
+        assert!(close(overlap_at_k(&ours, &["d", "x", "a", "y"], 4), 0.5));
+        assert!(close(overlap_at_k(&ours, &["a", "b"], 4), 0.5));
+
+        assert!(close(rbo(&ours, &ours, 0.9), 1.0));
+        assert!(close(rbo(&ours, &["w", "x", "y", "z"], 0.9), 0.0));
+        assert!(rbo(&ours, &["a", "b", "d", "c"], 0.9) > rbo(&ours, &["b", "a", "c", "d"], 0.9));
+
+        assert!(close(ndcg_at_k(&ours, &ours, 4), 1.0));
+        assert!(close(ndcg_at_k(&ours, &["x", "y"], 4), 0.0));
+        assert!(ndcg_at_k(&ours, &["a", "x"], 4) > ndcg_at_k(&ours, &["x", "a"], 4));
+
+        assert_eq!(
+            normalize_url("https://www.Example.com/a/?b=c#d"),
+            normalize_url("http://example.com/a?b=c")
+        );
+    }
+
+    #[tokio::test]
+    async fn compare_fixtures() {
+        let config = Config::new();
+        let engines = vec![
+            config.engine("google").unwrap(),
+            config.engine("startpage").unwrap(),
+        ];
+
+        let queries = vec![
+            Query {
+                query: "Rust".to_string(),
+                results: vec![
+                    "https://www.rust-lang.org/".to_string(),
+                    "https://doc.rust-lang.org/book/".to_string(),
+                    "https://en.wikipedia.org/wiki/Rust_(programming_language)".to_string(),
+                    "https://github.com/rust-lang/rust".to_string(),
+                ],
+            },
+            Query {
+                query: "missing fixture".to_string(),
+                results: vec!["https://example.com/".to_string()],
+            },
+        ];
+
+        let fetcher = FixtureFetcher::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/compare"));
+        let report = Comparison::new(engines).k(4).run(&fetcher, &queries).await;
+
+        let rust = &report.queries[0];
+        let google = rust.engines[0].scores.unwrap();
+        let startpage = rust.engines[1].scores.unwrap();
+
+        assert_eq!(rust.engines[0].results.len(), 4);
+        assert!(close(google.overlap, 1.0));
+        assert!(close(startpage.overlap, 0.5));
+        assert!(google.rbo > startpage.rbo);
+        assert!(google.ndcg > startpage.ndcg);
+
+        assert!(report.queries[1].engines[0].error.is_some());
+        assert_eq!(report.engines[0].queries, 1);
+        assert_eq!(report.engines[0].failed, 1);
+        assert!(close(report.engines[0].mean.overlap, 1.0));
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["k"], 4);
+        assert_eq!(json["engines"][1]["engine"], "startpage");
+    }
+}
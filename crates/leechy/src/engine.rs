@@ -9,27 +9,115 @@
  *
  */
 use kuchiki::traits::*;
+use kuchiki::{XPath, XPathNode, XPathValue};
+use std::collections::HashSet;
+use std::path::Path;
 use std::sync::LazyLock;
 
+use crate::fetch::{Fetcher, HttpFetcher, Request};
+use crate::{Error, RateLimit, Result};
+
 static PARSED_CONFIG: LazyLock<Config> =
-    LazyLock::new(|| toml::from_str(include_str!("conf.toml")).unwrap());
+    LazyLock::new(|| Config::from_toml(include_str!("conf.toml")).unwrap());
+
+/// The placeholders that can be used in a search URL template.
+pub const PLACEHOLDERS: &[&str] = &["{query}", "{page}", "{offset}"];
 
-#[derive(serde::Deserialize, Clone)]
+fn results_per_page() -> usize {
+    10
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 pub struct Config {
-    engines: Vec<EngineConf>,
+    pub engines: Vec<EngineConf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Config {
+    /// The engines bundled with leechy.
     pub fn new() -> Self {
         PARSED_CONFIG.clone()
     }
+
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn engine(&self, name: &str) -> Option<Engine> {
+        self.engines
+            .iter()
+            .find(|e| e.name == name)
+            .cloned()
+            .map(Engine::new)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.engines.iter().map(|e| e.name.as_str())
+    }
 }
 
-#[derive(serde::Deserialize, Clone)]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 pub struct EngineConf {
-    name: String,
-    search: String,
-    xpath: String,
+    pub name: String,
+
+    /// URL template of the result page. `{query}` is replaced by the URL-encoded query,
+    /// `{page}` by the 1-based page number and `{offset}` by the 0-based index of the
+    /// first result on the page.
+    pub search: String,
+
+    /// XPath selecting one node per result, in rank order.
+    pub result: String,
+
+    /// XPath relative to a result for its URL. If it selects an element,
+    /// its `href` attribute is used.
+    pub url: String,
+
+    /// XPath relative to a result for its title.
+    #[serde(default)]
+    pub title: Option<String>,
+
+    /// XPath relative to a result for its snippet.
+    #[serde(default)]
+    pub snippet: Option<String>,
+
+    /// Number of results per page, used for `{offset}`.
+    #[serde(default = "results_per_page")]
+    pub results_per_page: usize,
+
+    /// Query parameter holding the target of redirect links on the engine's own host,
+    /// like `q` in `/url?q=https://example.com/`.
+    #[serde(default)]
+    pub redirect_param: Option<String>,
+
+    /// Rate limit of requests to the engine, instead of the default of the fetcher.
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SearchResult {
+    pub url: Url,
+    pub title: Option<String>,
+    pub snippet: Option<String>,
+
+    /// 1-based position of the result across all fetched pages.
+    pub rank: usize,
+}
+
+struct CompiledXPaths {
+    result: XPath,
+    url: XPath,
+    title: Option<XPath>,
+    snippet: Option<XPath>,
 }
 
 pub struct Engine {
@@ -41,35 +129,170 @@ impl Engine {
         Engine { conf }
     }
 
+    /// An engine bundled with leechy.
     pub fn by_name(name: &str) -> Option<Self> {
-        let conf = Config::new().engines.into_iter().find(|e| e.name == name)?;
-        Some(Engine::new(conf))
+        Config::new().engine(name)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.conf.name
+    }
+
+    pub fn conf(&self) -> &EngineConf {
+        &self.conf
+    }
+
+    /// The URL of a result page for the query. Pages are 1-based.
+    pub fn search_url(&self, query: &str, page: usize) -> Result<url::Url> {
+        let encoded_query: String =
+            url::form_urlencoded::byte_serialize(query.as_bytes()).collect();
+        let page = page.max(1);
+        let offset = (page - 1) * self.conf.results_per_page;
+
+        let url = self
+            .conf
+            .search
+            .replace("{query}", &encoded_query)
+            .replace("{page}", &page.to_string())
+            .replace("{offset}", &offset.to_string());
+
+        url::Url::parse(&url).map_err(|err| Error::InvalidTemplate {
+            engine: self.conf.name.clone(),
+            message: err.to_string(),
+        })
+    }
+
+    fn xpaths(&self) -> Result<CompiledXPaths> {
+        let optional = |xpath: &Option<String>| xpath.as_deref().map(XPath::parse).transpose();
+
+        Ok(CompiledXPaths {
+            result: XPath::parse(&self.conf.result)?,
+            url: XPath::parse(&self.conf.url)?,
+            title: optional(&self.conf.title)?,
+            snippet: optional(&self.conf.snippet)?,
+        })
+    }
+
+    /// Parse a result page fetched from `page_url`, which relative result URLs are
+    /// resolved against. Ranks start at 1 and results without a URL are skipped.
+    pub fn parse(&self, html: &str, page_url: &url::Url) -> Result<Vec<SearchResult>> {
+        let xpaths = self.xpaths()?;
+        let document = kuchiki::parse_html().one(html);
+
+        let mut results = Vec::new();
+
+        for node in xpaths.result.evaluate(&document)?.nodes() {
+            let node = node.node();
+
+            let Some(url) = self.result_url(&xpaths.url.evaluate(node)?, page_url) else {
+                continue;
+            };
+
+            let text = |xpath: &Option<XPath>| -> Result<Option<String>> {
+                Ok(match xpath {
+                    Some(xpath) => Some(normalize(&xpath.evaluate(node)?.string()))
+                        .filter(|text| !text.is_empty()),
+                    None => None,
+                })
+            };
+
+            results.push(SearchResult {
+                url,
+                title: text(&xpaths.title)?,
+                snippet: text(&xpaths.snippet)?,
+                rank: results.len() + 1,
+            });
+        }
+
+        Ok(results)
+    }
+
+    fn result_url(&self, value: &XPathValue, page_url: &url::Url) -> Option<Url> {
+        let href = match value.nodes().first() {
+            Some(XPathNode::Node(node)) => match node.as_element() {
+                Some(elem) => elem.attributes.borrow().get("href")?.to_string(),
+                None => value.string(),
+            },
+            Some(XPathNode::Attribute { value, .. }) => value.clone(),
+            None => value.string(),
+        };
+
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') {
+            return None;
+        }
+
+        let mut url = page_url.join(href).ok()?;
+
+        if let Some(param) = &self.conf.redirect_param {
+            if url.host() == page_url.host() {
+                let target = url
+                    .query_pairs()
+                    .find(|(key, _)| key == param)
+                    .and_then(|(_, target)| url::Url::parse(&target).ok());
+
+                if let Some(target) = target {
+                    url = target;
+                }
+            }
+        }
+
+        matches!(url.scheme(), "http" | "https").then(|| Url(url.to_string()))
+    }
+
+    pub async fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
+        self.search_pages(query, 1).await
+    }
+
+    /// Fetch the first `pages` result pages with the shared [`HttpFetcher`]. Results that were
+    /// already seen on an earlier page are dropped and ranks continue across pages.
+    pub async fn search_pages(&self, query: &str, pages: usize) -> Result<Vec<SearchResult>> {
+        self.search_with(HttpFetcher::shared()?, query, pages).await
     }
 
-    pub async fn search(&self, query: &str) -> Result<Vec<Url>, reqwest::Error> {
-        let encoded_query = query.replace(' ', "+");
-        let url = self.conf.search.replace("{query}", &encoded_query);
-        let useragent = crate::useragent::UserAgent::random_weighted();
+    /// Like [`Engine::search_pages`], but fetches the result pages with `fetcher`.
+    pub async fn search_with<F: Fetcher + ?Sized>(
+        &self,
+        fetcher: &F,
+        query: &str,
+        pages: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let mut results: Vec<SearchResult> = Vec::new();
+        let mut seen = HashSet::new();
+
+        for page in 1..=pages.max(1) {
+            let request = Request {
+                engine: self.name(),
+                query,
+                page,
+                url: self.search_url(query, page)?,
+                rate_limit: self.conf.rate_limit,
+            };
+
+            let body = fetcher.fetch(&request).await?;
 
-        let client = reqwest::Client::builder()
-            .user_agent(useragent.as_str())
-            .build()?;
+            let page_results = self.parse(&body, &request.url)?;
+            if page_results.is_empty() {
+                break;
+            }
 
-        let body = client.get(&url).send().await?.text().await?;
+            for mut result in page_results {
+                if seen.insert(result.url.clone()) {
+                    result.rank = results.len() + 1;
+                    results.push(result);
+                }
+            }
+        }
 
-        Ok(kuchiki::parse_html()
-            .one(body)
-            .select_xpath(&self.conf.xpath)
-            .map(|url| {
-                let attr = url.attributes.borrow();
-                let url = attr.get("href").unwrap();
-                Url(url.to_string())
-            })
-            .collect())
+        Ok(results)
     }
 }
 
-#[derive(Debug)]
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct Url(String);
 
 impl std::fmt::Display for Url {
@@ -83,3 +306,82 @@ impl AsRef<str> for Url {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_config() {
+        let config = Config::new();
+        assert_eq!(
+            config.names().collect::<Vec<_>>(),
+            vec!["startpage", "google"]
+        );
+
+        for engine in &config.engines {
+            let engine = Engine::new(engine.clone());
+            assert!(engine.xpaths().is_ok(), "{}", engine.name());
+        }
+    }
+
+    #[test]
+    fn search_url() {
+        let engine = Engine::by_name("google").unwrap();
+
+        assert_eq!(
+            engine.search_url("rust & c++ 100%", 1).unwrap().as_str(),
+            "https://www.google.com/search?q=rust+%26+c%2B%2B+100%25&udm=14&hl=en-US&start=0"
+        );
+        assert!(engine
+            .search_url("rust", 3)
+            .unwrap()
+            .as_str()
+            .ends_with("&start=20"));
+
+        let engine = Engine::by_name("startpage").unwrap();
+        assert_eq!(
+            engine.search_url("a b", 2).unwrap().as_str(),
+            "https://www.startpage.com/sp/search?query=a+b&page=2"
+        );
+    }
+
+    #[test]
+    fn parse_fixtures() {
+        let engine = Engine::by_name("google").unwrap();
+        let page_url = engine.search_url("rust", 1).unwrap();
+        let results = engine
+            .parse(include_str!("../fixtures/google.html"), &page_url)
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].url.as_ref(), "https://www.rust-lang.org/");
+        assert_eq!(
+            results[0].title.as_deref(),
+            Some("Rust Programming Language")
+        );
+        assert!(results[0]
+            .snippet
+            .as_deref()
+            .unwrap()
+            .starts_with("A language"));
+        assert_eq!(
+            results[2].url.as_ref(),
+            "https://en.wikipedia.org/wiki/Rust_(programming_language)"
+        );
+        assert_eq!(results[2].rank, 3);
+
+        let engine = Engine::by_name("startpage").unwrap();
+        let page_url = engine.search_url("rust", 1).unwrap();
+        let results = engine
+            .parse(include_str!("../fixtures/startpage.html"), &page_url)
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].url.as_ref(), "https://doc.rust-lang.org/book/");
+        assert_eq!(
+            results[1].title.as_deref(),
+            Some("The Rust Programming Language - The Rust Programming Language")
+        );
+    }
+}
@@ -0,0 +1,273 @@
+/**
+ * @file fetch.rs
+ * @author Krisna Pranav
+ * @brief fetch
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::ratelimit::{RateLimit, TokenBucket};
+use crate::useragent::UserAgent;
+use crate::Result;
+
+/// A result page to fetch.
+#[derive(Debug, Clone)]
+pub struct Request<'a> {
+    pub engine: &'a str,
+    pub query: &'a str,
+
+    /// 1-based page number.
+    pub page: usize,
+    pub url: url::Url,
+
+    /// Rate limit of the engine, if it has its own.
+    pub rate_limit: Option<RateLimit>,
+}
+
+/// Fetches result pages for an engine.
+#[async_trait]
+pub trait Fetcher: Send + Sync {
+    async fn fetch(&self, request: &Request<'_>) -> Result<String>;
+}
+
+#[async_trait]
+impl<F: Fetcher + ?Sized> Fetcher for Box<F> {
+    async fn fetch(&self, request: &Request<'_>) -> Result<String> {
+        (**self).fetch(request).await
+    }
+}
+
+static SHARED: OnceLock<HttpFetcher> = OnceLock::new();
+
+/// Fetches result pages over HTTP with a single client.
+///
+/// Requests to each engine are throttled by a token bucket with the rate limit of the
+/// engine, or the default rate limit of the fetcher. Responses with status 429 or 5xx are
+/// retried with exponential backoff, honouring a `Retry-After` header in seconds.
+pub struct HttpFetcher {
+    client: reqwest::Client,
+    buckets: std::sync::Mutex<HashMap<String, Arc<TokenBucket>>>,
+    default_rate_limit: RateLimit,
+    retries: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl HttpFetcher {
+    pub fn new() -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .user_agent(UserAgent::random_weighted().as_str())
+            .build()?;
+
+        Ok(Self {
+            client,
+            buckets: std::sync::Mutex::new(HashMap::new()),
+            default_rate_limit: RateLimit::default(),
+            retries: 3,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        })
+    }
+
+    /// The fetcher used by [`Engine::search_pages`](crate::Engine::search_pages). It is
+    /// built on first use, and building it again is attempted if that fails.
+    pub fn shared() -> Result<&'static Self> {
+        if let Some(fetcher) = SHARED.get() {
+            return Ok(fetcher);
+        }
+
+        let fetcher = Self::new()?;
+        Ok(SHARED.get_or_init(|| fetcher))
+    }
+
+    /// Rate limit of engines without their own. Defaults to one request per second.
+    pub fn default_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.default_rate_limit = limit;
+        self
+    }
+
+    /// Number of retries after the first attempt. Defaults to 3.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Delay before the first retry, doubled for every following retry up to `max`.
+    /// Defaults to one second and a minute.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    fn bucket(&self, request: &Request<'_>) -> Arc<TokenBucket> {
+        let limit = request.rate_limit.unwrap_or(self.default_rate_limit);
+
+        self.buckets
+            .lock()
+            .unwrap()
+            .entry(request.engine.to_string())
+            .or_insert_with(|| Arc::new(TokenBucket::new(limit)))
+            .clone()
+    }
+}
+
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let seconds = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+
+    Some(Duration::from_secs(seconds))
+}
+
+#[async_trait]
+impl Fetcher for HttpFetcher {
+    async fn fetch(&self, request: &Request<'_>) -> Result<String> {
+        let bucket = self.bucket(request);
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+
+        loop {
+            bucket.acquire().await;
+
+            let response = self.client.get(request.url.clone()).send().await?;
+            let status = response.status();
+
+            let retry =
+                status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+
+            if !retry || attempt >= self.retries {
+                return Ok(response.error_for_status()?.text().await?);
+            }
+
+            let delay = retry_after(&response)
+                .unwrap_or(backoff)
+                .min(self.max_backoff);
+            tokio::time::sleep(delay).await;
+
+            backoff = (backoff * 2).min(self.max_backoff);
+            attempt += 1;
+        }
+    }
+}
+
+/// Reads saved result pages from `<dir>/<engine>/<query>.html`, where the query is
+/// lowercased with every run of other characters than letters and digits replaced by `-`.
+/// Later pages are read from `<query>-<page>.html`.
+pub struct FixtureFetcher {
+    dir: PathBuf,
+}
+
+impl FixtureFetcher {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn path(&self, request: &Request<'_>) -> PathBuf {
+        let name = match request.page {
+            0 | 1 => format!("{}.html", slug(request.query)),
+            page => format!("{}-{page}.html", slug(request.query)),
+        };
+
+        self.dir.join(request.engine).join(name)
+    }
+}
+
+#[async_trait]
+impl Fetcher for FixtureFetcher {
+    async fn fetch(&self, request: &Request<'_>) -> Result<String> {
+        Ok(std::fs::read_to_string(self.path(request))?)
+    }
+}
+
+pub(crate) fn slug(query: &str) -> String {
+    query
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Serve one response per connection with the given statuses, then 200.
+    async fn serve(statuses: Vec<u16>) -> url::Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = url::Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+
+        tokio::spawn(async move {
+            let mut statuses = statuses.into_iter();
+
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0; 1024];
+                let _ = socket.read(&mut buf).await;
+
+                let status = statuses.next().unwrap_or(200);
+                let body = format!("status {status}");
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        url
+    }
+
+    fn request(url: url::Url) -> Request<'static> {
+        Request {
+            engine: "test",
+            query: "rust",
+            page: 1,
+            url,
+            rate_limit: Some(RateLimit {
+                requests_per_second: 0.0,
+                burst: 1,
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn retries() {
+        let fetcher = HttpFetcher::new()
+            .unwrap()
+            .retries(2)
+            .backoff(Duration::from_millis(20), Duration::from_millis(100));
+
+        let url = serve(vec![429, 503]).await;
+        let start = Instant::now();
+        assert_eq!(fetcher.fetch(&request(url)).await.unwrap(), "status 200");
+        assert!(start.elapsed() >= Duration::from_millis(60));
+
+        let url = serve(vec![500, 500, 500]).await;
+        assert!(fetcher.fetch(&request(url)).await.is_err());
+
+        let url = serve(vec![404]).await;
+        assert!(fetcher.fetch(&request(url)).await.is_err());
+    }
+}
@@ -8,7 +8,46 @@
  * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
  *
  */
+pub mod cache;
+pub mod compare;
 mod engine;
+pub mod fetch;
+mod ratelimit;
 mod useragent;
+pub mod validate;
 
-pub use engine::Engine;
+pub use engine::{Config, Engine, EngineConf, SearchResult, Url, PLACEHOLDERS};
+pub use ratelimit::{RateLimit, TokenBucket};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("failed to parse engine config: {0}")]
+    Config(#[from] toml::de::Error),
+
+    #[error("failed to parse json: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("xpath error: {0}")]
+    XPath(#[from] kuchiki::Error),
+
+    #[error("invalid search template for engine '{engine}': {message}")]
+    InvalidTemplate { engine: String, message: String },
+
+    #[error("page {page} of '{query}' from engine '{engine}' is not cached")]
+    NotCached {
+        engine: String,
+        query: String,
+        page: usize,
+    },
+
+    #[error("unknown engine '{0}'")]
+    UnknownEngine(String),
+}
@@ -0,0 +1,198 @@
+/**
+ * @file main.rs
+ * @author Krisna Pranav
+ * @brief main
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use leechy::cache::CachedFetcher;
+use leechy::compare::{load_queries, Comparison};
+use leechy::fetch::{Fetcher, FixtureFetcher, HttpFetcher};
+use leechy::{validate, Config, Error};
+
+#[derive(Parser)]
+#[command(version, about = "Scrape search engine result pages")]
+struct Args {
+    /// Engine config to use instead of the bundled engines.
+    #[arg(short, long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Cache result pages in this directory and reuse them on later runs.
+    #[arg(long, global = true)]
+    cache: Option<PathBuf>,
+
+    /// Only use cached result pages.
+    #[arg(long, global = true, requires = "cache")]
+    offline: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check the templates and xpaths of every engine against saved result pages.
+    Validate {
+        /// Directory with a `<engine>.html` result page per engine.
+        #[arg(short, long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"))]
+        fixtures: PathBuf,
+    },
+
+    /// Search with an engine and print the results.
+    Search {
+        engine: String,
+        query: String,
+
+        #[arg(short, long, default_value_t = 1)]
+        pages: usize,
+    },
+
+    /// Compare the results of engines with our own results and print a JSON report.
+    Compare {
+        /// JSON array of `{"query": .., "results": [..]}` with our own results.
+        queries: PathBuf,
+
+        /// Engines to compare with. Defaults to every configured engine.
+        #[arg(short, long)]
+        engine: Vec<String>,
+
+        /// Cutoff for overlap and NDCG.
+        #[arg(short, default_value_t = 10)]
+        k: usize,
+
+        #[arg(short, long, default_value_t = 1)]
+        pages: usize,
+
+        /// Read result pages from `<dir>/<engine>/<query>.html` instead of fetching them.
+        #[arg(short, long)]
+        fixtures: Option<PathBuf>,
+
+        /// Write the report to a file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+impl Args {
+    fn fetcher(&self, fixtures: Option<PathBuf>) -> leechy::Result<Box<dyn Fetcher>> {
+        let fetcher: Box<dyn Fetcher> = match fixtures {
+            Some(dir) => Box::new(FixtureFetcher::new(dir)),
+            None => Box::new(HttpFetcher::new()?),
+        };
+
+        Ok(match &self.cache {
+            Some(dir) => Box::new(CachedFetcher::new(fetcher, dir).offline(self.offline)),
+            None => fetcher,
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let config = match &args.config {
+        Some(path) => Config::open(path)?,
+        None => Config::new(),
+    };
+
+    match &args.command {
+        Command::Validate { fixtures } => {
+            let mut failed = false;
+
+            for conf in &config.engines {
+                let path = fixtures.join(format!("{}.html", conf.name));
+                let fixture = std::fs::read_to_string(&path).ok();
+
+                let report = validate::validate(conf, fixture.as_deref());
+
+                match (report.is_ok(), report.results) {
+                    (true, Some(results)) => println!("{}: ok ({results} results)", report.engine),
+                    (true, None) => {
+                        println!("{}: ok (no fixture at {})", report.engine, path.display())
+                    }
+                    (false, _) => {
+                        failed = true;
+                        println!("{}: failed", report.engine);
+
+                        for problem in &report.problems {
+                            println!("  - {problem}");
+                        }
+                    }
+                }
+            }
+
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Command::Search {
+            engine,
+            query,
+            pages,
+        } => {
+            let engine = config
+                .engine(engine)
+                .ok_or_else(|| Error::UnknownEngine(engine.clone()))?;
+            let fetcher = args.fetcher(None)?;
+
+            for result in engine.search_with(&*fetcher, query, *pages).await? {
+                println!("{}. {}", result.rank, result.url);
+
+                if let Some(title) = result.title {
+                    println!("   {title}");
+                }
+
+                if let Some(snippet) = result.snippet {
+                    println!("   {snippet}");
+                }
+            }
+        }
+        Command::Compare {
+            queries,
+            engine,
+            k,
+            pages,
+            fixtures,
+            output,
+        } => {
+            let engines = if engine.is_empty() {
+                config
+                    .names()
+                    .filter_map(|name| config.engine(name))
+                    .collect()
+            } else {
+                engine
+                    .iter()
+                    .map(|name| {
+                        config
+                            .engine(name)
+                            .ok_or_else(|| Error::UnknownEngine(name.clone()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+            };
+
+            let queries = load_queries(queries)?;
+            let fetcher = args.fetcher(fixtures.clone())?;
+
+            let report = Comparison::new(engines)
+                .k(*k)
+                .pages(*pages)
+                .run(&*fetcher, &queries)
+                .await;
+
+            match output {
+                Some(path) => std::fs::write(path, report.to_json())?,
+                None => println!("{}", report.to_json()),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+/**
+ * @file ratelimit.rs
+ * @author Krisna Pranav
+ * @brief ratelimit
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+/// Longest single sleep while waiting for a token. Tiny rates would otherwise wait
+/// longer than a `Duration` can hold.
+const MAX_WAIT: Duration = Duration::from_secs(60 * 60);
+
+fn burst() -> u32 {
+    1
+}
+
+/// How fast requests may be sent to an engine.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// Sustained number of requests per second. Zero, negative or NaN disables the limit.
+    pub requests_per_second: f64,
+
+    /// Number of requests that may be sent at once after being idle.
+    #[serde(default = "burst")]
+    pub burst: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 1.0,
+            burst: burst(),
+        }
+    }
+}
+
+struct State {
+    tokens: f64,
+    last: Instant,
+}
+
+/// A token bucket that starts full. Waiters are served in order.
+pub struct TokenBucket {
+    limit: RateLimit,
+    state: Mutex<State>,
+}
+
+impl TokenBucket {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            state: Mutex::new(State {
+                tokens: limit.burst.max(1) as f64,
+                last: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Wait until a request may be sent.
+    pub async fn acquire(&self) {
+        let capacity = self.limit.burst.max(1) as f64;
+        let rate = self.limit.requests_per_second;
+
+        let mut state = self.state.lock().await;
+
+        loop {
+            let now = Instant::now();
+            let elapsed = now.duration_since(state.last).as_secs_f64();
+            state.tokens = (state.tokens + elapsed * rate).min(capacity);
+            state.last = now;
+
+            if state.tokens >= 1.0 || rate <= 0.0 || rate.is_nan() {
+                state.tokens = (state.tokens - 1.0).max(0.0);
+                return;
+            }
+
+            let wait = Duration::try_from_secs_f64((1.0 - state.tokens) / rate)
+                .unwrap_or(MAX_WAIT)
+                .min(MAX_WAIT);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn token_bucket() {
+        let bucket = TokenBucket::new(RateLimit {
+            requests_per_second: 20.0,
+            burst: 2,
+        });
+
+        let start = Instant::now();
+        bucket.acquire().await;
+        bucket.acquire().await;
+        assert!(start.elapsed() < Duration::from_millis(40));
+
+        bucket.acquire().await;
+        bucket.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn tiny_rate() {
+        let bucket = TokenBucket::new(RateLimit {
+            requests_per_second: 1e-300,
+            burst: 1,
+        });
+
+        bucket.acquire().await;
+        let second = tokio::time::timeout(Duration::from_millis(20), bucket.acquire()).await;
+        assert!(second.is_err());
+
+        let bucket = TokenBucket::new(RateLimit {
+            requests_per_second: f64::NAN,
+            burst: 1,
+        });
+        bucket.acquire().await;
+        bucket.acquire().await;
+    }
+}
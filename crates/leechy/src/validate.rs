@@ -0,0 +1,271 @@
+/**
+ * @file validate.rs
+ * @author Krisna Pranav
+ * @brief validate
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use kuchiki::traits::*;
+use kuchiki::XPath;
+
+use crate::{Engine, EngineConf, PLACEHOLDERS};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    #[error("search template has no {{query}} placeholder")]
+    MissingQuery,
+
+    #[error("search template uses '{0}', which is never substituted")]
+    UnknownPlaceholder(String),
+
+    #[error("search template does not give a valid URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("invalid {field} xpath: {message}")]
+    InvalidXPath {
+        field: &'static str,
+        message: String,
+    },
+
+    #[error("no results found in fixture")]
+    NoResults,
+
+    #[error("{missing} of {total} results have no url")]
+    MissingUrls { missing: usize, total: usize },
+
+    #[error("{missing} of {total} results have no title")]
+    MissingTitles { missing: usize, total: usize },
+
+    #[error("{missing} of {total} results have no snippet")]
+    MissingSnippets { missing: usize, total: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub engine: String,
+
+    /// Number of results parsed from the fixture, if one was checked.
+    pub results: Option<usize>,
+
+    pub problems: Vec<Problem>,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Check the search template and xpaths of an engine. If a saved result page is given,
+/// it must give at least one result and every result must have a url, and a title and
+/// snippet if the engine extracts them.
+pub fn validate(conf: &EngineConf, fixture: Option<&str>) -> Report {
+    let mut problems = template_problems(&conf.search);
+
+    let engine = Engine::new(conf.clone());
+
+    let page_url = match engine.search_url("leechy", 1) {
+        Ok(url) => Some(url),
+        Err(err) => {
+            problems.push(Problem::InvalidUrl(err.to_string()));
+            None
+        }
+    };
+
+    let xpaths = [
+        ("result", Some(&conf.result)),
+        ("url", Some(&conf.url)),
+        ("title", conf.title.as_ref()),
+        ("snippet", conf.snippet.as_ref()),
+    ];
+
+    for (field, xpath) in xpaths {
+        if let Some(Err(err)) = xpath.map(|xpath| XPath::parse(xpath)) {
+            problems.push(Problem::InvalidXPath {
+                field,
+                message: err.to_string(),
+            });
+        }
+    }
+
+    let mut results = None;
+
+    if let (Some(fixture), Some(page_url), true) = (fixture, page_url, problems.is_empty()) {
+        match fixture_problems(&engine, fixture, &page_url) {
+            Ok((count, fixture_problems)) => {
+                results = Some(count);
+                problems.extend(fixture_problems);
+            }
+            Err(err) => problems.push(Problem::InvalidXPath {
+                field: "result",
+                message: err.to_string(),
+            }),
+        }
+    }
+
+    Report {
+        engine: conf.name.clone(),
+        results,
+        problems,
+    }
+}
+
+fn template_problems(template: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    if !template.contains("{query}") {
+        problems.push(Problem::MissingQuery);
+    }
+
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+
+        let placeholder = &rest[start..start + len + 1];
+        let prefixed = start > 0 && rest.as_bytes()[start - 1] == b'$';
+
+        if prefixed {
+            problems.push(Problem::UnknownPlaceholder(format!("${placeholder}")));
+        } else if !PLACEHOLDERS.contains(&placeholder) {
+            problems.push(Problem::UnknownPlaceholder(placeholder.to_string()));
+        }
+
+        rest = &rest[start + len + 1..];
+    }
+
+    problems
+}
+
+fn fixture_problems(
+    engine: &Engine,
+    fixture: &str,
+    page_url: &url::Url,
+) -> crate::Result<(usize, Vec<Problem>)> {
+    let conf = engine.conf();
+    let mut problems = Vec::new();
+
+    let document = kuchiki::parse_html().one(fixture);
+    let total = XPath::parse(&conf.result)?
+        .evaluate(&document)?
+        .nodes()
+        .len();
+
+    let results = engine.parse(fixture, page_url)?;
+
+    if total == 0 {
+        problems.push(Problem::NoResults);
+        return Ok((0, problems));
+    }
+
+    if results.len() < total {
+        problems.push(Problem::MissingUrls {
+            missing: total - results.len(),
+            total,
+        });
+    }
+
+    if conf.title.is_some() {
+        let missing = results.iter().filter(|r| r.title.is_none()).count();
+        if missing > 0 {
+            problems.push(Problem::MissingTitles { missing, total });
+        }
+    }
+
+    if conf.snippet.is_some() {
+        let missing = results.iter().filter(|r| r.snippet.is_none()).count();
+        if missing > 0 {
+            problems.push(Problem::MissingSnippets { missing, total });
+        }
+    }
+
+    Ok((results.len(), problems))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    fn conf(search: &str, result: &str) -> EngineConf {
+        EngineConf {
+            name: "test".to_string(),
+            search: search.to_string(),
+            result: result.to_string(),
+            url: "./a".to_string(),
+            title: Some("./a".to_string()),
+            snippet: None,
+            results_per_page: 10,
+            redirect_param: None,
+            rate_limit: None,
+        }
+    }
+
+    #[test]
+    fn bundled_fixtures() {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures");
+
+        for conf in Config::new().engines {
+            let fixture =
+                std::fs::read_to_string(format!("{fixtures}/{}.html", conf.name)).unwrap();
+            let report = validate(&conf, Some(&fixture));
+
+            assert!(report.is_ok(), "{}: {:?}", conf.name, report.problems);
+            assert!(report.results.unwrap() > 0);
+        }
+    }
+
+    #[test]
+    fn templates() {
+        let report = validate(&conf("https://example.com/?q=${query}", "//li"), None);
+        assert_eq!(
+            report.problems,
+            vec![Problem::UnknownPlaceholder("${query}".to_string())]
+        );
+
+        let report = validate(&conf("https://example.com/?p={page}&n={num}", "//li"), None);
+        assert_eq!(
+            report.problems,
+            vec![
+                Problem::MissingQuery,
+                Problem::UnknownPlaceholder("{num}".to_string())
+            ]
+        );
+
+        let report = validate(&conf("example.com/?q={query}", "//li["), None);
+        assert!(matches!(report.problems[0], Problem::InvalidUrl(_)));
+        assert!(matches!(
+            report.problems[1],
+            Problem::InvalidXPath {
+                field: "result",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn fixture() {
+        let conf = conf("https://example.com/?q={query}", "//li");
+
+        let report = validate(
+            &conf,
+            Some("<ul><li><a href='/a'>A</a></li><li>B</li></ul>"),
+        );
+        assert_eq!(report.results, Some(1));
+        assert_eq!(
+            report.problems,
+            vec![Problem::MissingUrls {
+                missing: 1,
+                total: 2
+            }]
+        );
+
+        let report = validate(&conf, Some("<p>nothing</p>"));
+        assert_eq!(report.problems, vec![Problem::NoResults]);
+    }
+}
@@ -48,6 +48,37 @@ pub enum Error {
     Pattern,
 }
 
+impl Error {
+    /// The byte range of the source the error points at. Errors at the end of the
+    /// source and errors that are not about a single token have no span.
+    pub fn span(&self) -> Option<(usize, usize)> {
+        match self {
+            Error::UnexpectedToken {
+                token: (start, _, end),
+                ..
+            }
+            | Error::UnrecognizedToken {
+                token: (start, _, end),
+            }
+            | Error::NumberParse {
+                token: (start, _, end),
+            } => Some((*start, *end)),
+            Error::Unknown(start, end) => Some((*start, *end)),
+            Error::UnexpectedEof { .. } | Error::RankingStagesMismatch | Error::Pattern => None,
+        }
+    }
+
+    /// The tokens the parser expected instead.
+    pub fn expected(&self) -> &[String] {
+        match self {
+            Error::UnexpectedEof { expected } | Error::UnexpectedToken { expected, .. } => {
+                expected
+            }
+            _ => &[],
+        }
+    }
+}
+
 pub fn parse(optic: &str) -> Result<Optic> {
     let raw_optic = ast::parse(optic)?;
 
@@ -356,6 +387,47 @@ impl Optic {
     pub fn parse(optic: &str) -> Result<Self> {
         parse(optic)
     }
+
+    /// Merge two optics. The rules of both are kept in order without duplicates, and
+    /// non-matching results are discarded if either optic discards them. A host that is
+    /// ranked by both keeps its strongest preference: blocked over disliked over liked.
+    #[must_use]
+    pub fn merge(mut self, other: Optic) -> Optic {
+        for rule in other.rules {
+            if !self.rules.contains(&rule) {
+                self.rules.push(rule);
+            }
+        }
+
+        self.discard_non_matching |= other.discard_non_matching;
+        self.host_rankings.merge_into(other.host_rankings);
+
+        let HostRankings {
+            liked,
+            disliked,
+            blocked,
+        } = self.host_rankings;
+
+        let blocked: Vec<_> = blocked.into_iter().unique().collect();
+        let disliked: Vec<_> = disliked
+            .into_iter()
+            .unique()
+            .filter(|host| !blocked.contains(host))
+            .collect();
+        let liked = liked
+            .into_iter()
+            .unique()
+            .filter(|host| !blocked.contains(host) && !disliked.contains(host))
+            .collect();
+
+        self.host_rankings = HostRankings {
+            liked,
+            disliked,
+            blocked,
+        };
+
+        self
+    }
 }
 
 impl Display for Optic {
@@ -550,3 +622,84 @@ impl HostRankings {
         self.blocked.extend(host_rankings.blocked);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_roundtrip() {
+        let source = r#"
+            DiscardNonMatching;
+            Rule {
+                Matches {
+                    Site("|example.com|"),
+                    Url("/blog*")
+                },
+                Matches {
+                    Title("rust")
+                },
+                Action(Boost(3))
+            };
+            Rule {
+                Matches {
+                    Site("|spam.com|")
+                },
+                Action(Discard)
+            };
+            Like(Site("docs.rs"));
+            Dislike(Site("w3schools.com"));
+        "#;
+
+        let optic = Optic::parse(source).unwrap();
+        assert_eq!(optic.host_rankings.blocked, vec!["spam.com".to_string()]);
+        assert_eq!(Optic::parse(&optic.to_string()).unwrap(), optic);
+    }
+
+    #[test]
+    fn merge() {
+        let a = Optic::parse(
+            r#"
+            Rule { Matches { Title("rust") }, Action(Boost(3)) };
+            Like(Site("docs.rs"));
+            Like(Site("example.com"));
+        "#,
+        )
+        .unwrap();
+
+        let b = Optic::parse(
+            r#"
+            DiscardNonMatching;
+            Rule { Matches { Title("rust") }, Action(Boost(3)) };
+            Rule { Matches { Title("go") }, Action(Downrank(1)) };
+            Like(Site("docs.rs"));
+            Dislike(Site("example.com"));
+            Rule { Matches { Site("|spam.com|") }, Action(Discard) };
+        "#,
+        )
+        .unwrap();
+
+        let merged = a.merge(b);
+
+        assert!(merged.discard_non_matching);
+        assert_eq!(merged.rules.len(), 2);
+        assert_eq!(
+            merged.host_rankings,
+            HostRankings {
+                liked: vec!["docs.rs".to_string()],
+                disliked: vec!["example.com".to_string()],
+                blocked: vec!["spam.com".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn error_span() {
+        let source = "Rule { Matches { Site(\"a\") }, Action(Boost(1)) };\nLike(Title(\"a\"))";
+        let err = Optic::parse(source).unwrap_err();
+
+        let (start, end) = err.span().unwrap();
+        assert_eq!(&source[start..end], "Title");
+        assert!(err.expected().contains(&"\"Site\"".to_string()));
+    }
+}
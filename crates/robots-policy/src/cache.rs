@@ -0,0 +1,619 @@
+/**
+ * @file cache.rs
+ * @author Krisna Pranav
+ * @brief robots-policy[cache]
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use url::Url;
+
+use crate::{Params, Robots};
+
+const DEFAULT_EXPIRY: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+const UNREACHABLE_GRACE_PERIOD: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const MAX_REDIRECTS: usize = 5;
+
+/// The status of URLs with an opaque origin, like `data:` URLs. They have no robots.txt,
+/// so they are never cached.
+static ALLOW_ALL: RobotsStatus = RobotsStatus::AllowAll;
+
+/// The result of fetching a robots.txt file.
+#[derive(Debug, Clone)]
+pub enum FetchOutcome {
+    /// A 2xx response with the body of the file.
+    Success(String),
+    /// A 3xx response pointing to the given location.
+    Redirect(Url),
+    /// Any other HTTP status code.
+    Status(u16),
+    Timeout,
+    NetworkError,
+} // pub enum FetchOutcome
+
+/// Fetches robots.txt files on behalf of the [`RobotsCache`].
+pub trait RobotsFetcher {
+    fn fetch(&self, url: &Url) -> FetchOutcome;
+} // pub trait RobotsFetcher
+
+impl<F> RobotsFetcher for F
+where
+    F: Fn(&Url) -> FetchOutcome,
+{
+    fn fetch(&self, url: &Url) -> FetchOutcome {
+        self(url)
+    }
+} // impl<F> RobotsFetcher for F
+
+/// What the crawler should assume about an origin, as defined by RFC 9309.
+#[derive(Debug, Clone)]
+pub enum RobotsStatus {
+    /// The file was fetched and parsed.
+    Parsed(Robots),
+    /// The file is unavailable (4xx other than 429, or too many redirects), so everything may be crawled.
+    AllowAll,
+    /// The file is unreachable (429, 5xx or network errors), so nothing may be crawled until it has been retried.
+    DisallowAll,
+} // pub enum RobotsStatus
+
+impl RobotsStatus {
+    pub fn is_allowed(&self, url: &Url) -> bool {
+        match self {
+            RobotsStatus::Parsed(robots) => robots.is_allowed(url),
+            RobotsStatus::AllowAll => true,
+            RobotsStatus::DisallowAll => false,
+        }
+    } // pub fn is_allowed(&self, url: &Url) -> bool
+
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        match self {
+            RobotsStatus::Parsed(robots) => robots.crawl_delay(),
+            _ => None,
+        }
+    } // pub fn crawl_delay(&self) -> Option<Duration>
+
+    pub fn sitemaps(&self) -> &[String] {
+        match self {
+            RobotsStatus::Parsed(robots) => robots.sitemaps(),
+            _ => &[],
+        }
+    } // pub fn sitemaps(&self) -> &[String]
+} // impl RobotsStatus
+
+#[derive(Debug)]
+struct Entry {
+    status: RobotsStatus,
+    expires: Instant,
+    last_parsed: Option<Robots>,
+    unreachable_since: Option<Instant>,
+} // struct Entry
+
+/// Caches the parsed robots.txt of every origin and decides what
+/// fetch failures mean for the crawler.
+#[derive(Debug)]
+pub struct RobotsCache {
+    useragent: String,
+    params: Params,
+    expiry: Duration,
+    retry_after: Duration,
+    entries: HashMap<String, Entry>,
+} // pub struct RobotsCache
+
+impl RobotsCache {
+    pub fn new(useragent: &str) -> Self {
+        Self {
+            useragent: useragent.to_string(),
+            params: Params::default(),
+            expiry: DEFAULT_EXPIRY,
+            retry_after: DEFAULT_RETRY_AFTER,
+            entries: HashMap::new(),
+        }
+    } // pub fn new(useragent: &str) -> Self
+
+    /// How long a fetched robots.txt is kept before it is fetched again.
+    pub fn with_expiry(mut self, expiry: Duration) -> Self {
+        self.expiry = expiry;
+        self
+    } // pub fn with_expiry(mut self, expiry: Duration) -> Self
+
+    /// How long to wait before retrying an unreachable robots.txt.
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = retry_after;
+        self
+    } // pub fn with_retry_after(mut self, retry_after: Duration) -> Self
+
+    pub fn with_params(mut self, params: Params) -> Self {
+        self.params = params;
+        self
+    } // pub fn with_params(mut self, params: Params) -> Self
+
+    fn key(url: &Url) -> Option<String> {
+        let origin = url.origin();
+        origin.is_tuple().then(|| origin.ascii_serialization())
+    } // fn key(url: &Url) -> Option<String>
+
+    fn robots_url(url: &Url) -> Option<Url> {
+        let mut robots_url = url.clone();
+        robots_url.set_query(None);
+        robots_url.set_fragment(None);
+        robots_url.set_path("/robots.txt");
+
+        if robots_url.cannot_be_a_base() || !robots_url.origin().is_tuple() {
+            return None;
+        }
+
+        Some(robots_url)
+    } // fn robots_url(url: &Url) -> Option<Url>
+
+    /// The cached status for the origin of `url`, if it has not expired.
+    pub fn cached(&self, url: &Url) -> Option<&RobotsStatus> {
+        self.entries
+            .get(&Self::key(url)?)
+            .filter(|entry| entry.expires > Instant::now())
+            .map(|entry| &entry.status)
+    } // pub fn cached(&self, url: &Url) -> Option<&RobotsStatus>
+
+    /// The status for the origin of `url`, fetching the robots.txt if it isn't cached.
+    pub fn status<F: RobotsFetcher>(&mut self, url: &Url, fetcher: &F) -> &RobotsStatus {
+        let Some(key) = Self::key(url) else {
+            return &ALLOW_ALL;
+        };
+        let now = Instant::now();
+
+        let is_fresh = self
+            .entries
+            .get(&key)
+            .map(|entry| entry.expires > now)
+            .unwrap_or(false);
+
+        if !is_fresh {
+            let outcome = match Self::robots_url(url) {
+                Some(robots_url) => Self::fetch_following_redirects(robots_url, fetcher),
+                None => FetchOutcome::Status(404),
+            };
+
+            self.insert(url, outcome);
+        }
+
+        &self.entries[&key].status
+    } // pub fn status<F: RobotsFetcher>(&mut self, url: &Url, fetcher: &F) -> &RobotsStatus
+
+    fn fetch_following_redirects<F: RobotsFetcher>(url: Url, fetcher: &F) -> FetchOutcome {
+        let mut url = url;
+
+        for _ in 0..=MAX_REDIRECTS {
+            match fetcher.fetch(&url) {
+                FetchOutcome::Redirect(location) => url = location,
+                outcome => return outcome,
+            }
+        }
+
+        FetchOutcome::Redirect(url)
+    } // fn fetch_following_redirects<F: RobotsFetcher>(url: Url, fetcher: &F) -> FetchOutcome
+
+    /// Record the outcome of fetching the robots.txt for the origin of `url`.
+    /// A `Redirect` outcome means that the redirect chain was not resolved.
+    pub fn insert(&mut self, url: &Url, outcome: FetchOutcome) -> &RobotsStatus {
+        let Some(key) = Self::key(url) else {
+            return &ALLOW_ALL;
+        };
+        let now = Instant::now();
+        let previous = self.entries.remove(&key);
+
+        let (last_parsed, unreachable_since) = match previous {
+            Some(entry) => (entry.last_parsed, entry.unreachable_since),
+            None => (None, None),
+        };
+
+        let entry = match outcome {
+            FetchOutcome::Success(body) => {
+                match Robots::parse_with_params(&self.useragent, &body, self.params) {
+                    Ok(robots) => Entry {
+                        status: RobotsStatus::Parsed(robots.clone()),
+                        expires: now + self.expiry,
+                        last_parsed: Some(robots),
+                        unreachable_since: None,
+                    },
+                    Err(_) => Entry {
+                        status: RobotsStatus::AllowAll,
+                        expires: now + self.expiry,
+                        last_parsed,
+                        unreachable_since: None,
+                    },
+                }
+            }
+            FetchOutcome::Redirect(_) => Entry {
+                status: RobotsStatus::AllowAll,
+                expires: now + self.expiry,
+                last_parsed,
+                unreachable_since: None,
+            },
+            // too many requests is handled like a server error
+            FetchOutcome::Status(status) if (400..500).contains(&status) && status != 429 => {
+                Entry {
+                    status: RobotsStatus::AllowAll,
+                    expires: now + self.expiry,
+                    last_parsed,
+                    unreachable_since: None,
+                }
+            }
+            FetchOutcome::Status(_) | FetchOutcome::Timeout | FetchOutcome::NetworkError => {
+                let unreachable_since = unreachable_since.unwrap_or(now);
+
+                // after a long outage we fall back to the last known file, or allow everything
+                let status = if now.duration_since(unreachable_since) >= UNREACHABLE_GRACE_PERIOD {
+                    last_parsed
+                        .clone()
+                        .map(RobotsStatus::Parsed)
+                        .unwrap_or(RobotsStatus::AllowAll)
+                } else {
+                    RobotsStatus::DisallowAll
+                };
+
+                Entry {
+                    status,
+                    expires: now + self.retry_after,
+                    last_parsed,
+                    unreachable_since: Some(unreachable_since),
+                }
+            }
+        };
+
+        &self.entries.entry(key).or_insert(entry).status
+    } // pub fn insert(&mut self, url: &Url, outcome: FetchOutcome) -> &RobotsStatus
+
+    pub fn is_allowed<F: RobotsFetcher>(&mut self, url: &Url, fetcher: &F) -> bool {
+        self.status(url, fetcher).is_allowed(url)
+    } // pub fn is_allowed<F: RobotsFetcher>(&mut self, url: &Url, fetcher: &F) -> bool
+
+    /// The crawl delay of the cached robots.txt for the origin of `url`.
+    pub fn crawl_delay(&self, url: &Url) -> Option<Duration> {
+        self.cached(url).and_then(|status| status.crawl_delay())
+    } // pub fn crawl_delay(&self, url: &Url) -> Option<Duration>
+
+    /// The sitemaps of the cached robots.txt for the origin of `url`.
+    pub fn sitemaps(&self, url: &Url) -> &[String] {
+        self.cached(url)
+            .map(|status| status.sitemaps())
+            .unwrap_or(&[])
+    } // pub fn sitemaps(&self, url: &Url) -> &[String]
+
+    /// Drop all expired entries. Unreachable origins are kept so their outage
+    /// can still be tracked the next time they are fetched, until they have been
+    /// unreachable for longer than the grace period plus the expiry.
+    pub fn evict_expired(&mut self) {
+        self.evict_expired_at(Instant::now());
+    } // pub fn evict_expired(&mut self)
+
+    fn evict_expired_at(&mut self, now: Instant) {
+        let max_outage = UNREACHABLE_GRACE_PERIOD + self.expiry;
+
+        self.entries.retain(|_, entry| {
+            entry.expires > now
+                || entry
+                    .unreachable_since
+                    .map(|since| now.saturating_duration_since(since) <= max_outage)
+                    .unwrap_or(false)
+        });
+    } // fn evict_expired_at(&mut self, now: Instant)
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    } // pub fn len(&self) -> usize
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    } // pub fn is_empty(&self) -> bool
+} // impl RobotsCache
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    /// Stand-in for an HTTP server that serves fixed responses and records requests.
+    #[derive(Default)]
+    struct TestServer {
+        responses: HashMap<String, FetchOutcome>,
+        requests: RefCell<Vec<String>>,
+    }
+
+    impl TestServer {
+        fn with(mut self, url: &str, outcome: FetchOutcome) -> Self {
+            self.responses.insert(url.to_string(), outcome);
+            self
+        }
+
+        fn num_requests(&self) -> usize {
+            self.requests.borrow().len()
+        }
+    }
+
+    impl RobotsFetcher for TestServer {
+        fn fetch(&self, url: &Url) -> FetchOutcome {
+            self.requests.borrow_mut().push(url.to_string());
+            self.responses
+                .get(url.as_str())
+                .cloned()
+                .unwrap_or(FetchOutcome::Status(404))
+        }
+    }
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    /// An HTTP server on localhost that answers with a raw response per path,
+    /// and 404 for every other path.
+    struct LocalServer {
+        origin: String,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl LocalServer {
+        fn start(routes: &[(&str, &str)]) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let origin = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(AtomicUsize::new(0));
+
+            let routes: HashMap<String, String> = routes
+                .iter()
+                .map(|(path, response)| (path.to_string(), response.to_string()))
+                .collect();
+            let counter = Arc::clone(&requests);
+
+            std::thread::spawn(move || {
+                for mut stream in listener.incoming().flatten() {
+                    counter.fetch_add(1, Ordering::SeqCst);
+
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+
+                    let mut header = String::new();
+                    while reader.read_line(&mut header).unwrap() > 2 {
+                        header.clear();
+                    }
+
+                    let path = request_line.split(' ').nth(1).unwrap_or_default();
+                    let response = routes
+                        .get(path)
+                        .map(String::as_str)
+                        .unwrap_or("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+
+                    stream.write_all(response.as_bytes()).unwrap();
+                }
+            });
+
+            Self { origin, requests }
+        }
+
+        fn url(&self, path: &str) -> Url {
+            url(&format!("{}{path}", self.origin))
+        }
+
+        fn num_requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+
+    /// Fetches robots.txt files with plain HTTP/1.1 requests.
+    struct HttpFetcher;
+
+    impl RobotsFetcher for HttpFetcher {
+        fn fetch(&self, url: &Url) -> FetchOutcome {
+            let addr = url.socket_addrs(|| None).unwrap()[0];
+            let Ok(mut stream) = TcpStream::connect_timeout(&addr, Duration::from_secs(1)) else {
+                return FetchOutcome::NetworkError;
+            };
+            stream
+                .set_read_timeout(Some(Duration::from_secs(1)))
+                .unwrap();
+
+            write!(
+                stream,
+                "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+                url.path(),
+                addr
+            )
+            .unwrap();
+
+            let mut response = String::new();
+            match stream.read_to_string(&mut response) {
+                Ok(_) => {}
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    return FetchOutcome::Timeout
+                }
+                Err(_) => return FetchOutcome::NetworkError,
+            }
+
+            let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+            let status: u16 = head.split(' ').nth(1).unwrap().parse().unwrap();
+            let location = head
+                .lines()
+                .find_map(|line| line.strip_prefix("Location: "));
+
+            match (status, location) {
+                (200..=299, _) => FetchOutcome::Success(body.to_string()),
+                (300..=399, Some(location)) => FetchOutcome::Redirect(url.join(location).unwrap()),
+                (status, _) => FetchOutcome::Status(status),
+            }
+        }
+    }
+
+    #[test]
+    fn local_http_server() {
+        let server = LocalServer::start(&[
+            (
+                "/robots.txt",
+                "HTTP/1.1 301 Moved Permanently\r\nLocation: /moved.txt\r\nContent-Length: 0\r\n\r\n",
+            ),
+            (
+                "/moved.txt",
+                "HTTP/1.1 200 OK\r\n\r\nUser-agent: *\nDisallow: /private\nCrawl-delay: 2\n",
+            ),
+        ]);
+        let throttled = LocalServer::start(&[(
+            "/robots.txt",
+            "HTTP/1.1 429 Too Many Requests\r\nContent-Length: 0\r\n\r\n",
+        )]);
+        let missing = LocalServer::start(&[]);
+        let closed = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}/a", listener.local_addr().unwrap())
+        };
+
+        let mut cache = RobotsCache::new("doodlebot");
+
+        assert!(cache.is_allowed(&server.url("/a"), &HttpFetcher));
+        assert!(!cache.is_allowed(&server.url("/private/a"), &HttpFetcher));
+        assert_eq!(
+            cache.crawl_delay(&server.url("/")),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(server.num_requests(), 2);
+
+        assert!(!cache.is_allowed(&throttled.url("/a"), &HttpFetcher));
+        assert!(cache.is_allowed(&missing.url("/a"), &HttpFetcher));
+        assert!(!cache.is_allowed(&url(&closed), &HttpFetcher));
+        assert_eq!(throttled.num_requests(), 1);
+        assert_eq!(missing.num_requests(), 1);
+    }
+
+    #[test]
+    fn opaque_origins() {
+        let server = TestServer::default();
+        let mut cache = RobotsCache::new("doodlebot");
+
+        cache.insert(&url("data:text/plain,a"), FetchOutcome::Status(503));
+        assert!(cache.is_allowed(&url("data:text/plain,b"), &server));
+        assert!(cache.cached(&url("data:text/plain,a")).is_none());
+        assert!(cache.is_empty());
+        assert_eq!(server.num_requests(), 0);
+    }
+
+    #[test]
+    fn caches_parsed_file() {
+        let server = TestServer::default().with(
+            "https://example.com/robots.txt",
+            FetchOutcome::Success(
+                "User-agent: *\nDisallow: /private\nCrawl-delay: 5\nSitemap: https://example.com/sitemap.xml"
+                    .to_string(),
+            ),
+        );
+        let mut cache = RobotsCache::new("doodlebot");
+
+        assert!(cache.is_allowed(&url("https://example.com/"), &server));
+        assert!(!cache.is_allowed(&url("https://example.com/private?a=1"), &server));
+        assert_eq!(server.num_requests(), 1);
+
+        assert_eq!(
+            cache.crawl_delay(&url("https://example.com/")),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            cache.sitemaps(&url("https://example.com/abc")),
+            &["https://example.com/sitemap.xml"]
+        );
+        assert_eq!(cache.sitemaps(&url("https://other.com/")), &[] as &[String]);
+    }
+
+    #[test]
+    fn status_codes() {
+        let server = TestServer::default()
+            .with(
+                "https://forbidden.com/robots.txt",
+                FetchOutcome::Status(403),
+            )
+            .with("https://error.com/robots.txt", FetchOutcome::Status(503))
+            .with(
+                "https://throttled.com/robots.txt",
+                FetchOutcome::Status(429),
+            )
+            .with("https://timeout.com/robots.txt", FetchOutcome::Timeout);
+        let mut cache = RobotsCache::new("doodlebot");
+
+        assert!(cache.is_allowed(&url("https://missing.com/a"), &server));
+        assert!(cache.is_allowed(&url("https://forbidden.com/a"), &server));
+        assert!(!cache.is_allowed(&url("https://error.com/a"), &server));
+        assert!(!cache.is_allowed(&url("https://throttled.com/a"), &server));
+        assert!(!cache.is_allowed(&url("https://timeout.com/a"), &server));
+    }
+
+    #[test]
+    fn redirects() {
+        let mut server = TestServer::default()
+            .with(
+                "https://example.com/robots.txt",
+                FetchOutcome::Redirect(url("https://www.example.com/robots.txt")),
+            )
+            .with(
+                "https://www.example.com/robots.txt",
+                FetchOutcome::Success("User-agent: *\nDisallow: /".to_string()),
+            );
+
+        for i in 0..10 {
+            server = server.with(
+                &format!("https://loop.com/{i}"),
+                FetchOutcome::Redirect(url(&format!("https://loop.com/{}", i + 1))),
+            );
+        }
+        server = server.with(
+            "https://loop.com/robots.txt",
+            FetchOutcome::Redirect(url("https://loop.com/0")),
+        );
+
+        let mut cache = RobotsCache::new("doodlebot");
+
+        assert!(!cache.is_allowed(&url("https://example.com/a"), &server));
+        assert!(cache.is_allowed(&url("https://loop.com/a"), &server));
+        assert_eq!(server.num_requests(), 2 + MAX_REDIRECTS + 1);
+    }
+
+    #[test]
+    fn expiry() {
+        let server = TestServer::default().with(
+            "https://example.com/robots.txt",
+            FetchOutcome::Success("User-agent: *\nDisallow: /".to_string()),
+        );
+        let mut cache = RobotsCache::new("doodlebot").with_expiry(Duration::ZERO);
+
+        assert!(!cache.is_allowed(&url("https://example.com/a"), &server));
+        assert!(cache.cached(&url("https://example.com/a")).is_none());
+        assert!(!cache.is_allowed(&url("https://example.com/a"), &server));
+        assert_eq!(server.num_requests(), 2);
+
+        cache.evict_expired();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn evict_unreachable() {
+        let mut cache = RobotsCache::new("doodlebot")
+            .with_expiry(Duration::ZERO)
+            .with_retry_after(Duration::ZERO);
+
+        cache.insert(&url("https://example.com/a"), FetchOutcome::Timeout);
+        cache.evict_expired();
+        assert_eq!(cache.len(), 1);
+
+        let later = Instant::now() + UNREACHABLE_GRACE_PERIOD - Duration::from_secs(60);
+        cache.evict_expired_at(later);
+        assert_eq!(cache.len(), 1);
+
+        let later = Instant::now() + UNREACHABLE_GRACE_PERIOD + Duration::from_secs(60);
+        cache.evict_expired_at(later);
+        assert!(cache.is_empty());
+    }
+}
@@ -11,19 +11,25 @@
 
 const MAX_CHAR_LIMIT_DEFAULT: usize = 512_000;
 
+mod cache;
+mod page_policy;
 mod parser;
 mod pattern;
+mod sitemap;
 use crate::parser::Line;
+pub use cache::{FetchOutcome, RobotsCache, RobotsFetcher, RobotsStatus};
 use itertools::Itertools;
+pub use page_policy::PagePolicy;
 use pattern::Pattern;
+pub use sitemap::{ChangeFreq, SitemapEntry, SitemapItem, SitemapParams, SitemapParser};
 use std::time::Duration;
 use url::Url;
 
-#[derive(Debug, PartialEq, Eq)]
-enum Directive {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Directive {
     Allow,
     Disallow,
-} // enum Directive
+} // pub enum Directive
 
 impl Ord for Directive {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
@@ -41,17 +47,45 @@ impl PartialOrd for Directive {
     }
 } // impl PartialOrd for Directive
 
-#[derive(Debug, PartialEq, Eq)]
-struct Rule {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
     pattern: Pattern,
     directive: Directive,
-} // struct Rule
+    line: usize,
+} // pub struct Rule
 
+impl Rule {
+    fn new(path: &str, directive: Directive, line: usize) -> Self {
+        Self {
+            pattern: Pattern::new(path),
+            directive,
+            line,
+        }
+    } // fn new(path: &str, directive: Directive, line: usize) -> Self
+
+    pub fn pattern(&self) -> &str {
+        self.pattern.as_str()
+    } // pub fn pattern(&self) -> &str
+
+    pub fn directive(&self) -> Directive {
+        self.directive
+    } // pub fn directive(&self) -> Directive
+
+    /// The 1-based line number of the rule in the robots.txt.
+    pub fn line(&self) -> usize {
+        self.line
+    } // pub fn line(&self) -> usize
+} // impl Rule
+
+/// Rules are ordered by precedence: longer patterns first, then allow before disallow,
+/// then by their position in the file.
 impl Ord for Rule {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         self.pattern
             .cmp(&other.pattern)
             .then(self.directive.cmp(&other.directive))
+            .then(self.line.cmp(&other.line))
+            .then_with(|| self.pattern.as_str().cmp(other.pattern.as_str()))
     } // fn cmp(&self, other: &Self) -> std::cmp::Ordering
 } // impl Ord for Rule
 
@@ -61,6 +95,137 @@ impl PartialOrd for Rule {
     } // fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering>
 } // impl PartialOrd for Rule
 
+/// A group of rules as defined by RFC 9309: one or more `User-agent` lines
+/// followed by the rules that apply to those agents.
+#[derive(Debug, Clone, Default)]
+pub struct Group {
+    user_agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<f32>,
+} // pub struct Group
+
+impl Group {
+    /// The lowercased product tokens this group applies to. `*` is kept as is.
+    pub fn user_agents(&self) -> &[String] {
+        &self.user_agents
+    } // pub fn user_agents(&self) -> &[String]
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    } // pub fn rules(&self) -> &[Rule]
+
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.crawl_delay.map(Duration::from_secs_f32)
+    } // pub fn crawl_delay(&self) -> Option<Duration>
+
+    fn is_wildcard(&self) -> bool {
+        self.user_agents.iter().any(|agent| agent == "*")
+    } // fn is_wildcard(&self) -> bool
+
+    /// Length of the longest product token in this group matching `useragent`.
+    fn match_len(&self, useragent: &str) -> Option<usize> {
+        self.user_agents
+            .iter()
+            .filter(|agent| product_matches(agent, useragent))
+            .map(|agent| agent.len())
+            .max()
+    } // fn match_len(&self, useragent: &str) -> Option<usize>
+
+    fn merge(&mut self, other: &Group) {
+        self.rules.extend(other.rules.iter().cloned());
+
+        if other.crawl_delay.is_some() {
+            self.crawl_delay = other.crawl_delay;
+        }
+    } // fn merge(&mut self, other: &Group)
+} // impl Group
+
+/// A Yandex `Clean-param` directive: the listed query parameters don't change
+/// the content of pages whose path matches the (optional) prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanParam {
+    params: Vec<String>,
+    path: Option<Pattern>,
+    line: usize,
+} // pub struct CleanParam
+
+impl CleanParam {
+    fn parse(value: &str, line: usize) -> Option<Self> {
+        let mut parts = value.split_whitespace();
+
+        let params: Vec<_> = parts
+            .next()?
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| param.to_string())
+            .collect();
+
+        if params.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            params,
+            path: parts.next().map(Pattern::new),
+            line,
+        })
+    } // fn parse(value: &str, line: usize) -> Option<Self>
+
+    pub fn params(&self) -> &[String] {
+        &self.params
+    } // pub fn params(&self) -> &[String]
+
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_ref().map(|path| path.as_str())
+    } // pub fn path(&self) -> Option<&str>
+
+    /// The 1-based line number of the directive in the robots.txt.
+    pub fn line(&self) -> usize {
+        self.line
+    } // pub fn line(&self) -> usize
+
+    fn applies_to(&self, path: &str) -> bool {
+        self.path
+            .as_ref()
+            .map(|pattern| pattern.matches(path))
+            .unwrap_or(true)
+    } // fn applies_to(&self, path: &str) -> bool
+} // impl CleanParam
+
+/// Why a path is allowed or disallowed, as returned by [`Robots::explain`].
+#[derive(Debug, Clone)]
+pub struct Explanation<'a> {
+    /// The path (including query) that was matched against the rules.
+    pub path: String,
+    pub allowed: bool,
+    /// The rule that decided the outcome. `None` if no rule matched.
+    pub decisive: Option<&'a Rule>,
+    /// Every rule that matched the path, in order of precedence.
+    pub matches: Vec<&'a Rule>,
+} // pub struct Explanation<'a>
+
+/// Extract the product token from a `User-agent` value (`Doodlebot/1.0` -> `doodlebot`).
+fn product_token(agent: &str) -> String {
+    if agent.starts_with('*') {
+        return "*".to_string();
+    }
+
+    agent
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic() || *c == '-' || *c == '_')
+        .collect::<String>()
+        .to_ascii_lowercase()
+} // fn product_token(agent: &str) -> String
+
+/// A product token matches our (lowercased) user agent if it is equal to it,
+/// or if it is a more general token of it (`doodlebot` matches `doodlebot-images`).
+fn product_matches(agent: &str, useragent: &str) -> bool {
+    match useragent.strip_prefix(agent) {
+        Some(rest) => rest.is_empty() || rest.starts_with('-'),
+        None => false,
+    }
+} // fn product_matches(agent: &str, useragent: &str) -> bool
+
 #[derive(Debug, Clone, Copy)]
 pub struct Params {
     pub char_limit: usize,
@@ -74,13 +239,56 @@ impl Default for Params {
     } // fn default() -> Self
 } // impl Default for Params
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Robots {
     rules: Vec<Rule>,
     crawl_delay: Option<f32>,
     sitemaps: Vec<String>,
+    groups: Vec<Group>,
+    clean_params: Vec<CleanParam>,
+    host: Option<String>,
 } // pub struct Robots
 
+/// The 1-based line numbers of byte offsets in a robots.txt. `\n`, `\r\n` and a lone
+/// `\r` each end a line, even where the parser skips several of them at once.
+struct LineNumbers<'a> {
+    text: &'a [u8],
+    offset: usize,
+    line: usize,
+} // struct LineNumbers<'a>
+
+impl<'a> LineNumbers<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text: text.as_bytes(),
+            offset: 0,
+            line: 1,
+        }
+    } // fn new(text: &'a str) -> Self
+
+    /// The line of `offset`. Offsets must not decrease between calls.
+    fn at(&mut self, offset: usize) -> usize {
+        for i in self.offset..offset {
+            match self.text[i] {
+                b'\n' => self.line += 1,
+                b'\r' if self.text.get(i + 1) != Some(&b'\n') => self.line += 1,
+                _ => {}
+            }
+        }
+
+        self.offset = offset;
+        self.line
+    } // fn at(&mut self, offset: usize) -> usize
+} // impl<'a> LineNumbers<'a>
+
+/// Directives that apply to the whole file rather than a single group.
+#[derive(Debug, Default)]
+struct FileDirectives {
+    sitemaps: Vec<String>,
+    clean_params: Vec<CleanParam>,
+    host: Option<String>,
+} // struct FileDirectives
+
 impl Robots {
     fn is_valid_user_agent(useragent: &str) -> bool {
         useragent
@@ -89,6 +297,75 @@ impl Robots {
             && !useragent.is_empty()
     } // fn is_valid_user_agent(useragent: &str) -> bool
 
+    fn parse_groups(
+        robotstxt: &str,
+        lines: &[(usize, Line)],
+    ) -> (Group, Vec<Group>, FileDirectives) {
+        // rules that appear before the first user-agent line apply to every agent
+        let mut global = Group::default();
+        let mut groups: Vec<Group> = Vec::new();
+        let mut directives = FileDirectives::default();
+        let mut in_user_agents = false;
+        let mut line_numbers = LineNumbers::new(robotstxt);
+
+        for (offset, line) in lines {
+            let line_number = line_numbers.at(*offset);
+
+            match line {
+                Line::UserAgent(agents) => {
+                    if !in_user_agents {
+                        groups.push(Group::default());
+                        in_user_agents = true;
+                    }
+
+                    let group = groups.last_mut().unwrap();
+                    group.user_agents.extend(
+                        agents
+                            .iter()
+                            .map(|agent| product_token(agent))
+                            .filter(|agent| !agent.is_empty()),
+                    );
+                }
+                Line::Allow(path) | Line::Disallow(path) => {
+                    in_user_agents = false;
+
+                    let directive = if matches!(line, Line::Allow(_)) {
+                        Directive::Allow
+                    } else {
+                        Directive::Disallow
+                    };
+
+                    if !path.is_empty() {
+                        groups
+                            .last_mut()
+                            .unwrap_or(&mut global)
+                            .rules
+                            .push(Rule::new(path, directive, line_number));
+                    }
+                }
+                Line::CrawlDelay(Some(delay)) => {
+                    in_user_agents = false;
+                    groups.last_mut().unwrap_or(&mut global).crawl_delay = Some(*delay);
+                }
+                Line::Sitemap(sitemap) => {
+                    directives.sitemaps.push(sitemap.to_string());
+                }
+                Line::CleanParam(value) => {
+                    directives
+                        .clean_params
+                        .extend(CleanParam::parse(value, line_number));
+                }
+                // only the first host directive is taken into account
+                Line::Host(host) if directives.host.is_none() && !host.is_empty() => {
+                    directives.host = Some(host.to_string());
+                }
+                _ => {}
+            }
+        }
+
+        (global, groups, directives)
+    } // fn parse_groups(robotstxt: &str, lines: &[(usize, Line)]) -> (Group, Vec<Group>, FileDirectives)
+
     pub fn parse_with_params(
         useragent: &str,
         robotstxt: &str,
@@ -105,112 +382,35 @@ impl Robots {
 
         let robotstxt = robotstxt.replace('\0', "\n");
         let (_, lines) = parser::parse(&robotstxt).map_err(|e| anyhow::anyhow!(e.to_string()))?;
-        let mut useragent = useragent.to_lowercase();
-
-        if !lines.iter().any(|line| {
-            if let Line::UserAgent(agents) = line {
-                agents.iter().any(|agent| {
-                    agent
-                        .chars()
-                        .zip(useragent.chars())
-                        .all(|(c1, c2)| c1.to_ascii_lowercase() == c2)
-                })
-            } else {
-                false
-            }
-        }) {
-            useragent = "*".to_string();
-        }
+        let useragent = useragent.to_ascii_lowercase();
 
-        let mut rules = Vec::new();
-        let mut crawl_delay = None;
-        let mut sitemaps = Vec::new();
-        let mut idx = 0;
-        let mut useragent_lines = 0;
-
-        while idx < lines.len() {
-            let line = &lines[idx];
-            idx += 1;
-
-            if let Line::UserAgent(agents) = &line {
-                useragent_lines += 1;
-                if agents.iter().any(|agent| {
-                    agent
-                        .chars()
-                        .zip(useragent.chars())
-                        .all(|(c1, c2)| c1.to_ascii_lowercase() == c2)
-                }) {
-                    let mut has_captured_directive = false;
-                    while idx < lines.len() {
-                        let line = &lines[idx];
-                        idx += 1;
-
-                        match line {
-                            Line::Allow(path) => {
-                                has_captured_directive = true;
-
-                                if !path.is_empty() {
-                                    rules.push(Rule {
-                                        pattern: Pattern::new(path),
-                                        directive: Directive::Allow,
-                                    });
-                                }
-                            }
-                            Line::Disallow(path) => {
-                                has_captured_directive = true;
-
-                                if !path.is_empty() {
-                                    rules.push(Rule {
-                                        pattern: Pattern::new(path),
-                                        directive: Directive::Disallow,
-                                    });
-                                }
-                            }
-                            Line::UserAgent(_) if has_captured_directive => {
-                                idx -= 1;
-                                break;
-                            }
-                            Line::Sitemap(sitemap) => {
-                                sitemaps.push(sitemap.to_string());
-                            }
-                            Line::CrawlDelay(Some(delay)) => {
-                                has_captured_directive = true;
-                                crawl_delay = Some(*delay);
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            } else if useragent_lines == 0 {
-                match line {
-                    Line::Allow(path) => {
-                        rules.push(Rule {
-                            pattern: Pattern::new(path),
-                            directive: Directive::Allow,
-                        });
-                    }
-                    Line::Disallow(path) => {
-                        rules.push(Rule {
-                            pattern: Pattern::new(path),
-                            directive: Directive::Disallow,
-                        });
-                    }
-                    Line::CrawlDelay(Some(delay)) => {
-                        crawl_delay = Some(*delay);
-                    }
-                    _ => {}
-                }
-            }
+        let (mut effective, groups, directives) = Self::parse_groups(&robotstxt, &lines);
 
-            if let Line::Sitemap(url) = line {
-                sitemaps.push(url.to_string());
+        // RFC 9309: obey the groups with the most specific matching product token,
+        // merging all groups that share it, and fall back to the `*` groups.
+        let best = groups
+            .iter()
+            .filter_map(|group| group.match_len(&useragent))
+            .max();
+
+        for group in &groups {
+            let selected = match best {
+                Some(len) => group.match_len(&useragent) == Some(len),
+                None => group.is_wildcard(),
+            };
+
+            if selected {
+                effective.merge(group);
             }
         }
 
         Ok(Self {
-            rules,
-            crawl_delay,
-            sitemaps,
+            rules: effective.rules,
+            crawl_delay: effective.crawl_delay,
+            sitemaps: directives.sitemaps,
+            groups,
+            clean_params: directives.clean_params,
+            host: directives.host,
         })
     } // pub fn parse_with_params
 
@@ -244,7 +444,7 @@ impl Robots {
         }
     } // fn prepare_path(url: &Url) -> String
 
-    fn is_precise_path_allowed(&self, path: &str) -> bool {
+    fn explain_precise_path(&self, path: &str) -> Explanation<'_> {
         let mut path = path.to_string();
 
         if path.is_empty() {
@@ -252,7 +452,12 @@ impl Robots {
         }
 
         if path == "/robots.txt" {
-            return true;
+            return Explanation {
+                path,
+                allowed: true,
+                decisive: None,
+                matches: Vec::new(),
+            };
         }
 
         let mut matches: Vec<_> = self
@@ -263,20 +468,36 @@ impl Robots {
 
         matches.sort();
 
-        matches
-            .first()
-            .map(|rule| rule.directive == Directive::Allow)
-            .unwrap_or(true)
-    } // fn is_precise_path_allowed(&self, path: &str) -> bool
+        let decisive = matches.first().copied();
 
-    pub fn is_path_allowed(&self, path: &str) -> bool {
-        let res = self.is_precise_path_allowed(path);
+        Explanation {
+            path,
+            allowed: decisive
+                .map(|rule| rule.directive == Directive::Allow)
+                .unwrap_or(true),
+            decisive,
+            matches,
+        }
+    } // fn explain_precise_path(&self, path: &str) -> Explanation<'_>
+
+    /// Explain which rules decide whether `path` is allowed.
+    pub fn explain_path(&self, path: &str) -> Explanation<'_> {
+        let res = self.explain_precise_path(path);
 
-        if !res && path.ends_with('/') {
-            self.is_precise_path_allowed(format!("{}index.html", path).as_str())
+        if !res.allowed && path.ends_with('/') {
+            self.explain_precise_path(format!("{}index.html", path).as_str())
         } else {
             res
         }
+    } // pub fn explain_path(&self, path: &str) -> Explanation<'_>
+
+    /// Explain which rules decide whether `url` is allowed.
+    pub fn explain(&self, url: &Url) -> Explanation<'_> {
+        self.explain_path(&Self::prepare_path(url))
+    } // pub fn explain(&self, url: &Url) -> Explanation<'_>
+
+    pub fn is_path_allowed(&self, path: &str) -> bool {
+        self.explain_path(path).allowed
     } // pub fn is_path_allowed(&self, path: &str) -> bool
 
     pub fn crawl_delay(&self) -> Option<Duration> {
@@ -286,4 +507,244 @@ impl Robots {
     pub fn sitemaps(&self) -> &[String] {
         &self.sitemaps
     } // pub fn sitemaps(&self) -> &[String]
+
+    /// All groups in the robots.txt, regardless of which agent they apply to.
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    } // pub fn groups(&self) -> &[Group]
+
+    pub fn clean_params(&self) -> &[CleanParam] {
+        &self.clean_params
+    } // pub fn clean_params(&self) -> &[CleanParam]
+
+    /// The preferred mirror of the site from the `Host` directive.
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    } // pub fn host(&self) -> Option<&str>
+
+    /// Remove the query parameters that `Clean-param` directives mark as irrelevant for `url`.
+    pub fn clean_url(&self, url: &Url) -> Url {
+        let path = url.path();
+        let ignored: Vec<_> = self
+            .clean_params
+            .iter()
+            .filter(|clean_param| clean_param.applies_to(path))
+            .flat_map(|clean_param| clean_param.params.iter())
+            .collect();
+
+        if ignored.is_empty() || url.query().is_none() {
+            return url.clone();
+        }
+
+        let pairs: Vec<_> = url
+            .query_pairs()
+            .filter(|(key, _)| !ignored.iter().any(|param| *param == key))
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+
+        let mut cleaned = url.clone();
+
+        if pairs.is_empty() {
+            cleaned.set_query(None);
+        } else {
+            cleaned.query_pairs_mut().clear().extend_pairs(pairs);
+        }
+
+        cleaned
+    } // pub fn clean_url(&self, url: &Url) -> Url
+
+    /// The rules that apply to the user agent the file was parsed for.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    } // pub fn rules(&self) -> &[Rule]
 } // impl Robots
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_allowed(robots: &Robots, url: &str) -> bool {
+        robots.is_allowed(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn prefix_of_product_does_not_match() {
+        let robots_txt = r#"User-agent: D
+Disallow: /
+
+User-agent: *
+Disallow: /private"#;
+
+        let robots = Robots::parse("DoodleBot", robots_txt).unwrap();
+
+        assert!(is_allowed(&robots, "https://example.com/"));
+        assert!(!is_allowed(&robots, "https://example.com/private"));
+    }
+
+    #[test]
+    fn most_specific_group_wins() {
+        let robots_txt = r#"User-agent: doodlebot
+Disallow: /a
+
+User-agent: doodlebot-images
+Disallow: /b
+
+User-agent: *
+Disallow: /"#;
+
+        let robots = Robots::parse("doodlebot-images", robots_txt).unwrap();
+        assert!(is_allowed(&robots, "https://example.com/a"));
+        assert!(!is_allowed(&robots, "https://example.com/b"));
+
+        let robots = Robots::parse("doodlebot", robots_txt).unwrap();
+        assert!(!is_allowed(&robots, "https://example.com/a"));
+        assert!(is_allowed(&robots, "https://example.com/b"));
+
+        let robots = Robots::parse("doodlebot-news", robots_txt).unwrap();
+        assert!(!is_allowed(&robots, "https://example.com/a"));
+        assert!(is_allowed(&robots, "https://example.com/b"));
+    }
+
+    #[test]
+    fn repeated_groups_are_merged() {
+        let robots_txt = r#"User-agent: DoodleBot/1.0
+Disallow: /a
+Crawl-delay: 1
+
+User-agent: other
+Disallow: /c
+
+User-agent: doodlebot
+Disallow: /b
+Crawl-delay: 2"#;
+
+        let robots = Robots::parse("doodlebot", robots_txt).unwrap();
+
+        assert!(!is_allowed(&robots, "https://example.com/a"));
+        assert!(!is_allowed(&robots, "https://example.com/b"));
+        assert!(is_allowed(&robots, "https://example.com/c"));
+        assert_eq!(robots.crawl_delay(), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn wildcard_groups_are_merged() {
+        let robots_txt = r#"User-agent: *
+Disallow: /a
+
+User-agent: other
+Disallow: /b
+
+User-agent: *
+Disallow: /c"#;
+
+        let robots = Robots::parse("doodlebot", robots_txt).unwrap();
+
+        assert!(!is_allowed(&robots, "https://example.com/a"));
+        assert!(is_allowed(&robots, "https://example.com/b"));
+        assert!(!is_allowed(&robots, "https://example.com/c"));
+    }
+
+    #[test]
+    fn consecutive_user_agents_share_group() {
+        let robots_txt = r#"User-agent: a
+User-agent: doodlebot
+Disallow: /
+Sitemap: https://example.com/sitemap.xml
+
+User-agent: b
+Allow: /"#;
+
+        let robots = Robots::parse("doodlebot", robots_txt).unwrap();
+
+        assert_eq!(robots.groups().len(), 2);
+        assert_eq!(robots.groups()[0].user_agents(), &["a", "doodlebot"]);
+        assert_eq!(
+            robots.groups()[0].rules()[0].directive(),
+            Directive::Disallow
+        );
+        assert_eq!(robots.groups()[1].user_agents(), &["b"]);
+        assert_eq!(robots.sitemaps(), &["https://example.com/sitemap.xml"]);
+        assert!(!is_allowed(&robots, "https://example.com/"));
+    }
+
+    #[test]
+    fn explain() {
+        let robots_txt = r#"User-agent: *
+Disallow: /private
+Allow: /private/public
+
+# comment
+Disallow: /*.pdf$"#;
+
+        let robots = Robots::parse("doodlebot", robots_txt).unwrap();
+
+        let explanation =
+            robots.explain(&Url::parse("https://example.com/private/public/a.pdf").unwrap());
+        assert!(explanation.allowed);
+        assert_eq!(explanation.matches.len(), 3);
+
+        let decisive = explanation.decisive.unwrap();
+        assert_eq!(decisive.pattern(), "/private/public");
+        assert_eq!(decisive.directive(), Directive::Allow);
+        assert_eq!(decisive.line(), 3);
+
+        let explanation = robots.explain(&Url::parse("https://example.com/a.pdf").unwrap());
+        assert!(!explanation.allowed);
+        assert_eq!(explanation.decisive.unwrap().line(), 6);
+
+        let explanation = robots.explain(&Url::parse("https://example.com/").unwrap());
+        assert!(explanation.allowed);
+        assert!(explanation.decisive.is_none());
+    }
+
+    #[test]
+    fn line_numbers() {
+        for (robots_txt, lines) in [
+            ("User-agent: *\r\rDisallow: /a\r\r\rAllow: /b", [3, 6]),
+            ("User-agent: *\r\n\r\nDisallow: /a\n\r\rAllow: /b", [3, 6]),
+        ] {
+            let robots = Robots::parse("doodlebot", robots_txt).unwrap();
+            let rules = robots.groups()[0].rules();
+
+            assert_eq!(rules.iter().map(Rule::line).collect::<Vec<_>>(), lines);
+        }
+
+        let robots =
+            Robots::parse("doodlebot", "User-agent: *\nDisallow: /a\nDisallow: /a").unwrap();
+        let rules = robots.groups()[0].rules();
+
+        assert_ne!(rules[0], rules[1]);
+        assert_eq!(rules[0].cmp(&rules[1]), std::cmp::Ordering::Less);
+        assert_eq!(rules[0].cmp(&rules[0].clone()), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn clean_param_and_host() {
+        let robots_txt = r#"User-agent: *
+Disallow: /admin
+Clean-param: ref&utm_source /articles/
+Clean-param: sid
+Host: www.example.com
+Host: other.example.com"#;
+
+        let robots = Robots::parse("doodlebot", robots_txt).unwrap();
+
+        assert_eq!(robots.host(), Some("www.example.com"));
+        assert_eq!(robots.clean_params().len(), 2);
+        assert_eq!(robots.clean_params()[0].params(), &["ref", "utm_source"]);
+        assert_eq!(robots.clean_params()[0].path(), Some("/articles/"));
+        assert_eq!(robots.clean_params()[1].line(), 4);
+
+        let url = Url::parse("https://example.com/articles/1?ref=a&id=2&sid=3").unwrap();
+        assert_eq!(
+            robots.clean_url(&url).as_str(),
+            "https://example.com/articles/1?id=2"
+        );
+
+        let url = Url::parse("https://example.com/other?ref=a&sid=3").unwrap();
+        assert_eq!(
+            robots.clean_url(&url).as_str(),
+            "https://example.com/other?ref=a"
+        );
+    }
+}
@@ -0,0 +1,333 @@
+/**
+ * @file page_policy.rs
+ * @author Krisna Pranav
+ * @brief robots-policy[page_policy]
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+use crate::{product_matches, product_token};
+
+/// Name of the meta tag that applies to every crawler.
+const GENERIC_META_NAME: &str = "robots";
+
+const DIRECTIVES: &[&str] = &[
+    "all",
+    "none",
+    "index",
+    "noindex",
+    "follow",
+    "nofollow",
+    "archive",
+    "noarchive",
+    "snippet",
+    "nosnippet",
+    "max-snippet",
+    "max-image-preview",
+    "max-video-preview",
+    "noimageindex",
+    "notranslate",
+    "indexifembedded",
+    "unavailable_after",
+];
+
+/// Page-level crawler directives from `<meta name="robots">` tags and
+/// `X-Robots-Tag` headers.
+///
+/// Policies from several sources are combined with [`PagePolicy::combine`],
+/// where the most restrictive value of every directive wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PagePolicy {
+    pub noindex: bool,
+    pub nofollow: bool,
+    pub noarchive: bool,
+    pub nosnippet: bool,
+    pub max_snippet: Option<usize>,
+    pub unavailable_after: Option<DateTime<Utc>>,
+} // pub struct PagePolicy
+
+impl PagePolicy {
+    /// Parse the `content` of a `<meta name="...">` tag.
+    ///
+    /// Returns `None` if the meta tag is neither `robots` nor addressed to `useragent`.
+    pub fn from_meta(useragent: &str, name: &str, content: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+
+        if name != GENERIC_META_NAME && !Self::is_addressed_to(&name, useragent) {
+            return None;
+        }
+
+        Some(Self::parse_directives(content))
+    } // pub fn from_meta(useragent: &str, name: &str, content: &str) -> Option<Self>
+
+    /// Parse a single `X-Robots-Tag` header value. The value can optionally
+    /// be scoped to an agent, as in `doodlebot: noindex, nofollow`.
+    ///
+    /// Returns `None` if the value is scoped to another agent.
+    pub fn from_header(useragent: &str, value: &str) -> Option<Self> {
+        if let Some((scope, rest)) = value.split_once(':') {
+            let scope = scope.trim().to_ascii_lowercase();
+
+            if Self::is_scope(&scope, rest) {
+                if !Self::is_addressed_to(&scope, useragent) {
+                    return None;
+                }
+
+                return Some(Self::parse_directives(rest));
+            }
+        }
+
+        Some(Self::parse_directives(value))
+    } // pub fn from_header(useragent: &str, value: &str) -> Option<Self>
+
+    /// Combine two policies, keeping the most restrictive value of every directive.
+    pub fn combine(self, other: Self) -> Self {
+        Self {
+            noindex: self.noindex || other.noindex,
+            nofollow: self.nofollow || other.nofollow,
+            noarchive: self.noarchive || other.noarchive,
+            nosnippet: self.nosnippet || other.nosnippet,
+            max_snippet: min_option(self.max_snippet, other.max_snippet),
+            unavailable_after: min_option(self.unavailable_after, other.unavailable_after),
+        }
+    } // pub fn combine(self, other: Self) -> Self
+
+    /// Whether the page may be indexed at the given point in time.
+    pub fn is_indexable_at(&self, now: DateTime<Utc>) -> bool {
+        !self.noindex
+            && self
+                .unavailable_after
+                .map(|deadline| now <= deadline)
+                .unwrap_or(true)
+    } // pub fn is_indexable_at(&self, now: DateTime<Utc>) -> bool
+
+    /// Whether a snippet may be shown for the page.
+    pub fn allows_snippet(&self) -> bool {
+        !self.nosnippet && self.max_snippet != Some(0)
+    } // pub fn allows_snippet(&self) -> bool
+
+    /// Whether the text before the first `:` of a header value is an agent scope
+    /// rather than an unknown `name:value` directive. A scope is a bare product
+    /// token followed by a directive list, so `max-foo:large, noindex` is not one.
+    fn is_scope(scope: &str, rest: &str) -> bool {
+        let first = rest.split([',', ':']).next().unwrap_or_default();
+
+        !DIRECTIVES.contains(&scope)
+            && !scope.is_empty()
+            && product_token(scope) == scope
+            && DIRECTIVES.contains(&first.trim().to_ascii_lowercase().as_str())
+    } // fn is_scope(scope: &str, rest: &str) -> bool
+
+    fn is_addressed_to(name: &str, useragent: &str) -> bool {
+        let name = product_token(name);
+        !name.is_empty() && name != "*" && product_matches(&name, &useragent.to_ascii_lowercase())
+    } // fn is_addressed_to(name: &str, useragent: &str) -> bool
+
+    fn parse_directives(content: &str) -> Self {
+        let mut policy = Self::default();
+
+        for (key, value) in split_directives(content) {
+            match key.as_str() {
+                "noindex" => policy.noindex = true,
+                "nofollow" => policy.nofollow = true,
+                "none" => {
+                    policy.noindex = true;
+                    policy.nofollow = true;
+                }
+                "noarchive" => policy.noarchive = true,
+                "nosnippet" => policy.nosnippet = true,
+                "max-snippet" => {
+                    // negative values mean that there is no limit
+                    if let Some(Ok(len)) = value.map(|v| v.parse::<i64>()) {
+                        if len >= 0 {
+                            policy.max_snippet = min_option(policy.max_snippet, Some(len as usize));
+                        }
+                    }
+                }
+                "unavailable_after" => {
+                    if let Some(date) = value.and_then(|v| parse_date(&v)) {
+                        policy.unavailable_after = min_option(policy.unavailable_after, Some(date));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        policy
+    } // fn parse_directives(content: &str) -> Self
+} // impl PagePolicy
+
+/// Split a directive list into lowercased keys and their optional values.
+/// Dates in `unavailable_after` can themselves contain commas, so fragments
+/// that don't start with a known directive are glued back onto the previous value.
+fn split_directives(content: &str) -> Vec<(String, Option<String>)> {
+    let mut directives: Vec<(String, Option<String>)> = Vec::new();
+
+    for part in content.split(',') {
+        let (key, value) = match part.split_once(':') {
+            Some((key, value)) => (key.trim().to_ascii_lowercase(), Some(value.trim())),
+            None => (part.trim().to_ascii_lowercase(), None),
+        };
+
+        if DIRECTIVES.contains(&key.as_str()) {
+            directives.push((key, value.map(str::to_string)));
+        } else if let Some((_, Some(prev))) = directives.last_mut() {
+            prev.push(',');
+            prev.push_str(part);
+        }
+    }
+
+    directives
+} // fn split_directives(content: &str) -> Vec<(String, Option<String>)>
+
+/// Parse the date formats seen in `unavailable_after` (RFC 822, RFC 850, ISO 8601).
+/// Timezone abbreviations other than numeric offsets are treated as UTC.
+pub(crate) fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    let date = date.trim();
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Some(date.with_timezone(&Utc));
+    }
+
+    if let Ok(date) = DateTime::parse_from_rfc2822(date) {
+        return Some(date.with_timezone(&Utc));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        return Some(date.and_hms_opt(0, 0, 0)?.and_utc());
+    }
+
+    let without_zone = match date.rsplit_once(' ') {
+        Some((rest, zone)) if zone.chars().all(|c| c.is_ascii_alphabetic()) => rest,
+        _ => date,
+    };
+
+    [
+        "%Y-%m-%dT%H:%M:%S",
+        "%d %b %Y %H:%M:%S",
+        "%a, %d %b %Y %H:%M:%S",
+        "%A, %d-%b-%y %H:%M:%S",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(without_zone, format).ok())
+    .map(|date| date.and_utc())
+} // pub(crate) fn parse_date(date: &str) -> Option<DateTime<Utc>>
+
+fn min_option<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+} // fn min_option<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T>
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn meta_scoping() {
+        let policy = PagePolicy::from_meta("doodlebot", "robots", "noindex, nofollow").unwrap();
+        assert!(policy.noindex);
+        assert!(policy.nofollow);
+
+        let policy = PagePolicy::from_meta("doodlebot", "DoodleBot", "noarchive").unwrap();
+        assert!(policy.noarchive);
+        assert!(!policy.noindex);
+
+        assert_eq!(
+            PagePolicy::from_meta("doodlebot", "googlebot", "noindex"),
+            None
+        );
+        assert_eq!(
+            PagePolicy::from_meta("doodlebot", "description", "none"),
+            None
+        );
+    }
+
+    #[test]
+    fn header_scoping() {
+        let policy = PagePolicy::from_header("doodlebot", "doodlebot: none").unwrap();
+        assert!(policy.noindex);
+        assert!(policy.nofollow);
+
+        assert_eq!(
+            PagePolicy::from_header("doodlebot", "otherbot: noindex"),
+            None
+        );
+
+        let policy =
+            PagePolicy::from_header("doodlebot", "max-image-preview:large, noindex").unwrap();
+        assert!(policy.noindex);
+        assert_eq!(
+            PagePolicy::from_header("doodlebot", "max-video-preview:-1"),
+            Some(PagePolicy::default())
+        );
+
+        // unknown directives with a value are not mistaken for a scope
+        let policy = PagePolicy::from_header("doodlebot", "max-foo:large, noindex").unwrap();
+        assert!(policy.noindex);
+
+        let policy = PagePolicy::from_header("doodlebot", "unavailable_after: 2024-05-01").unwrap();
+        assert_eq!(
+            policy.unavailable_after,
+            Some(Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn snippet_limits() {
+        let policy = PagePolicy::from_meta("doodlebot", "robots", "max-snippet:-1").unwrap();
+        assert_eq!(policy.max_snippet, None);
+        assert!(policy.allows_snippet());
+
+        let policy = PagePolicy::from_meta("doodlebot", "robots", "max-snippet:0").unwrap();
+        assert!(!policy.allows_snippet());
+    }
+
+    #[test]
+    fn dates_with_commas() {
+        let policy = PagePolicy::from_header(
+            "doodlebot",
+            "noarchive, unavailable_after: Friday, 25-Jun-10 15:00:00 PST, nosnippet",
+        )
+        .unwrap();
+
+        assert!(policy.noarchive);
+        assert!(policy.nosnippet);
+        assert_eq!(
+            policy.unavailable_after,
+            Some(Utc.with_ymd_and_hms(2010, 6, 25, 15, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn most_restrictive_wins() {
+        let meta = PagePolicy::from_meta(
+            "doodlebot",
+            "robots",
+            "max-snippet:50, unavailable_after: 2030-01-01",
+        )
+        .unwrap();
+        let header =
+            PagePolicy::from_header("doodlebot", "doodlebot: noindex, max-snippet:20").unwrap();
+        let scoped =
+            PagePolicy::from_meta("doodlebot", "doodlebot", "unavailable_after: 2025-01-01")
+                .unwrap();
+
+        let policy = meta.combine(header).combine(scoped);
+
+        assert!(policy.noindex);
+        assert!(!policy.nofollow);
+        assert_eq!(policy.max_snippet, Some(20));
+        assert_eq!(
+            policy.unavailable_after,
+            Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap())
+        );
+        assert!(!policy.is_indexable_at(Utc::now()));
+    }
+}
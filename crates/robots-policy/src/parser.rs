@@ -25,24 +25,33 @@ pub enum Line<'a> {
     Disallow(&'a str),
     Sitemap(&'a str),
     CrawlDelay(Option<f32>),
+    CleanParam(&'a str),
+    Host(&'a str),
     Raw(()),
 } // pub enum Line<'a>
 
-pub fn parse(input: &str) -> IResult<&str, Vec<Line>> {
-    let (input, (lines, _)) = many_till(
+/// Parse the lines of a robots.txt, each with the byte offset in `input` where it starts.
+pub fn parse<'a>(input: &'a str) -> IResult<&'a str, Vec<(usize, Line<'a>)>> {
+    let line = |rest: &'a str| {
+        let offset = input.len() - rest.len();
+
         alt((
             parse_user_agent,
             parse_allow,
             parse_disallow,
             parse_sitemap,
             parse_crawl_delay,
+            parse_clean_param,
+            parse_host,
             parse_raw,
-        )),
-        eof,
-    )(input)?;
+        ))(rest)
+        .map(|(rest, line)| (rest, (offset, line)))
+    };
+
+    let (input, (lines, _)) = many_till(line, eof)(input)?;
 
     Ok((input, lines))
-} // pub fn parse(input: &str) -> IResult<&str, Vec<Line>>
+} // pub fn parse<'a>(input: &'a str) -> IResult<&'a str, Vec<(usize, Line<'a>)>>
 
 fn is_not_line_ending(c: char) -> bool {
     c != '\n' && c != '\r'
@@ -166,6 +175,26 @@ fn parse_crawl_delay(input: &str) -> IResult<&str, Line> {
     Ok((input, Line::CrawlDelay(crawl_delay)))
 } // fn parse_craw_delay
 
+fn parse_clean_param(input: &str) -> IResult<&str, Line> {
+    let clean_param = (
+        tag_no_case("clean-param"),
+        tag_no_case("clean param"),
+        tag_no_case("cleanparam"),
+    );
+
+    let (input, _) = preceded(space0, alt(clean_param))(input)?;
+
+    let (input, clean_param) = product(input)?;
+    Ok((input, Line::CleanParam(clean_param)))
+} // fn parse_clean_param(input: &str) -> IResult<&str, Line>
+
+fn parse_host(input: &str) -> IResult<&str, Line> {
+    let (input, _) = preceded(space0, tag_no_case("host"))(input)?;
+
+    let (input, host) = product(input)?;
+    Ok((input, Line::Host(host)))
+} // fn parse_host(input: &str) -> IResult<&str, Line>
+
 fn parse_raw(input: &str) -> IResult<&str, Line> {
     let (input, _raw) = take_while(is_not_line_ending)(input)?;
     let (input, _) = consume_newline(input)?;
@@ -10,7 +10,7 @@
  */
 use std::cmp::Ordering;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pattern {
     pattern: String,
     len: usize,
@@ -57,6 +57,10 @@ impl Pattern {
         self.len
     }
 
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
     pub fn matches(&self, path: &str) -> bool {
         let path = percent_encode(path);
         let parts = self.pattern.split('*');
@@ -0,0 +1,621 @@
+/**
+ * @file sitemap.rs
+ * @author Krisna Pranav
+ * @brief robots-policy[sitemap]
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::io::{self, BufRead, BufReader, Read};
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use quick_xml::events::{BytesStart, Event};
+use url::Url;
+
+use crate::page_policy::parse_date;
+
+/// Maximum number of urls in a single sitemap as defined by sitemaps.org.
+const MAX_URLS_DEFAULT: usize = 50_000;
+
+/// Maximum uncompressed size of a single sitemap as defined by sitemaps.org.
+const MAX_BYTES_DEFAULT: u64 = 50 * 1024 * 1024;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+
+#[derive(Debug, Clone, Copy)]
+pub struct SitemapParams {
+    pub max_urls: usize,
+    pub max_bytes: u64,
+} // pub struct SitemapParams
+
+impl Default for SitemapParams {
+    fn default() -> Self {
+        Self {
+            max_urls: MAX_URLS_DEFAULT,
+            max_bytes: MAX_BYTES_DEFAULT,
+        }
+    } // fn default() -> Self
+} // impl Default for SitemapParams
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeFreq {
+    Always,
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+    Never,
+} // pub enum ChangeFreq
+
+impl FromStr for ChangeFreq {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "always" => Ok(Self::Always),
+            "hourly" => Ok(Self::Hourly),
+            "daily" => Ok(Self::Daily),
+            "weekly" => Ok(Self::Weekly),
+            "monthly" => Ok(Self::Monthly),
+            "yearly" => Ok(Self::Yearly),
+            "never" => Ok(Self::Never),
+            _ => Err(anyhow::anyhow!("Invalid change frequency: {s}")),
+        }
+    } // fn from_str(s: &str) -> Result<Self, Self::Err>
+} // impl FromStr for ChangeFreq
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapEntry {
+    pub loc: Url,
+    pub lastmod: Option<DateTime<Utc>>,
+    pub changefreq: Option<ChangeFreq>,
+    pub priority: Option<f32>,
+} // pub struct SitemapEntry
+
+impl SitemapEntry {
+    fn new(loc: Url) -> Self {
+        Self {
+            loc,
+            lastmod: None,
+            changefreq: None,
+            priority: None,
+        }
+    } // fn new(loc: Url) -> Self
+} // impl SitemapEntry
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SitemapItem {
+    /// A page, from a `<urlset>`, a plain-text sitemap or a feed.
+    Url(SitemapEntry),
+    /// Another sitemap, from a `<sitemapindex>`.
+    Sitemap(SitemapEntry),
+} // pub enum SitemapItem
+
+impl SitemapItem {
+    pub fn entry(&self) -> &SitemapEntry {
+        match self {
+            SitemapItem::Url(entry) | SitemapItem::Sitemap(entry) => entry,
+        }
+    } // pub fn entry(&self) -> &SitemapEntry
+} // impl SitemapItem
+
+/// Errors when the uncompressed sitemap grows beyond `max_bytes`.
+struct LimitedReader<R> {
+    inner: R,
+    remaining: u64,
+} // struct LimitedReader<R>
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            let mut probe = [0; 1];
+
+            return match self.inner.read(&mut probe)? {
+                0 => Ok(0),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "sitemap exceeds the maximum size",
+                )),
+            };
+        }
+
+        let max = buf
+            .len()
+            .min(self.remaining.try_into().unwrap_or(usize::MAX));
+        let n = self.inner.read(&mut buf[..max])?;
+        self.remaining -= n as u64;
+
+        Ok(n)
+    } // fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>
+} // impl<R: Read> Read for LimitedReader<R>
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Loc,
+    LastMod,
+    ChangeFreq,
+    Priority,
+} // enum Field
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ItemKind {
+    Url,
+    Sitemap,
+} // enum ItemKind
+
+/// The element an item was read from, which decides where its url comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    /// `<url>` or `<sitemap>`, where only `<loc>` holds the url.
+    Sitemap,
+    /// RSS `<item>`, with the url as the text of `<link>`.
+    Rss,
+    /// Atom `<entry>`, with the url in `<link href="..." />`.
+    Atom,
+} // enum Container
+
+#[derive(Debug)]
+struct PartialItem {
+    kind: ItemKind,
+    container: Container,
+    loc: Option<String>,
+    lastmod: Option<String>,
+    changefreq: Option<String>,
+    priority: Option<String>,
+} // struct PartialItem
+
+impl PartialItem {
+    fn new(kind: ItemKind, container: Container) -> Self {
+        Self {
+            kind,
+            container,
+            loc: None,
+            lastmod: None,
+            changefreq: None,
+            priority: None,
+        }
+    } // fn new(kind: ItemKind, container: Container) -> Self
+
+    fn field_mut(&mut self, field: Field) -> &mut Option<String> {
+        match field {
+            Field::Loc => &mut self.loc,
+            Field::LastMod => &mut self.lastmod,
+            Field::ChangeFreq => &mut self.changefreq,
+            Field::Priority => &mut self.priority,
+        }
+    } // fn field_mut(&mut self, field: Field) -> &mut Option<String>
+
+    fn finish(self) -> Option<SitemapItem> {
+        let loc = Url::parse(self.loc?.trim()).ok()?;
+        let mut entry = SitemapEntry::new(loc);
+
+        entry.lastmod = self.lastmod.as_deref().and_then(parse_date);
+        entry.changefreq = self.changefreq.and_then(|c| c.parse().ok());
+        entry.priority = self
+            .priority
+            .and_then(|p| p.trim().parse::<f32>().ok())
+            .filter(|p| (0.0..=1.0).contains(p));
+
+        Some(match self.kind {
+            ItemKind::Url => SitemapItem::Url(entry),
+            ItemKind::Sitemap => SitemapItem::Sitemap(entry),
+        })
+    } // fn finish(self) -> Option<SitemapItem>
+} // impl PartialItem
+
+struct XmlSitemap<'a> {
+    reader: quick_xml::Reader<Box<dyn BufRead + 'a>>,
+    buf: Vec<u8>,
+    item: Option<PartialItem>,
+    field: Option<Field>,
+} // struct XmlSitemap<'a>
+
+impl<'a> XmlSitemap<'a> {
+    fn new(reader: Box<dyn BufRead + 'a>) -> Self {
+        let mut reader = quick_xml::Reader::from_reader(reader);
+        reader.trim_text(true);
+
+        Self {
+            reader,
+            buf: Vec::new(),
+            item: None,
+            field: None,
+        }
+    } // fn new(reader: Box<dyn BufRead + 'a>) -> Self
+
+    fn local_name(e: &BytesStart) -> String {
+        String::from_utf8_lossy(e.local_name().as_ref()).to_ascii_lowercase()
+    } // fn local_name(e: &BytesStart) -> String
+
+    /// Atom entries carry their url in `<link href="..." />`.
+    fn atom_link(e: &BytesStart) -> Option<String> {
+        let mut href = None;
+
+        for attr in e.attributes().flatten() {
+            match attr.key.local_name().as_ref() {
+                b"href" => href = attr.unescape_value().ok().map(|v| v.to_string()),
+                b"rel" if attr.value.as_ref() != b"alternate" => return None,
+                _ => {}
+            }
+        }
+
+        href
+    } // fn atom_link(e: &BytesStart) -> Option<String>
+
+    fn start(&mut self, e: &BytesStart) {
+        let name = Self::local_name(e);
+
+        match name.as_str() {
+            "url" => self.item = Some(PartialItem::new(ItemKind::Url, Container::Sitemap)),
+            "item" => self.item = Some(PartialItem::new(ItemKind::Url, Container::Rss)),
+            "entry" => self.item = Some(PartialItem::new(ItemKind::Url, Container::Atom)),
+            "sitemap" => self.item = Some(PartialItem::new(ItemKind::Sitemap, Container::Sitemap)),
+            _ => {
+                let field = match name.as_str() {
+                    "loc" | "link" => Field::Loc,
+                    "lastmod" | "pubdate" | "updated" => Field::LastMod,
+                    "changefreq" => Field::ChangeFreq,
+                    "priority" => Field::Priority,
+                    _ => return,
+                };
+
+                if let Some(item) = self.item.as_mut() {
+                    // links in sitemaps, like `<xhtml:link hreflang>`, are alternates
+                    if name == "link" {
+                        match item.container {
+                            Container::Sitemap => return,
+                            Container::Rss => {}
+                            Container::Atom => {
+                                if let Some(href) = Self::atom_link(e) {
+                                    item.loc.get_or_insert(href);
+                                }
+
+                                return;
+                            }
+                        }
+                    }
+
+                    self.field = Some(field);
+                }
+            }
+        }
+    } // fn start(&mut self, e: &BytesStart)
+
+    fn text(&mut self, text: &str) {
+        if let (Some(item), Some(field)) = (self.item.as_mut(), self.field) {
+            let value = item.field_mut(field);
+
+            if value.is_none() || field != Field::Loc {
+                value.get_or_insert_with(String::new).push_str(text);
+            }
+        }
+    } // fn text(&mut self, text: &str)
+
+    fn next_item(&mut self) -> Result<Option<SitemapItem>, anyhow::Error> {
+        loop {
+            self.buf.clear();
+
+            match self.reader.read_event_into(&mut self.buf)? {
+                Event::Start(e) => {
+                    let e = e.into_owned();
+                    self.start(&e);
+                }
+                Event::Empty(e) => {
+                    let e = e.into_owned();
+                    self.start(&e);
+                    self.field = None;
+                }
+                Event::Text(e) => {
+                    let text = e.unescape()?.into_owned();
+                    self.text(&text);
+                }
+                Event::CData(e) => {
+                    let text = String::from_utf8_lossy(&e.into_inner()).into_owned();
+                    self.text(&text);
+                }
+                Event::End(e) => {
+                    let name =
+                        String::from_utf8_lossy(e.local_name().as_ref()).to_ascii_lowercase();
+
+                    match name.as_str() {
+                        "url" | "item" | "entry" | "sitemap" => {
+                            self.field = None;
+
+                            if let Some(item) = self.item.take().and_then(PartialItem::finish) {
+                                return Ok(Some(item));
+                            }
+                        }
+                        _ => self.field = None,
+                    }
+                }
+                Event::Eof => return Ok(None),
+                _ => {}
+            }
+        }
+    } // fn next_item(&mut self) -> Result<Option<SitemapItem>, anyhow::Error>
+} // impl<'a> XmlSitemap<'a>
+
+enum Format<'a> {
+    Xml(Box<XmlSitemap<'a>>),
+    Text(Box<dyn BufRead + 'a>),
+} // enum Format<'a>
+
+/// Streaming parser for XML sitemaps, sitemap indexes, RSS/Atom feeds and
+/// plain-text sitemaps. Gzip-compressed input is detected and decompressed.
+///
+/// Items are yielded as they are read, so large sitemaps are never kept in memory.
+pub struct SitemapParser<'a> {
+    format: Format<'a>,
+    params: SitemapParams,
+    num_items: usize,
+    done: bool,
+} // pub struct SitemapParser<'a>
+
+impl<'a> SitemapParser<'a> {
+    pub fn new<R: Read + 'a>(reader: R) -> Result<Self, anyhow::Error> {
+        Self::with_params(reader, SitemapParams::default())
+    } // pub fn new<R: Read + 'a>(reader: R) -> Result<Self, anyhow::Error>
+
+    pub fn with_params<R: Read + 'a>(
+        reader: R,
+        params: SitemapParams,
+    ) -> Result<Self, anyhow::Error> {
+        let mut reader = BufReader::new(reader);
+
+        let decoded: Box<dyn Read + 'a> = if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Box::new(GzDecoder::new(reader))
+        } else {
+            Box::new(reader)
+        };
+
+        let mut reader: Box<dyn BufRead + 'a> = Box::new(BufReader::new(LimitedReader {
+            inner: decoded,
+            remaining: params.max_bytes,
+        }));
+
+        let head = reader.fill_buf()?;
+        let head = head.strip_prefix(&UTF8_BOM).unwrap_or(head);
+        let is_xml = head
+            .iter()
+            .find(|b| !b.is_ascii_whitespace())
+            .map(|b| *b == b'<')
+            .unwrap_or(false);
+
+        let format = if is_xml {
+            Format::Xml(Box::new(XmlSitemap::new(reader)))
+        } else {
+            Format::Text(reader)
+        };
+
+        Ok(Self {
+            format,
+            params,
+            num_items: 0,
+            done: false,
+        })
+    } // pub fn with_params
+} // impl<'a> SitemapParser<'a>
+
+fn next_text_item(reader: &mut dyn BufRead) -> Result<Option<SitemapItem>, anyhow::Error> {
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_start_matches('\u{feff}').trim();
+
+        if let Ok(loc) = Url::parse(line) {
+            return Ok(Some(SitemapItem::Url(SitemapEntry::new(loc))));
+        }
+    }
+} // fn next_text_item(reader: &mut dyn BufRead) -> Result<Option<SitemapItem>, anyhow::Error>
+
+impl Iterator for SitemapParser<'_> {
+    type Item = Result<SitemapItem, anyhow::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let item = match &mut self.format {
+            Format::Xml(xml) => xml.next_item(),
+            Format::Text(reader) => next_text_item(reader.as_mut()),
+        };
+
+        match item {
+            Ok(Some(item)) => {
+                self.num_items += 1;
+
+                if self.num_items > self.params.max_urls {
+                    self.done = true;
+                    return Some(Err(anyhow::anyhow!(
+                        "Sitemap contains more than {} urls",
+                        self.params.max_urls
+                    )));
+                }
+
+                Some(Ok(item))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    } // fn next(&mut self) -> Option<Self::Item>
+} // impl Iterator for SitemapParser<'_>
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use chrono::TimeZone;
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    fn parse(input: &[u8]) -> Vec<SitemapItem> {
+        SitemapParser::new(input)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    const URLSET: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/</loc>
+    <lastmod>2024-01-02</lastmod>
+    <changefreq>daily</changefreq>
+    <priority>0.8</priority>
+  </url>
+  <url>
+    <loc>https://example.com/a?b=1&amp;c=2</loc>
+  </url>
+  <url>
+    <loc>not a url</loc>
+  </url>
+</urlset>"#;
+
+    #[test]
+    fn urlset() {
+        let items = parse(URLSET.as_bytes());
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0],
+            SitemapItem::Url(SitemapEntry {
+                loc: Url::parse("https://example.com/").unwrap(),
+                lastmod: Some(Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap()),
+                changefreq: Some(ChangeFreq::Daily),
+                priority: Some(0.8),
+            })
+        );
+        assert_eq!(
+            items[1].entry().loc.as_str(),
+            "https://example.com/a?b=1&c=2"
+        );
+    }
+
+    #[test]
+    fn sitemap_index() {
+        let items = parse(
+            br#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap>
+    <loc>https://example.com/sitemap1.xml.gz</loc>
+    <lastmod>2004-10-01T18:23:17+00:00</lastmod>
+  </sitemap>
+</sitemapindex>"#,
+        );
+
+        assert_eq!(items.len(), 1);
+        assert!(matches!(items[0], SitemapItem::Sitemap(_)));
+        assert_eq!(
+            items[0].entry().lastmod,
+            Some(Utc.with_ymd_and_hms(2004, 10, 1, 18, 23, 17).unwrap())
+        );
+    }
+
+    #[test]
+    fn gzip() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(URLSET.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(parse(&compressed), parse(URLSET.as_bytes()));
+    }
+
+    #[test]
+    fn plain_text() {
+        let items = parse(b"https://example.com/a\n\nhttps://example.com/b\r\n");
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].entry().loc.as_str(), "https://example.com/b");
+    }
+
+    #[test]
+    fn feeds() {
+        let rss = parse(
+            br#"<rss version="2.0"><channel>
+  <link>https://example.com/</link>
+  <item>
+    <title>A</title>
+    <link>https://example.com/a</link>
+    <pubDate>Tue, 10 Jun 2003 04:00:00 GMT</pubDate>
+  </item>
+</channel></rss>"#,
+        );
+
+        assert_eq!(rss.len(), 1);
+        assert_eq!(rss[0].entry().loc.as_str(), "https://example.com/a");
+        assert_eq!(
+            rss[0].entry().lastmod,
+            Some(Utc.with_ymd_and_hms(2003, 6, 10, 4, 0, 0).unwrap())
+        );
+
+        let atom = parse(
+            br#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <entry>
+    <link rel="edit" href="https://example.com/edit"/>
+    <link href="https://example.com/b"/>
+    <updated>2003-12-13T18:30:02Z</updated>
+  </entry>
+</feed>"#,
+        );
+
+        assert_eq!(atom.len(), 1);
+        assert_eq!(atom[0].entry().loc.as_str(), "https://example.com/b");
+    }
+
+    #[test]
+    fn hreflang_alternates() {
+        let items = parse(
+            br#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+        xmlns:xhtml="http://www.w3.org/1999/xhtml">
+  <url>
+    <xhtml:link rel="alternate" hreflang="de" href="https://ex.com/de/"/>
+    <loc>https://ex.com/</loc>
+    <xhtml:link rel="alternate" hreflang="fr" href="https://ex.com/fr/"/>
+  </url>
+</urlset>"#,
+        );
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].entry().loc.as_str(), "https://ex.com/");
+    }
+
+    #[test]
+    fn limits() {
+        let params = SitemapParams {
+            max_urls: 1,
+            ..Default::default()
+        };
+        let res: Vec<_> = SitemapParser::with_params(URLSET.as_bytes(), params)
+            .unwrap()
+            .collect();
+        assert_eq!(res.len(), 2);
+        assert!(res[1].is_err());
+
+        let params = SitemapParams {
+            max_bytes: 100,
+            ..Default::default()
+        };
+        assert!(SitemapParser::with_params(URLSET.as_bytes(), params)
+            .unwrap()
+            .any(|item| item.is_err()));
+    }
+}
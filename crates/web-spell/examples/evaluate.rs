@@ -0,0 +1,43 @@
+/**
+ * @file evaluate.rs
+ * @author Krisna Pranav
+ * @brief evaluate
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+// Evaluate a trained model on a dataset and print the best configs of the default grid:
+// `cargo run --release -p web-spell --example evaluate -- <model dir> <dataset tsv>`
+use web_spell::{
+    eval::{self, Grid},
+    CorrectionConfig, SpellChecker,
+};
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+
+    let (Some(model), Some(dataset)) = (args.next(), args.next()) else {
+        anyhow::bail!("usage: evaluate <model dir> <dataset tsv>");
+    };
+
+    let mut checker = SpellChecker::open(model, CorrectionConfig::default())?;
+    let examples = eval::open_dataset(dataset)?;
+
+    for result in eval::grid_search(&mut checker, &examples, &Grid::default())
+        .iter()
+        .take(10)
+    {
+        println!(
+            "{} precision={:.3} recall={:.3} f1={:.3} false_correction_rate={:.3}",
+            serde_json::to_string(&result.config)?,
+            result.metrics.precision(),
+            result.metrics.recall(),
+            result.metrics.f1(),
+            result.metrics.false_correction_rate(),
+        );
+    }
+
+    Ok(())
+}
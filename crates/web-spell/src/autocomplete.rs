@@ -0,0 +1,279 @@
+/**
+ * @file autocomplete.rs
+ * @author Krisna Pranav
+ * @brief autocomplete
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::{collections::HashMap, path::Path};
+
+use crate::stupid_backoff::LeftToRight;
+use crate::trainer::model_dir;
+
+use super::{Result, StupidBackoff, TermDict, Tokenizer};
+
+/// Maximum number of terms from the dictionary that are scored by the language model.
+const MAX_CANDIDATES: usize = 100;
+
+const DEFAULT_MAX_EDIT_DISTANCE: u32 = 1;
+const DEFAULT_FUZZY_PENALTY: f64 = 8.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    /// The full completed query.
+    pub text: String,
+
+    /// Log-probability of the completed word given the preceding words,
+    /// minus a penalty for fuzzy matches. Higher is better.
+    pub score: f64,
+
+    /// Whether the typed prefix had to be corrected to find the completion.
+    pub fuzzy: bool,
+}
+
+/// Completes queries using the term dictionary and language model of a trained
+/// language. A partially typed last word is completed by a (fuzzy) prefix search in the
+/// term dictionary, otherwise the next word is predicted from the preceding words.
+pub struct Autocomplete {
+    term_dict: TermDict,
+    language_model: StupidBackoff,
+    tokenizer: Tokenizer,
+
+    max_edit_distance: u32,
+    fuzzy_penalty: f64,
+}
+
+impl Autocomplete {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = model_dir(path)?;
+        let term_dict = TermDict::open(path.join("term_dict"))?;
+        let language_model = StupidBackoff::open(path.join("stupid_backoff"))?;
+
+        Ok(Self {
+            term_dict,
+            language_model,
+            tokenizer: Tokenizer::default(),
+            max_edit_distance: DEFAULT_MAX_EDIT_DISTANCE,
+            fuzzy_penalty: DEFAULT_FUZZY_PENALTY,
+        })
+    }
+
+    pub fn with_tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Maximum edit distance between the typed prefix and the prefix of a fuzzy completion.
+    /// Set to 0 to disable fuzzy matching.
+    pub fn with_max_edit_distance(mut self, max_edit_distance: u32) -> Self {
+        self.max_edit_distance = max_edit_distance;
+        self
+    }
+
+    /// Penalty in log2 units subtracted from the score of fuzzy completions.
+    pub fn with_fuzzy_penalty(mut self, fuzzy_penalty: f64) -> Self {
+        self.fuzzy_penalty = fuzzy_penalty;
+        self
+    }
+
+    /// The `k` best completions of `query`, best first.
+    pub fn complete(&self, query: &str, k: usize) -> Vec<Completion> {
+        let tokens = self.tokenizer.tokenize(query);
+
+        let last = match tokens.last() {
+            Some(last) => last,
+            None => return Vec::new(),
+        };
+
+        let terms: Vec<String> = tokens.iter().map(|token| token.text.clone()).collect();
+
+        let mut completions = if last.span.end == query.len() {
+            let context = &terms[..terms.len() - 1];
+            let prefix = &query[..last.span.start];
+
+            self.complete_word(&last.text, context)
+                .into_iter()
+                .map(|(word, score, fuzzy)| Completion {
+                    text: format!("{prefix}{word}"),
+                    score,
+                    fuzzy,
+                })
+                .collect::<Vec<_>>()
+        } else {
+            let separator = if query.ends_with(char::is_whitespace) {
+                ""
+            } else {
+                " "
+            };
+
+            self.next_words(&terms)
+                .into_iter()
+                .map(|(word, score)| Completion {
+                    text: format!("{query}{separator}{word}"),
+                    score,
+                    fuzzy: false,
+                })
+                .collect()
+        };
+
+        completions.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.text.cmp(&b.text)));
+        completions.truncate(k);
+
+        completions
+    }
+
+    /// Complete the partial word `prefix`. Returns the words with their scores and
+    /// whether they are fuzzy matches.
+    fn complete_word(&self, prefix: &str, context: &[String]) -> Vec<(String, f64, bool)> {
+        let mut candidates: HashMap<String, (u64, bool)> = self
+            .term_dict
+            .prefix_search(prefix, 0, MAX_CANDIDATES)
+            .into_iter()
+            .map(|(term, freq)| (term, (freq, false)))
+            .collect();
+
+        if self.max_edit_distance > 0 && prefix.chars().count() > 2 {
+            for (term, freq) in
+                self.term_dict
+                    .prefix_search(prefix, self.max_edit_distance, MAX_CANDIDATES)
+            {
+                candidates.entry(term).or_insert((freq, true));
+            }
+        }
+
+        let mut candidates: Vec<_> = candidates.into_iter().collect();
+        candidates.sort_by(|(a_term, (a, _)), (b_term, (b, _))| {
+            b.cmp(a).then_with(|| a_term.cmp(b_term))
+        });
+        candidates.truncate(MAX_CANDIDATES);
+
+        candidates
+            .into_iter()
+            .map(|(word, (_, fuzzy))| {
+                let mut score = self.lm_log_prob(context, &word);
+
+                if fuzzy {
+                    score -= self.fuzzy_penalty;
+                }
+
+                (word, score, fuzzy)
+            })
+            .collect()
+    }
+
+    /// Predict the word following `context`, backing off to shorter contexts.
+    fn next_words(&self, context: &[String]) -> Vec<(String, f64)> {
+        let mut candidates: HashMap<String, u64> = HashMap::new();
+
+        for start in 0..context.len() {
+            for (word, freq) in self
+                .language_model
+                .next_words(&context[start..], MAX_CANDIDATES)
+            {
+                let entry = candidates.entry(word).or_default();
+                *entry = (*entry).max(freq);
+            }
+        }
+
+        let mut candidates: Vec<_> = candidates.into_iter().collect();
+        candidates.sort_by(|(a_word, a), (b_word, b)| b.cmp(a).then_with(|| a_word.cmp(b_word)));
+        candidates.truncate(MAX_CANDIDATES);
+
+        candidates
+            .into_iter()
+            .map(|(word, _)| {
+                let score = self.lm_log_prob(context, &word);
+                (word, score)
+            })
+            .collect()
+    }
+
+    fn lm_log_prob(&self, context: &[String], word: &str) -> f64 {
+        let mut words: Vec<String> = context[context.len().saturating_sub(2)..].to_vec();
+        words.push(word.to_string());
+
+        self.language_model.log_prob(&words, LeftToRight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{train_lang, ENGLISH};
+    use crate::Lang;
+
+    fn texts(completions: &[Completion]) -> Vec<&str> {
+        completions.iter().map(|c| c.text.as_str()).collect()
+    }
+
+    #[test]
+    fn complete_partial_word() {
+        let dir = file_store::gen_temp_dir().unwrap();
+        train_lang(&dir, Lang::Eng, ENGLISH);
+        let autocomplete = Autocomplete::open(dir.as_ref().join("eng")).unwrap();
+
+        let completions = autocomplete.complete("The weat", 3);
+        assert_eq!(texts(&completions), vec!["The weather"]);
+        assert!(!completions[0].fuzzy);
+
+        let completions = autocomplete.complete("the wewth", 3);
+        assert_eq!(texts(&completions), vec!["the weather"]);
+        assert!(completions[0].fuzzy);
+
+        let completions = autocomplete
+            .with_max_edit_distance(0)
+            .complete("the wewth", 3);
+        assert!(completions.is_empty());
+    }
+
+    #[test]
+    fn predict_next_word() {
+        let dir = file_store::gen_temp_dir().unwrap();
+        train_lang(&dir, Lang::Eng, ENGLISH);
+        let autocomplete = Autocomplete::open(dir.as_ref().join("eng")).unwrap();
+
+        let completions = autocomplete.complete("the sun is ", 3);
+        assert_eq!(completions[0].text, "the sun is shining");
+
+        let completions = autocomplete.complete("the quick", 1);
+        assert_eq!(texts(&completions), vec!["the quick"]);
+
+        let completions = autocomplete.complete("the fox,", 5);
+        assert!(texts(&completions).contains(&"the fox, is"));
+        assert!(texts(&completions).contains(&"the fox, jumps"));
+
+        for pair in completions.windows(2) {
+            assert!(pair[0].score >= pair[1].score);
+        }
+
+        assert!(autocomplete.complete("", 3).is_empty());
+    }
+
+    #[test]
+    fn bounded_candidates() {
+        let dir = file_store::gen_temp_dir().unwrap();
+        train_lang(&dir, Lang::Eng, ENGLISH);
+        let eng = dir.as_ref().join("eng");
+
+        let term_dict = TermDict::open(eng.join("term_dict")).unwrap();
+        let terms = term_dict.prefix_search("t", 0, 2);
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].0, "the");
+        assert!(terms[0].1 >= terms[1].1);
+
+        let language_model = StupidBackoff::open(eng.join("stupid_backoff")).unwrap();
+        let words = language_model.next_words(&[], 3);
+        assert_eq!(words.len(), 3);
+        assert_eq!(words[0].0, "the");
+
+        let empty = dir.as_ref().join("empty");
+        crate::stupid_backoff::StupidBackoffTrainer::new(0)
+            .build(&empty)
+            .unwrap();
+        let empty = StupidBackoff::open(&empty).unwrap();
+        assert!(empty.next_words(&["the".to_string()], 3).is_empty());
+    }
+}
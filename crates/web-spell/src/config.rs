@@ -21,6 +21,14 @@ fn lm_prob_weight() -> f64 {
     5.77
 }
 
+fn beam_width() -> usize {
+    5
+}
+
+fn lang_confidence_threshold() -> f64 {
+    0.5
+}
+
 #[derive(Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
 pub struct CorrectionConfig {
     #[serde(default = "misspelled_prob")]
@@ -31,6 +39,19 @@ pub struct CorrectionConfig {
 
     #[serde(default = "correction_threshold")]
     pub correction_threshold: f64,
+
+    /// Number of partial corrections kept at every position during the beam search.
+    #[serde(default = "beam_width")]
+    pub beam_width: usize,
+
+    /// Minimum confidence of the language detection before its result is trusted
+    /// by `SpellChecker::correct_auto`.
+    #[serde(default = "lang_confidence_threshold")]
+    pub lang_confidence_threshold: f64,
+
+    /// Fold diacritics when tokenizing. Must match the setting used during training.
+    #[serde(default)]
+    pub fold_diacritics: bool,
 }
 
 impl Default for CorrectionConfig {
@@ -39,6 +60,9 @@ impl Default for CorrectionConfig {
             misspelled_prob: misspelled_prob(),
             lm_prob_weight: lm_prob_weight(),
             correction_threshold: correction_threshold(),
+            beam_width: beam_width(),
+            lang_confidence_threshold: lang_confidence_threshold(),
+            fold_diacritics: false,
         }
     }
 }
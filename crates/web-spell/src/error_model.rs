@@ -33,6 +33,10 @@ pub enum ErrorType {
     Deletion(char),
     Substitution(char, char),
     Transposition(char, char),
+    /// A space is inserted, splitting a word in two.
+    SpaceInsertion,
+    /// A space is deleted, merging two words.
+    SpaceDeletion,
 }
 
 #[derive(
@@ -116,6 +120,15 @@ pub fn possible_errors(a: &str, b: &str) -> Option<ErrorSequence> {
         j -= 1;
     }
 
+    let errors: Vec<_> = errors
+        .into_iter()
+        .map(|error| match error {
+            ErrorType::Insertion(' ') => ErrorType::SpaceInsertion,
+            ErrorType::Deletion(' ') => ErrorType::SpaceDeletion,
+            error => error,
+        })
+        .collect();
+
     if !errors.is_empty() {
         Some(ErrorSequence(errors))
     } else {
@@ -210,6 +223,20 @@ impl ErrorModel {
         }
     }
 
+    /// Undo a previous [`ErrorModel::add`] of the same pair.
+    pub fn remove(&mut self, a: &str, b: &str) {
+        if let Some(errors) = possible_errors(a, b) {
+            if let Some(count) = self.errors.get_mut(&errors) {
+                *count -= 1;
+                self.total -= 1;
+
+                if *count == 0 {
+                    self.errors.remove(&errors);
+                }
+            }
+        }
+    }
+
     pub fn prob(&self, error: &ErrorSequence) -> f64 {
         let count = self.errors.get(error).unwrap_or(&0);
         *count as f64 / self.total as f64
@@ -222,3 +249,24 @@ impl ErrorModel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn space_errors() {
+        assert_eq!(
+            possible_errors("newyork", "new york"),
+            Some(ErrorSequence(vec![ErrorType::SpaceInsertion]))
+        );
+        assert_eq!(
+            possible_errors("face book", "facebook"),
+            Some(ErrorSequence(vec![ErrorType::SpaceDeletion]))
+        );
+        assert_eq!(
+            possible_errors("helo", "hello"),
+            Some(ErrorSequence(vec![ErrorType::Insertion('l')]))
+        );
+    }
+}
@@ -0,0 +1,289 @@
+/**
+ * @file eval.rs
+ * @author Krisna Pranav
+ * @brief eval
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+};
+
+use super::{tokenize, CorrectionConfig, Error, Lang, Result, SpellChecker};
+
+/// A labelled query. `expected` equals `query` if the query is spelled correctly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub query: String,
+    pub expected: String,
+    pub lang: Lang,
+}
+
+impl Example {
+    fn is_misspelled(&self) -> bool {
+        !same_terms(&self.query, &self.expected)
+    }
+}
+
+/// Read a dataset of tab separated `query`, `expected correction` and `lang` lines,
+/// where `lang` is an ISO 639-3 code like `eng`. Empty lines and lines starting
+/// with `#` are skipped.
+pub fn read_dataset<R: BufRead>(reader: R) -> Result<Vec<Example>> {
+    let mut examples = Vec::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_num = i + 1;
+
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<_> = line.split('\t').map(str::trim).collect();
+
+        if fields.len() != 3 {
+            return Err(Error::InvalidDataset(
+                line_num,
+                format!("expected 3 columns, found {}", fields.len()),
+            ));
+        }
+
+        let lang = Lang::from_str(fields[2]).map_err(|_| {
+            Error::InvalidDataset(line_num, format!("unknown language {:?}", fields[2]))
+        })?;
+
+        examples.push(Example {
+            query: fields[0].to_string(),
+            expected: fields[1].to_string(),
+            lang,
+        });
+    }
+
+    Ok(examples)
+}
+
+pub fn open_dataset<P: AsRef<Path>>(path: P) -> Result<Vec<Example>> {
+    read_dataset(BufReader::new(File::open(path)?))
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct Metrics {
+    pub examples: usize,
+
+    /// Examples where the expected correction differs from the query.
+    pub misspelled: usize,
+
+    /// Examples where the spell checker proposed a correction.
+    pub corrected: usize,
+
+    /// Corrections that match the expected correction.
+    pub true_corrections: usize,
+
+    /// Corrections proposed for queries that were spelled correctly.
+    pub false_corrections: usize,
+}
+
+impl Metrics {
+    /// Fraction of the proposed corrections that were right.
+    pub fn precision(&self) -> f64 {
+        ratio(self.true_corrections, self.corrected)
+    }
+
+    /// Fraction of the misspelled queries that were corrected right.
+    pub fn recall(&self) -> f64 {
+        ratio(self.true_corrections, self.misspelled)
+    }
+
+    pub fn f1(&self) -> f64 {
+        let (precision, recall) = (self.precision(), self.recall());
+
+        if precision + recall == 0.0 {
+            0.0
+        } else {
+            2.0 * precision * recall / (precision + recall)
+        }
+    }
+
+    /// Fraction of the correctly spelled queries that were changed anyway.
+    pub fn false_correction_rate(&self) -> f64 {
+        ratio(self.false_corrections, self.examples - self.misspelled)
+    }
+}
+
+/// Run the spell checker on every example. Queries and corrections are compared
+/// by their terms, so differences in case and punctuation are ignored.
+pub fn evaluate(checker: &SpellChecker, examples: &[Example]) -> Metrics {
+    let mut metrics = Metrics::default();
+
+    for example in examples {
+        let misspelled = example.is_misspelled();
+        let correction = checker
+            .correct(&example.query, &example.lang)
+            .filter(|correction| !correction.is_all_orig())
+            .map(String::from);
+
+        metrics.examples += 1;
+
+        if misspelled {
+            metrics.misspelled += 1;
+        }
+
+        if let Some(correction) = correction {
+            metrics.corrected += 1;
+
+            if !misspelled {
+                metrics.false_corrections += 1;
+            } else if same_terms(&correction, &example.expected) {
+                metrics.true_corrections += 1;
+            }
+
+            tracing::debug!(?example.query, ?example.expected, ?correction);
+        }
+    }
+
+    metrics
+}
+
+/// The values tried for each parameter of the [`CorrectionConfig`].
+#[derive(Debug, Clone)]
+pub struct Grid {
+    pub misspelled_prob: Vec<f64>,
+    pub lm_prob_weight: Vec<f64>,
+    pub correction_threshold: Vec<f64>,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self {
+            misspelled_prob: vec![0.01, 0.05, 0.1, 0.2],
+            lm_prob_weight: vec![1.0, 2.5, 5.77, 10.0],
+            correction_threshold: vec![0.0, 10.0, 25.0, 50.0, 100.0],
+        }
+    }
+}
+
+impl Grid {
+    fn configs(&self, base: CorrectionConfig) -> Vec<CorrectionConfig> {
+        let mut configs = Vec::new();
+
+        for &misspelled_prob in &self.misspelled_prob {
+            for &lm_prob_weight in &self.lm_prob_weight {
+                for &correction_threshold in &self.correction_threshold {
+                    configs.push(CorrectionConfig {
+                        misspelled_prob,
+                        lm_prob_weight,
+                        correction_threshold,
+                        ..base
+                    });
+                }
+            }
+        }
+
+        configs
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GridResult {
+    pub config: CorrectionConfig,
+    pub metrics: Metrics,
+}
+
+/// Evaluate every config in the grid. The results are sorted best first by F1,
+/// with ties broken by the lowest false-correction rate. The config of the spell
+/// checker is restored afterwards.
+pub fn grid_search(
+    checker: &mut SpellChecker,
+    examples: &[Example],
+    grid: &Grid,
+) -> Vec<GridResult> {
+    let base = checker.config();
+    let mut results = Vec::new();
+
+    for config in grid.configs(base) {
+        checker.set_config(config);
+
+        results.push(GridResult {
+            config,
+            metrics: evaluate(checker, examples),
+        });
+    }
+
+    checker.set_config(base);
+
+    results.sort_by(|a, b| {
+        b.metrics.f1().total_cmp(&a.metrics.f1()).then_with(|| {
+            a.metrics
+                .false_correction_rate()
+                .total_cmp(&b.metrics.false_correction_rate())
+        })
+    });
+
+    results
+}
+
+fn same_terms(a: &str, b: &str) -> bool {
+    tokenize(a) == tokenize(b)
+}
+
+fn ratio(a: usize, b: usize) -> f64 {
+    if b == 0 {
+        0.0
+    } else {
+        a as f64 / b as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dataset() {
+        let data = "# query\texpected\tlang\n\
+            the weathr\tthe weather\teng\n\
+            \n\
+            der hund\tder hund\tdeu\n";
+
+        let examples = read_dataset(data.as_bytes()).unwrap();
+
+        assert_eq!(examples.len(), 2);
+        assert_eq!(examples[0].expected, "the weather");
+        assert_eq!(examples[1].lang, Lang::Deu);
+        assert!(examples[0].is_misspelled());
+        assert!(!examples[1].is_misspelled());
+
+        assert!(matches!(
+            read_dataset("the weathr\tthe weather".as_bytes()),
+            Err(Error::InvalidDataset(1, _))
+        ));
+        assert!(matches!(
+            read_dataset("a\tb\tklingon".as_bytes()),
+            Err(Error::InvalidDataset(1, _))
+        ));
+    }
+
+    #[test]
+    fn metrics() {
+        let metrics = Metrics {
+            examples: 10,
+            misspelled: 4,
+            corrected: 4,
+            true_corrections: 3,
+            false_corrections: 1,
+        };
+
+        assert_eq!(metrics.precision(), 0.75);
+        assert_eq!(metrics.recall(), 0.75);
+        assert_eq!(metrics.f1(), 0.75);
+        assert_eq!(metrics.false_correction_rate(), 1.0 / 6.0);
+
+        assert_eq!(Metrics::default().precision(), 0.0);
+    }
+}
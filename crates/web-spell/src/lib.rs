@@ -8,27 +8,39 @@
  * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
  *
  */
+mod autocomplete;
 mod config;
 mod error_model;
+pub mod eval;
 pub mod spell_checker;
 mod stupid_backoff;
 mod term_freqs;
+mod tokenizer;
 mod trainer;
 
+#[cfg(test)]
+#[path = "../tests/test_support/mod.rs"]
+mod test_support;
+
+pub use autocomplete::{Autocomplete, Completion};
 pub use config::CorrectionConfig;
 pub use error_model::ErrorModel;
 pub use spell_checker::Lang;
 pub use spell_checker::SpellChecker;
+pub use spell_checker::{AutoCorrection, ScoredCorrection};
 pub use stupid_backoff::StupidBackoff;
 pub use term_freqs::TermDict;
+pub use tokenizer::{fold_diacritics, restore_case, Token, Tokenizer};
 pub use trainer::FirstTrainer;
 pub use trainer::FirstTrainerResult;
+pub use trainer::IncrementalTrainer;
 pub use trainer::SecondTrainer;
 
 use fst::Streamer;
-use std::ops::Range;
-
 use itertools::intersperse;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ops::Range;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -49,6 +61,9 @@ pub enum Error {
 
     #[error("Checker not found")]
     CheckerNotFound,
+
+    #[error("Invalid dataset on line {0}: {1}")]
+    InvalidDataset(usize, String),
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -66,6 +81,12 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 pub struct Correction {
     original: String,
     pub terms: Vec<CorrectionTerm>,
+
+    /// The text between the terms, like whitespace and punctuation. `separators[i]` comes
+    /// before `terms[i]` and the last separator after the last term. Corrections built
+    /// term by term have no separators, and their terms are joined by spaces.
+    #[serde(default)]
+    separators: Vec<String>,
 }
 
 #[derive(
@@ -85,17 +106,27 @@ pub enum CorrectionTerm {
 
 impl From<Correction> for String {
     fn from(correction: Correction) -> Self {
-        intersperse(
-            correction.terms.into_iter().map(|term| match term {
-                CorrectionTerm::Corrected {
-                    orig: _,
-                    correction,
-                } => correction,
-                CorrectionTerm::NotCorrected(orig) => orig,
-            }),
-            " ".to_string(),
-        )
-        .collect()
+        let terms = correction.terms.into_iter().map(|term| match term {
+            CorrectionTerm::Corrected {
+                orig: _,
+                correction,
+            } => correction,
+            CorrectionTerm::NotCorrected(orig) => orig,
+        });
+
+        if correction.separators.is_empty() {
+            return intersperse(terms, " ".to_string()).collect();
+        }
+
+        let mut separators = correction.separators.into_iter();
+        let mut res = separators.next().unwrap_or_default();
+
+        for (term, separator) in terms.zip(separators) {
+            res.push_str(&term);
+            res.push_str(&separator);
+        }
+
+        res
     }
 }
 
@@ -104,11 +135,82 @@ impl Correction {
         Self {
             original,
             terms: Vec::new(),
+            separators: Vec::new(),
         }
     }
 
     pub fn push(&mut self, term: CorrectionTerm) {
         self.terms.push(term);
+
+        if !self.separators.is_empty() {
+            self.separators.push(String::new());
+        }
+    }
+
+    /// Build a correction of `text` where `terms[i]` is the (possibly corrected)
+    /// term for `tokens[i]`. A term can contain a space if the token was split, and
+    /// an empty term means that the token was merged into the term before it.
+    /// Everything between the tokens, like whitespace and punctuation, is kept as
+    /// is in the separators and the capitalisation of corrected terms is restored
+    /// from the original text. Punctuation between merged tokens is kept after the
+    /// merged term, so `face, book` becomes `facebook,`.
+    pub fn from_tokens(text: &str, tokens: &[Token], terms: &[String]) -> Self {
+        let mut res = Self::empty(text.to_string());
+        let mut separators = Vec::new();
+        let mut last = 0;
+        let mut i = 0;
+
+        while i < tokens.len().min(terms.len()) {
+            let start = tokens[i].span.start;
+            let mut end_idx = i;
+
+            while end_idx + 1 < terms.len() && terms[end_idx + 1].is_empty() {
+                end_idx += 1;
+            }
+
+            separators.push(text[last..start].to_string());
+
+            let end = tokens[end_idx].span.end;
+            let orig = &text[start..end];
+
+            if end_idx == i && tokens[i].text == terms[i] {
+                res.push(CorrectionTerm::NotCorrected(orig.to_string()));
+            } else {
+                let mut correction = restore_case(orig, &terms[i]);
+
+                // punctuation between merged tokens is moved behind the merged word
+                for pair in tokens[i..=end_idx].windows(2) {
+                    correction.extend(
+                        text[pair[0].span.end..pair[1].span.start]
+                            .chars()
+                            .filter(|c| !c.is_whitespace()),
+                    );
+                }
+
+                res.push(CorrectionTerm::Corrected {
+                    orig: orig.to_string(),
+                    correction,
+                });
+            }
+
+            last = end;
+            i = end_idx + 1;
+        }
+
+        separators.push(text[last..].to_string());
+        res.separators = separators;
+
+        res
+    }
+
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    /// The text between the terms, see [`Correction::from_tokens`]. Empty if the
+    /// correction was built term by term.
+    pub fn separators(&self) -> &[String] {
+        &self.separators
     }
 
     pub fn is_all_orig(&self) -> bool {
@@ -167,13 +269,10 @@ pub fn sentence_ranges(text: &str) -> Vec<Range<usize>> {
 }
 
 pub fn tokenize(text: &str) -> Vec<String> {
-    text.to_lowercase()
-        .split_whitespace()
-        .filter(|s| {
-            !s.chars()
-                .any(|c| !c.is_ascii_alphanumeric() && c != '-' && c != '_')
-        })
-        .map(|s| s.to_string())
+    Tokenizer::default()
+        .tokenize(text)
+        .into_iter()
+        .map(|token| token.text)
         .collect()
 }
 
@@ -223,6 +322,47 @@ impl PartialEq for MergePointer<'_> {
 
 impl Eq for MergePointer<'_> {}
 
+/// The maximum number of entries read from an fst for a single prefix query. Prefixes
+/// matching more entries only consider the first ones in lexicographic order.
+const MAX_SCANNED: usize = 100_000;
+
+/// Keeps the `k` terms with the highest counts pushed into it, preferring the
+/// lexicographically smaller term on ties.
+struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<(u64, Reverse<String>)>>,
+}
+
+impl TopK {
+    fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    fn push(&mut self, term: String, count: u64) {
+        if self.k == 0 {
+            return;
+        }
+
+        self.heap.push(Reverse((count, Reverse(term))));
+
+        if self.heap.len() > self.k {
+            self.heap.pop();
+        }
+    }
+
+    /// The terms with their counts, highest count first.
+    fn into_sorted_vec(self) -> Vec<(String, u64)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((count, Reverse(term)))| (term, count))
+            .collect()
+    }
+}
+
 fn ceil_char_boundary(str: &str, index: usize) -> usize {
     let mut res = index;
 
@@ -9,15 +9,21 @@
  *
  */
 use super::Result;
-use std::{path::Path, str::FromStr};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
+use arc_swap::ArcSwap;
 use fnv::FnvHashMap;
 pub use whatlang::Lang;
 
 use crate::config::CorrectionConfig;
 use crate::stupid_backoff::{IntoMiddle, LeftToRight, RightToLeft};
+use crate::trainer::model_dir;
 
-use super::{error_model, Correction, CorrectionTerm, Error, ErrorModel, StupidBackoff, TermDict};
+use super::{error_model, Correction, Error, ErrorModel, StupidBackoff, TermDict, Tokenizer};
 
 struct LangSpellChecker {
     term_dict: TermDict,
@@ -25,21 +31,53 @@ struct LangSpellChecker {
     language_model: StupidBackoff,
 
     error_model: ErrorModel,
+}
 
-    config: CorrectionConfig,
+struct ScoredCandidate {
+    term: String,
+    score: f64,
+    error_log_prob: f64,
+}
+
+struct Hypothesis {
+    terms: Vec<String>,
+    lm_log_prob: f64,
+    error_log_prob: f64,
+    changed: bool,
+}
+
+impl Hypothesis {
+    fn score(&self, config: &CorrectionConfig) -> f64 {
+        config.lm_prob_weight * self.lm_log_prob + self.error_log_prob
+    }
+}
+
+/// The individual words of the terms, where split terms count as several words
+/// and merged away terms are skipped.
+fn words(terms: &[String]) -> Vec<String> {
+    terms
+        .iter()
+        .flat_map(|term| term.split(' '))
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn tokenizer(config: &CorrectionConfig) -> Tokenizer {
+    Tokenizer::new().with_diacritic_folding(config.fold_diacritics)
 }
 
 impl LangSpellChecker {
-    fn open<P: AsRef<Path>>(path: P, config: CorrectionConfig) -> Result<Self> {
-        let term_dict = TermDict::open(path.as_ref().join("term_dict"))?;
-        let language_model = StupidBackoff::open(path.as_ref().join("stupid_backoff"))?;
-        let error_model = ErrorModel::open(path.as_ref().join("error_model.json"))?;
+    fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = model_dir(path)?;
+        let term_dict = TermDict::open(path.join("term_dict"))?;
+        let language_model = StupidBackoff::open(path.join("stupid_backoff"))?;
+        let error_model = ErrorModel::open(path.join("error_model.json"))?;
 
         Ok(Self {
             term_dict,
             language_model,
             error_model,
-            config,
         })
     }
 
@@ -74,8 +112,9 @@ impl LangSpellChecker {
         candidates: &[String],
         context: Vec<String>,
         term_idx: usize,
-    ) -> Option<(String, f64)> {
-        let mut best_term: Option<(String, f64)> = None;
+        config: &CorrectionConfig,
+    ) -> Vec<ScoredCandidate> {
+        let mut scored = Vec::with_capacity(candidates.len());
         let mut context = context;
 
         for candidate in candidates {
@@ -87,106 +126,308 @@ impl LangSpellChecker {
 
             let log_prob = self.lm_logprob(term_idx, &context);
 
-            let scaled_lm_log_prob = self.config.lm_prob_weight * log_prob;
+            let scaled_lm_log_prob = config.lm_prob_weight * log_prob;
 
-            let error_log_prob = if candidate.as_str() != term {
-                match error_model::possible_errors(term, candidate) {
-                    Some(error_seq) => {
-                        (1.0 - self.config.misspelled_prob).log2()
-                            + self.error_model.log_prob(&error_seq)
-                    }
+            let error_seq = error_model::possible_errors(term, candidate);
+            let error_log_prob = error_seq
+                .as_ref()
+                .map(|error_seq| self.error_model.log_prob(error_seq))
+                .unwrap_or_default();
+
+            let local_error_log_prob = if candidate.as_str() != term {
+                match error_seq {
+                    Some(_) => (1.0 - config.misspelled_prob).log2() + error_log_prob,
                     None => 0.0,
                 }
             } else {
-                self.config.misspelled_prob.log2()
+                config.misspelled_prob.log2()
             };
-            tracing::trace!(?candidate, ?scaled_lm_log_prob, ?error_log_prob);
+            tracing::trace!(?candidate, ?scaled_lm_log_prob, ?local_error_log_prob);
+
+            scored.push(ScoredCandidate {
+                term: candidate.clone(),
+                score: scaled_lm_log_prob + local_error_log_prob,
+                error_log_prob,
+            });
+        }
 
-            let score = scaled_lm_log_prob + error_log_prob;
+        scored
+    }
+
+    /// Candidates that split the term at `terms[i]` into two known words, or merge it
+    /// with the next term into a known word. Returns the number of terms replaced
+    /// along with the replacement, where split words are separated by a space.
+    fn space_candidates(&self, terms: &[String], i: usize) -> Vec<(usize, String)> {
+        let term = &terms[i];
+        let mut candidates = Vec::new();
 
-            if best_term.is_none() || score > best_term.as_ref().unwrap().1 {
-                best_term = Some((candidate.clone(), score));
+        for (split, _) in term.char_indices().skip(1) {
+            let words = vec![term[..split].to_string(), term[split..].to_string()];
+
+            if words.iter().all(|word| self.term_dict.freq(word).is_some())
+                && self.language_model.freq(&words).is_some()
+            {
+                candidates.push((1, words.join(" ")));
             }
         }
 
-        best_term
-    }
+        if let Some(next) = terms.get(i + 1) {
+            let merged = format!("{term}{next}");
+
+            if self.term_dict.freq(&merged).is_some() {
+                candidates.push((2, merged));
+            }
+        }
 
-    fn correct_once(&self, text: &str) -> Option<Correction> {
-        let orig_terms = super::tokenize(text);
-        let mut terms = orig_terms.clone();
+        candidates
+    }
 
-        let mut corrections = Vec::new();
+    /// Log-probability of the full query under the language model, scored left to right.
+    fn query_lm_log_prob(&self, terms: &[String]) -> f64 {
+        self.window_lm_log_prob(&words(terms), 0)
+    }
 
-        let num_terms = terms.len();
-        for i in 0..num_terms {
-            let term = &terms[i];
-            let candidates = self.candidates(term);
+    /// Sum of the left to right log-probabilities of `words[from..]`.
+    fn window_lm_log_prob(&self, words: &[String], from: usize) -> f64 {
+        (from..words.len())
+            .map(|i| {
+                let context = &words[i.saturating_sub(2)..=i];
+                self.language_model.log_prob(context, LeftToRight)
+            })
+            .sum()
+    }
 
-            if candidates.is_empty() {
-                tracing::debug!("no candidates for {}", term);
+    /// Beam search over the positions of the query. At every position each hypothesis
+    /// is extended with the candidates whose local score beats the current term by more
+    /// than `correction_threshold`, and only the `beam_width` best hypotheses are kept.
+    ///
+    /// The terms of a hypothesis stay aligned with the original terms. A split term
+    /// contains a space and a term that was merged into the previous one is empty.
+    fn beam_search(
+        &self,
+        terms: &[String],
+        k: usize,
+        config: &CorrectionConfig,
+    ) -> Vec<Hypothesis> {
+        let beam_width = config.beam_width.max(k).max(1);
+        let mut beam = vec![Hypothesis {
+            terms: terms.to_vec(),
+            lm_log_prob: self.query_lm_log_prob(terms),
+            error_log_prob: 0.0,
+            changed: false,
+        }];
+
+        for i in 0..terms.len() {
+            let candidates = self.candidates(&terms[i]);
+            let space_candidates = self.space_candidates(terms, i);
+
+            if candidates.is_empty() && space_candidates.is_empty() {
+                tracing::debug!("no candidates for {}", terms[i]);
                 continue;
             }
 
-            let mut context = Vec::new();
-            let mut j = i.saturating_sub(2);
-            let mut this_term_context_idx = None;
-            let limit = std::cmp::min(i + 3, terms.len());
+            let mut next_beam = Vec::new();
 
-            while j < limit {
-                context.push(terms[j].clone());
-                if i == j {
-                    this_term_context_idx = Some(context.len() - 1);
+            for hypothesis in beam {
+                if hypothesis.terms[i].is_empty() {
+                    next_beam.push(hypothesis);
+                    continue;
                 }
-                j += 1;
-            }
 
-            let this_term_context_idx = this_term_context_idx.unwrap();
-            let term_log_prob = self.lm_logprob(this_term_context_idx, &context);
-            let scaled_term_log_prob = self.config.lm_prob_weight * term_log_prob
-                + ((1.0 - self.config.misspelled_prob).log2());
-
-            tracing::debug!(?term, ?term_log_prob, ?scaled_term_log_prob);
+                let left = words(&hypothesis.terms[..i]);
+                let left = &left[left.len().saturating_sub(2)..];
+
+                if !candidates.is_empty() {
+                    let right = words(&hypothesis.terms[i + 1..]);
+                    let right = &right[..right.len().min(2)];
+
+                    let term = &hypothesis.terms[i];
+                    let context: Vec<String> = left
+                        .iter()
+                        .chain(Some(term))
+                        .chain(right)
+                        .cloned()
+                        .collect();
+                    let this_term_context_idx = left.len();
+
+                    let term_log_prob = self.lm_logprob(this_term_context_idx, &context);
+                    let scaled_term_log_prob = config.lm_prob_weight * term_log_prob
+                        + ((1.0 - config.misspelled_prob).log2());
+
+                    tracing::debug!(?term, ?term_log_prob, ?scaled_term_log_prob);
+
+                    for candidate in self.score_candidates(
+                        term,
+                        &candidates,
+                        context,
+                        this_term_context_idx,
+                        config,
+                    ) {
+                        let diff = candidate.score - scaled_term_log_prob;
+                        tracing::debug!(?candidate.term, ?candidate.score, ?diff);
+
+                        if diff.is_finite() && diff > config.correction_threshold {
+                            let mut terms = hypothesis.terms.clone();
+                            terms[i] = candidate.term;
+
+                            next_beam.push(Hypothesis {
+                                lm_log_prob: self.query_lm_log_prob(&terms),
+                                error_log_prob: hypothesis.error_log_prob
+                                    + candidate.error_log_prob,
+                                changed: true,
+                                terms,
+                            });
+                        }
+                    }
+                }
 
-            if let Some((best_term, score)) =
-                self.score_candidates(term, &candidates, context, this_term_context_idx)
-            {
-                let diff = score - scaled_term_log_prob;
-                tracing::debug!(?best_term, ?score, ?diff);
-                if diff.is_finite() && diff > self.config.correction_threshold {
-                    corrections.push((i, best_term.clone()));
-                    terms[i] = best_term;
+                for (num_terms, candidate) in &space_candidates {
+                    let replaced = &hypothesis.terms[i..i + num_terms];
+                    let right = words(&hypothesis.terms[i + num_terms..]);
+                    let right = &right[..right.len().min(2)];
+
+                    let window = |middle: Vec<String>| -> Vec<String> {
+                        left.iter()
+                            .cloned()
+                            .chain(middle)
+                            .chain(right.iter().cloned())
+                            .collect()
+                    };
+
+                    let orig_log_prob =
+                        self.window_lm_log_prob(&window(words(replaced)), left.len());
+                    let candidate_log_prob = self.window_lm_log_prob(
+                        &window(words(std::slice::from_ref(candidate))),
+                        left.len(),
+                    );
+
+                    let error_log_prob =
+                        match error_model::possible_errors(&replaced.join(" "), candidate) {
+                            Some(error_seq) => self.error_model.log_prob(&error_seq),
+                            None => 0.0,
+                        };
+
+                    let diff = config.lm_prob_weight * (candidate_log_prob - orig_log_prob)
+                        + error_log_prob;
+                    tracing::debug!(?replaced, ?candidate, ?diff);
+
+                    if diff.is_finite() && diff > config.correction_threshold {
+                        let mut terms = hypothesis.terms.clone();
+                        terms[i].clone_from(candidate);
+                        for term in &mut terms[i + 1..i + num_terms] {
+                            term.clear();
+                        }
+
+                        next_beam.push(Hypothesis {
+                            lm_log_prob: self.query_lm_log_prob(&terms),
+                            error_log_prob: hypothesis.error_log_prob + error_log_prob,
+                            changed: true,
+                            terms,
+                        });
+                    }
                 }
+
+                next_beam.push(hypothesis);
             }
-        }
 
-        if corrections.is_empty() {
-            return None;
+            next_beam.sort_by(|a, b| {
+                b.score(config)
+                    .total_cmp(&a.score(config))
+                    .then_with(|| a.terms.cmp(&b.terms))
+            });
+            next_beam.truncate(beam_width);
+
+            beam = next_beam;
         }
 
-        let mut res = Correction::empty(text.to_string());
+        beam.retain(|hypothesis| hypothesis.changed);
+        beam.truncate(k);
 
-        for (orig, possible_correction) in orig_terms.into_iter().zip(terms.into_iter()) {
-            if orig == possible_correction {
-                res.push(CorrectionTerm::NotCorrected(orig));
-            } else {
-                res.push(CorrectionTerm::Corrected {
-                    orig,
-                    correction: possible_correction,
-                });
-            }
-        }
+        beam
+    }
+
+    fn suggestions(
+        &self,
+        text: &str,
+        k: usize,
+        config: &CorrectionConfig,
+    ) -> Vec<ScoredCorrection> {
+        let tokens = tokenizer(config).tokenize(text);
+        let terms: Vec<String> = tokens.iter().map(|token| token.text.clone()).collect();
+
+        self.beam_search(&terms, k, config)
+            .into_iter()
+            .map(|hypothesis| ScoredCorrection {
+                correction: Correction::from_tokens(text, &tokens, &hypothesis.terms),
+                score: hypothesis.score(config),
+                lm_log_prob: hypothesis.lm_log_prob,
+                error_log_prob: hypothesis.error_log_prob,
+            })
+            .collect()
+    }
+
+    fn correct(&self, text: &str, config: &CorrectionConfig) -> Option<Correction> {
+        self.suggestions(text, 1, config)
+            .into_iter()
+            .next()
+            .map(|suggestion| suggestion.correction)
+    }
 
-        Some(res)
+    fn tokenize(&self, text: &str, config: &CorrectionConfig) -> Vec<String> {
+        tokenizer(config)
+            .tokenize(text)
+            .into_iter()
+            .map(|token| token.text)
+            .collect()
     }
 
-    fn correct(&self, text: &str) -> Option<Correction> {
-        self.correct_once(text.to_lowercase().as_str())
+    /// Fraction of the terms that are known by the term dictionary.
+    fn coverage(&self, terms: &[String]) -> f64 {
+        if terms.is_empty() {
+            return 0.0;
+        }
+
+        let known = terms
+            .iter()
+            .filter(|term| self.term_dict.freq(term).is_some())
+            .count();
+
+        known as f64 / terms.len() as f64
     }
 }
 
+/// The result of [`SpellChecker::correct_auto`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoCorrection {
+    /// The language whose model was used for the correction.
+    pub lang: Lang,
+
+    /// Confidence of the language detection, or `None` if the detection was
+    /// not trusted and the language was chosen among the installed models instead.
+    pub confidence: Option<f64>,
+
+    pub correction: Option<Correction>,
+}
+
+/// A full-query correction together with the scores it was ranked by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoredCorrection {
+    pub correction: Correction,
+
+    /// `lm_prob_weight * lm_log_prob + error_log_prob`, higher is better.
+    pub score: f64,
+
+    /// Log-probability of the corrected query under the language model.
+    pub lm_log_prob: f64,
+
+    /// Sum of the error model log-probabilities of the corrected terms.
+    pub error_log_prob: f64,
+}
+
 pub struct SpellChecker {
-    lang_spell_checkers: FnvHashMap<Lang, LangSpellChecker>,
+    lang_spell_checkers: ArcSwap<FnvHashMap<Lang, Arc<LangSpellChecker>>>,
+    config: CorrectionConfig,
+    path: PathBuf,
 }
 
 impl SpellChecker {
@@ -201,7 +442,7 @@ impl SpellChecker {
 
         let mut lang_spell_checkers = FnvHashMap::default();
 
-        for entry in std::fs::read_dir(path)? {
+        for entry in std::fs::read_dir(path.as_ref())? {
             let entry = entry?;
             let path = entry.path();
 
@@ -222,18 +463,409 @@ impl SpellChecker {
                 }
             };
 
-            let lang_spell_checker = LangSpellChecker::open(path, config)?;
-            lang_spell_checkers.insert(lang, lang_spell_checker);
+            let lang_spell_checker = LangSpellChecker::open(path)?;
+            lang_spell_checkers.insert(lang, Arc::new(lang_spell_checker));
         }
 
         Ok(Self {
-            lang_spell_checkers,
+            lang_spell_checkers: ArcSwap::from_pointee(lang_spell_checkers),
+            config,
+            path: path.as_ref().to_path_buf(),
         })
     }
 
+    /// Reopen the model of `lang` from disk, for instance after it has been updated
+    /// by an [`IncrementalTrainer`](crate::IncrementalTrainer). Corrections that are
+    /// running while the model is swapped finish with the old model.
+    pub fn reload(&self, lang: &Lang) -> Result<()> {
+        let checker = Arc::new(LangSpellChecker::open(self.path.join(lang.code()))?);
+
+        self.lang_spell_checkers.rcu(|checkers| {
+            let mut checkers = FnvHashMap::clone(checkers);
+            checkers.insert(*lang, Arc::clone(&checker));
+            checkers
+        });
+
+        Ok(())
+    }
+
+    pub fn config(&self) -> CorrectionConfig {
+        self.config
+    }
+
+    /// Use a new config for all languages. The config is passed to the models on every
+    /// correction, so nothing is reopened.
+    pub fn set_config(&mut self, config: CorrectionConfig) {
+        self.config = config;
+    }
+
     pub fn correct(&self, text: &str, lang: &Lang) -> Option<Correction> {
         self.lang_spell_checkers
+            .load()
+            .get(lang)
+            .and_then(|s| s.correct(text, &self.config))
+    }
+
+    /// The `k` best corrections of `text`, best first. Each suggestion differs from
+    /// the original query in at least one term.
+    pub fn suggestions(&self, text: &str, lang: &Lang, k: usize) -> Vec<ScoredCorrection> {
+        self.lang_spell_checkers
+            .load()
             .get(lang)
-            .and_then(|s| s.correct(text))
+            .map(|s| s.suggestions(text, k, &self.config))
+            .unwrap_or_default()
+    }
+
+    /// The languages that have a model installed.
+    pub fn langs(&self) -> Vec<Lang> {
+        let mut langs: Vec<_> = self.lang_spell_checkers.load().keys().copied().collect();
+        langs.sort_by_key(|lang| lang.code());
+        langs
+    }
+
+    /// Detect the language of `text` and correct it with the matching model.
+    ///
+    /// If the detection isn't confident enough, or there is no model for the detected
+    /// language, the installed model that knows most of the terms in the text is used.
+    /// Returns `None` if no models are installed.
+    pub fn correct_auto(&self, text: &str) -> Option<AutoCorrection> {
+        let checkers = self.lang_spell_checkers.load();
+        let detected = whatlang::detect(text);
+
+        if let Some(info) = &detected {
+            if info.confidence() >= self.config.lang_confidence_threshold {
+                if let Some(checker) = checkers.get(&info.lang()) {
+                    return Some(AutoCorrection {
+                        lang: info.lang(),
+                        confidence: Some(info.confidence()),
+                        correction: checker.correct(text, &self.config),
+                    });
+                }
+            }
+        }
+
+        let hint = detected.map(|info| info.lang());
+
+        let (lang, checker, _) = checkers
+            .iter()
+            .map(|(lang, checker)| {
+                (
+                    *lang,
+                    checker,
+                    checker.coverage(&checker.tokenize(text, &self.config)),
+                )
+            })
+            .max_by(|(a_lang, _, a), (b_lang, _, b)| {
+                a.total_cmp(b)
+                    .then_with(|| (Some(*a_lang) == hint).cmp(&(Some(*b_lang) == hint)))
+                    .then_with(|| b_lang.code().cmp(a_lang.code()))
+            })?;
+
+        tracing::debug!(?hint, ?lang, "falling back to installed language model");
+
+        Some(AutoCorrection {
+            lang,
+            confidence: None,
+            correction: checker.correct(text, &self.config),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{train_lang, ENGLISH, GERMAN};
+    use crate::{CorrectionTerm, FirstTrainer, IncrementalTrainer, SecondTrainer};
+
+    fn spell_checker<P: AsRef<Path>>(root: P) -> SpellChecker {
+        train_lang(root.as_ref(), Lang::Eng, ENGLISH);
+        train_lang(root.as_ref(), Lang::Deu, GERMAN);
+
+        SpellChecker::open(root, CorrectionConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn correct_known_lang() {
+        let dir = file_store::gen_temp_dir().unwrap();
+        let checker = spell_checker(&dir);
+
+        assert_eq!(checker.langs(), vec![Lang::Deu, Lang::Eng]);
+
+        let correction = checker.correct("the weathr is nice today", &Lang::Eng);
+        assert_eq!(
+            correction.map(String::from),
+            Some("the weather is nice today".to_string())
+        );
+    }
+
+    #[test]
+    fn keeps_punctuation_and_case() {
+        let dir = file_store::gen_temp_dir().unwrap();
+        let checker = spell_checker(&dir);
+
+        let correction = checker
+            .correct("The Weathr is nice today!", &Lang::Eng)
+            .unwrap();
+        assert_eq!(correction.terms.len(), 5);
+        assert_eq!(
+            correction.terms[1],
+            CorrectionTerm::Corrected {
+                orig: "Weathr".to_string(),
+                correction: "Weather".to_string(),
+            }
+        );
+        assert_eq!(correction.separators(), ["", " ", " ", " ", " ", "!"]);
+        assert_eq!(
+            String::from(correction),
+            "The Weather is nice today!".to_string()
+        );
+
+        // corrections built term by term are joined by spaces
+        let mut correction = Correction::empty("a b".to_string());
+        correction.push(CorrectionTerm::NotCorrected("a".to_string()));
+        correction.push(CorrectionTerm::NotCorrected("b".to_string()));
+        assert_eq!(String::from(correction), "a b".to_string());
+
+        let correction = checker.correct("Der Hund schläft in der Sonne.", &Lang::Deu);
+        assert_eq!(correction, None);
+
+        let correction = checker.correct("Der Hund schläft in der SONE.", &Lang::Deu);
+        assert_eq!(
+            correction.map(String::from),
+            Some("Der Hund schläft in der SONNE.".to_string())
+        );
+    }
+
+    #[test]
+    fn top_k_suggestions() {
+        let dir = file_store::gen_temp_dir().unwrap();
+        let checker = spell_checker(&dir);
+
+        let suggestions = checker.suggestions("the dag is sleepinh in the sun", &Lang::Eng, 3);
+        // both corrections on their own are also suggested
+        assert_eq!(suggestions.len(), 3);
+
+        assert_eq!(
+            String::from(suggestions[0].correction.clone()),
+            "the dog is sleeping in the sun"
+        );
+
+        for suggestion in &suggestions {
+            assert!(!suggestion.correction.is_all_orig());
+            assert!(suggestion.lm_log_prob <= 0.0);
+            assert!(suggestion.error_log_prob <= 0.0);
+        }
+
+        for pair in suggestions.windows(2) {
+            assert!(pair[0].score >= pair[1].score);
+        }
+
+        assert!(checker
+            .suggestions("the dog is sleeping in the sun", &Lang::Eng, 3)
+            .is_empty());
+    }
+
+    #[test]
+    fn split_and_merge() {
+        let dir = file_store::gen_temp_dir().unwrap();
+        train_lang(
+            &dir,
+            Lang::Eng,
+            "we live in new york and we love new york. \
+            i posted it on facebook. facebook is a website. \
+            the city of new york is big. my friends are on facebook.",
+        );
+        let checker = SpellChecker::open(&dir, CorrectionConfig::default()).unwrap();
+
+        let correction = checker.correct("We live in Newyork", &Lang::Eng);
+        assert_eq!(
+            correction.map(String::from),
+            Some("We live in New York".to_string())
+        );
+
+        let correction = checker.correct("i posted it on face book.", &Lang::Eng);
+        assert_eq!(
+            correction.clone().map(String::from),
+            Some("i posted it on facebook.".to_string())
+        );
+        assert!(correction
+            .unwrap()
+            .terms
+            .contains(&CorrectionTerm::Corrected {
+                orig: "face book".to_string(),
+                correction: "facebook".to_string(),
+            }));
+
+        // punctuation between merged tokens is kept
+        let text = "on Face, book today";
+        let tokens = Tokenizer::new().tokenize(text);
+        let terms: Vec<String> = ["on", "facebook", "", "today"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let correction = Correction::from_tokens(text, &tokens, &terms);
+        assert!(correction.terms.contains(&CorrectionTerm::Corrected {
+            orig: "Face, book".to_string(),
+            correction: "Facebook,".to_string(),
+        }));
+        assert_eq!(String::from(correction), "on Facebook, today".to_string());
+    }
+
+    #[test]
+    fn incremental_update() {
+        let dir = file_store::gen_temp_dir().unwrap();
+        let checker = spell_checker(&dir);
+
+        let query = "the zebrq is striped";
+        assert_eq!(checker.correct(query, &Lang::Eng), None);
+
+        let mut trainer = IncrementalTrainer::new(dir.as_ref().join("eng")).unwrap();
+        for _ in 0..20 {
+            trainer.add("the zebra is striped. a zebra is not a horse.");
+        }
+        trainer.commit().unwrap();
+
+        // the running checker keeps the old model until it is reloaded
+        assert_eq!(checker.correct(query, &Lang::Eng), None);
+        assert_eq!(
+            checker
+                .correct("the weathr is nice today", &Lang::Eng)
+                .map(String::from),
+            Some("the weather is nice today".to_string())
+        );
+
+        checker.reload(&Lang::Eng).unwrap();
+
+        assert_eq!(
+            checker.correct(query, &Lang::Eng).map(String::from),
+            Some("the zebra is striped".to_string())
+        );
+        assert_eq!(
+            checker
+                .correct("the weathr is nice today", &Lang::Eng)
+                .map(String::from),
+            Some("the weather is nice today".to_string())
+        );
+
+        let entries: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries.len(), 2);
+
+        // the next trainer removes the replaced model and what a crashed update left behind
+        let eng = dir.as_ref().join("eng");
+        std::fs::create_dir(eng.join("v-crashed")).unwrap();
+        std::fs::create_dir(eng.join("staging-crashed")).unwrap();
+        let current = crate::trainer::model_dir(&eng).unwrap();
+
+        drop(IncrementalTrainer::new(&eng).unwrap());
+
+        for stale in [
+            "v-crashed",
+            "staging-crashed",
+            "term_dict",
+            "error_model.json",
+        ] {
+            assert!(!eng.join(stale).exists(), "{stale} was not removed");
+        }
+        assert!(current.join("term_dict").exists());
+
+        checker.reload(&Lang::Eng).unwrap();
+        assert_eq!(
+            checker.correct(query, &Lang::Eng).map(String::from),
+            Some("the zebra is striped".to_string())
+        );
+    }
+
+    /// The total of the error model of `path` and the number of learned pairs.
+    fn error_counts(path: &Path) -> (u64, usize) {
+        let path = crate::trainer::model_dir(path).unwrap();
+
+        let error_model: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path.join("error_model.json")).unwrap())
+                .unwrap();
+        let learned: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(path.join("learned_errors.json")).unwrap(),
+        )
+        .unwrap();
+
+        let pairs = learned["pairs"]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|pairs| pairs.as_array().unwrap().len())
+            .sum();
+
+        (error_model["total"].as_u64().unwrap(), pairs)
+    }
+
+    #[test]
+    fn incremental_update_without_learned_errors() {
+        let dir = file_store::gen_temp_dir().unwrap();
+        let eng = dir.as_ref().join("eng");
+
+        // a misspelling needs enough context to be learned
+        let mut trainer = FirstTrainer::new(eng.join("first")).unwrap();
+        for _ in 0..200 {
+            trainer.add(ENGLISH);
+        }
+        for _ in 0..10 {
+            trainer.add("the weathr is nice today");
+        }
+        let first = trainer.next_training_step().unwrap();
+        SecondTrainer::new(vec![first], &eng)
+            .unwrap()
+            .train()
+            .unwrap();
+
+        let (total, pairs) = error_counts(&eng);
+        assert!(pairs > 0);
+        assert_eq!(total, pairs as u64);
+
+        // models trained before the learned errors were stored
+        std::fs::remove_file(eng.join("learned_errors.json")).unwrap();
+
+        for text in ["the weathr is nice. the weathr is bad.", "the fox is lazy."] {
+            let mut trainer = IncrementalTrainer::new(&eng).unwrap();
+            for _ in 0..20 {
+                trainer.add(text);
+            }
+            trainer.commit().unwrap();
+
+            let (total, pairs) = error_counts(&eng);
+            assert_eq!(total, pairs as u64);
+        }
+    }
+
+    #[test]
+    fn correct_auto_detects_lang() {
+        let dir = file_store::gen_temp_dir().unwrap();
+        let checker = spell_checker(&dir);
+
+        let res = checker
+            .correct_auto("we are going to the beach this weekemd with our friends")
+            .unwrap();
+
+        assert_eq!(res.lang, Lang::Eng);
+        assert!(res.confidence.is_some());
+        assert_eq!(
+            res.correction.map(String::from),
+            Some("we are going to the beach this weekend with our friends".to_string())
+        );
+    }
+
+    #[test]
+    fn correct_auto_falls_back() {
+        let dir = file_store::gen_temp_dir().unwrap();
+        let checker = spell_checker(&dir);
+
+        // too short for a confident detection
+        let res = checker.correct_auto("der hund").unwrap();
+        assert_eq!(res.lang, Lang::Deu);
+        assert_eq!(res.confidence, None);
+
+        let res = checker.correct_auto("the fox").unwrap();
+        assert_eq!(res.lang, Lang::Eng);
     }
 }
@@ -8,7 +8,7 @@
  * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
  *
  */
-use super::{tokenize, MergePointer, Result};
+use super::{MergePointer, Result, TopK, MAX_SCANNED};
 use std::{
     cmp::Reverse,
     collections::{BTreeMap, BinaryHeap},
@@ -334,6 +334,45 @@ impl StupidBackoff {
         self.log_prob(words, strat).exp2()
     }
 
+    /// The `limit` most frequent words that directly follow `context` in the training
+    /// data, with the frequency of the resulting ngram. Only the last `max_ngram_size - 1`
+    /// words of the context are used and an empty context returns the unigrams. At most
+    /// [`MAX_SCANNED`](crate::MAX_SCANNED) ngrams are read.
+    pub fn next_words(&self, context: &[String], limit: usize) -> Vec<(String, u64)> {
+        let context = &context[context
+            .len()
+            .saturating_sub(self.n_counts.len().saturating_sub(1))..];
+
+        let q = if context.is_empty() {
+            String::new()
+        } else {
+            context.join(" ") + " "
+        };
+        let automaton = fst::automaton::Str::new(&q).starts_with();
+
+        let mut stream = self.ngrams.search(automaton).into_stream();
+
+        let mut top = TopK::new(limit);
+        let mut scanned = 0;
+
+        while let Some((ngram, freq)) = stream.next() {
+            if scanned == MAX_SCANNED {
+                break;
+            }
+            scanned += 1;
+
+            if let Ok(ngram) = std::str::from_utf8(ngram) {
+                let word = &ngram[q.len()..];
+
+                if !word.is_empty() && !word.contains(' ') {
+                    top.push(word.to_string(), freq);
+                }
+            }
+        }
+
+        top.into_sorted_vec()
+    }
+
     pub fn contexts(&self, word: &str) -> Vec<(Vec<String>, u64)> {
         let q = word.to_string() + " ";
         let automaton = fst::automaton::Str::new(&q).starts_with();
@@ -344,7 +383,7 @@ impl StupidBackoff {
 
         while let Some((ngram, freq)) = stream.next() {
             if let Ok(ngram) = std::str::from_utf8(ngram) {
-                let mut ngram = tokenize(ngram);
+                let mut ngram: Vec<String> = ngram.split(' ').map(String::from).collect();
                 ngram.rotate_right(1);
                 contexts.push((ngram, freq));
             }
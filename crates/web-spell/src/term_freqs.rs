@@ -8,12 +8,12 @@
  * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
  *
  */
-use super::{MergePointer, Result};
-use fst::{IntoStreamer, Streamer};
+use super::{MergePointer, Result, TopK, MAX_SCANNED};
+use fst::{Automaton, IntoStreamer, Streamer};
 
 use std::{
     cmp::Reverse,
-    collections::{BTreeMap, BinaryHeap},
+    collections::{BTreeMap, BTreeSet, BinaryHeap},
     fs::{File, OpenOptions},
     io::BufWriter,
     path::{Path, PathBuf},
@@ -348,6 +348,43 @@ impl TermDict {
         res
     }
 
+    /// The `limit` most frequent terms starting with `prefix`, or with a prefix within
+    /// `max_edit_distance` of it, together with their frequencies. At most
+    /// [`MAX_SCANNED`](crate::MAX_SCANNED) matches are read from each dictionary.
+    pub fn prefix_search(
+        &self,
+        prefix: &str,
+        max_edit_distance: u32,
+        limit: usize,
+    ) -> Vec<(String, u64)> {
+        let mut candidates = BTreeSet::new();
+
+        for stored in self.stored.iter() {
+            let top = if max_edit_distance == 0 {
+                let automaton = fst::automaton::Str::new(prefix).starts_with();
+                top_freqs(&stored.map, automaton, limit)
+            } else if let Ok(automaton) =
+                fst::automaton::Levenshtein::new(prefix, max_edit_distance)
+            {
+                top_freqs(&stored.map, automaton.starts_with(), limit)
+            } else {
+                continue;
+            };
+
+            candidates.extend(top.into_iter().map(|(term, _)| term));
+        }
+
+        // a term can be in the top of some dictionaries only, so sum its frequency in all
+        let mut top = TopK::new(limit);
+
+        for term in candidates {
+            let freq = self.freq(&term).unwrap_or_default();
+            top.push(term, freq);
+        }
+
+        top.into_sorted_vec()
+    }
+
     pub fn merge(&mut self, other: Self) -> Result<()> {
         for stored in other.stored {
             let uuid = uuid::Uuid::new_v4();
@@ -367,3 +404,26 @@ impl TermDict {
         &self.path
     }
 }
+
+fn top_freqs<A: Automaton>(
+    map: &fst::Map<memmap2::Mmap>,
+    automaton: A,
+    limit: usize,
+) -> Vec<(String, u64)> {
+    let mut stream = map.search(automaton).into_stream();
+    let mut top = TopK::new(limit);
+    let mut scanned = 0;
+
+    while let Some((term, freq)) = stream.next() {
+        if scanned == MAX_SCANNED {
+            break;
+        }
+        scanned += 1;
+
+        if let Ok(term) = std::str::from_utf8(term) {
+            top.push(term.to_string(), freq);
+        }
+    }
+
+    top.into_sorted_vec()
+}
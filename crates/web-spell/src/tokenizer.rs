@@ -0,0 +1,206 @@
+/**
+ * @file tokenizer.rs
+ * @author Krisna Pranav
+ * @brief tokenizer
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::ops::Range;
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Characters that join two words into a single token (`state-of-the-art`).
+const JOINERS: [&str; 2] = ["-", "_"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// The normalized term.
+    pub text: String,
+
+    /// Byte range of the token in the original text.
+    pub span: Range<usize>,
+}
+
+/// Splits text into words using Unicode word boundaries and normalizes them
+/// to lowercase NFC, optionally folding diacritics (`café` -> `cafe`).
+///
+/// The same tokenizer settings must be used for training and correction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tokenizer {
+    fold_diacritics: bool,
+}
+
+impl Tokenizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_diacritic_folding(mut self, fold_diacritics: bool) -> Self {
+        self.fold_diacritics = fold_diacritics;
+        self
+    }
+
+    pub fn normalize(&self, word: &str) -> String {
+        let word: String = word.nfc().collect::<String>().to_lowercase();
+
+        if self.fold_diacritics {
+            fold_diacritics(&word)
+        } else {
+            word
+        }
+    }
+
+    pub fn tokenize(&self, text: &str) -> Vec<Token> {
+        let mut spans: Vec<Range<usize>> = Vec::new();
+        let mut pending_joiner = false;
+
+        for (start, segment) in text.split_word_bound_indices() {
+            let end = start + segment.len();
+
+            if segment.chars().any(|c| c.is_alphanumeric()) {
+                match spans.last_mut() {
+                    Some(last) if std::mem::take(&mut pending_joiner) => last.end = end,
+                    _ => spans.push(start..end),
+                }
+            } else {
+                // a joiner directly after a word glues it to the next word
+                pending_joiner = JOINERS.contains(&segment)
+                    && spans.last().map(|last| last.end == start).unwrap_or(false);
+            }
+        }
+
+        spans
+            .into_iter()
+            .map(|span| Token {
+                text: self.normalize(&text[span.clone()]),
+                span,
+            })
+            .collect()
+    }
+}
+
+/// Remove diacritics by decomposing the text and dropping all combining marks.
+pub fn fold_diacritics(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .nfc()
+        .collect()
+}
+
+/// Apply the capitalisation of `orig` to `correction`. All-caps words stay all-caps
+/// and capitalised words stay capitalised.
+///
+/// If the correction splits `orig` into several space separated words, the case
+/// is restored per word from the aligned part of `orig`, and every word is
+/// capitalised if `orig` was (`Newyork` -> `New York`).
+pub fn restore_case(orig: &str, correction: &str) -> String {
+    if !correction.contains(' ') {
+        return restore_word_case(orig, correction);
+    }
+
+    let capitalised = orig
+        .chars()
+        .find(|c| c.is_alphabetic())
+        .map(|c| c.is_uppercase())
+        .unwrap_or(false);
+
+    let mut orig = orig.chars().filter(|c| !c.is_whitespace());
+
+    correction
+        .split(' ')
+        .map(|word| {
+            let aligned: String = orig.by_ref().take(word.chars().count()).collect();
+            let word = restore_word_case(&aligned, word);
+
+            if capitalised {
+                capitalise(&word)
+            } else {
+                word
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn restore_word_case(orig: &str, correction: &str) -> String {
+    let letters: Vec<char> = orig.chars().filter(|c| c.is_alphabetic()).collect();
+
+    if letters.len() > 1 && letters.iter().all(|c| !c.is_lowercase()) {
+        return correction.to_uppercase();
+    }
+
+    match letters.first() {
+        Some(first) if first.is_uppercase() => capitalise(correction),
+        _ => correction.to_string(),
+    }
+}
+
+fn capitalise(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(tokenizer: &Tokenizer, text: &str) -> Vec<String> {
+        tokenizer
+            .tokenize(text)
+            .into_iter()
+            .map(|token| token.text)
+            .collect()
+    }
+
+    #[test]
+    fn unicode_words() {
+        let tokenizer = Tokenizer::new();
+
+        assert_eq!(
+            terms(&tokenizer, "Café, STRASSE straße! Привет мир; Γειά σου"),
+            vec!["café", "strasse", "straße", "привет", "мир", "γειά", "σου"]
+        );
+        assert_eq!(
+            terms(&tokenizer, "state-of-the-art snake_case - hello"),
+            vec!["state-of-the-art", "snake_case", "hello"]
+        );
+    }
+
+    #[test]
+    fn spans_and_normalization() {
+        // 'e' followed by a combining acute accent
+        let text = "Cafe\u{301} ok";
+        let tokens = Tokenizer::new().tokenize(text);
+
+        assert_eq!(tokens[0].text, "café");
+        assert_eq!(&text[tokens[0].span.clone()], "Cafe\u{301}");
+        assert_eq!(&text[tokens[1].span.clone()], "ok");
+
+        let folding = Tokenizer::new().with_diacritic_folding(true);
+        assert_eq!(terms(&folding, text), vec!["cafe", "ok"]);
+        assert_eq!(terms(&folding, "Ünïcödé"), vec!["unicode"]);
+    }
+
+    #[test]
+    fn case_restoration() {
+        assert_eq!(restore_case("Weathr", "weather"), "Weather");
+        assert_eq!(restore_case("WEATHR", "weather"), "WEATHER");
+        assert_eq!(restore_case("weathr", "weather"), "weather");
+        assert_eq!(restore_case("Ärger", "ärger"), "Ärger");
+        assert_eq!(restore_case("I", "i"), "I");
+
+        assert_eq!(restore_case("Newyork", "new york"), "New York");
+        assert_eq!(restore_case("NEWYORK", "new york"), "NEW YORK");
+        assert_eq!(restore_case("newYork", "new york"), "new York");
+        assert_eq!(restore_case("newyork", "new york"), "new york");
+        assert_eq!(restore_case("Face Book", "facebook"), "Facebook");
+    }
+}
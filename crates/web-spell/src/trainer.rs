@@ -13,17 +13,21 @@ use rayon::prelude::*;
 
 use crate::error_model::ErrorModel;
 
-use super::{stupid_backoff::StupidBackoffTrainer, tokenize, Result};
+use super::{stupid_backoff::StupidBackoffTrainer, Error, Result, Tokenizer};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs::File,
+    io::{BufReader, BufWriter},
     path::{Path, PathBuf},
 };
+use uuid::Uuid;
 
 use super::{stupid_backoff::StupidBackoff, term_freqs::TermDict};
 
 pub struct FirstTrainer {
     term_dict: TermDict,
     lm_model: StupidBackoffTrainer,
+    tokenizer: Tokenizer,
 
     path: PathBuf,
 }
@@ -33,12 +37,23 @@ impl FirstTrainer {
         Ok(Self {
             term_dict: TermDict::open(path.as_ref().join("term_dict"))?,
             lm_model: StupidBackoffTrainer::new(3),
+            tokenizer: Tokenizer::default(),
             path: path.as_ref().to_path_buf(),
         })
     }
 
+    pub fn with_tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
     pub fn add(&mut self, text: &str) {
-        let tokens = tokenize(text);
+        let tokens: Vec<String> = self
+            .tokenizer
+            .tokenize(text)
+            .into_iter()
+            .map(|token| token.text)
+            .collect();
         for term in &tokens {
             self.term_dict.insert(term);
         }
@@ -107,26 +122,312 @@ impl SecondTrainer {
         tracing::info!("training error model");
         let terms = self.term_dict.terms();
 
+        let learned = LearnedErrors::learn(&self.term_dict, &self.lm_model, terms);
+
+        let mut error_model = ErrorModel::new();
+
+        for (term, possible_correction) in learned.pairs() {
+            error_model.add(term, possible_correction);
+        }
+
+        error_model.save(self.path.join("error_model.json"))?;
+        learned.save(self.path.join(LEARNED_ERRORS_FILE))?;
+
+        Ok(())
+    }
+}
+
+/// Names the version directory with the current model files, for models that have been
+/// updated by an [`IncrementalTrainer`]. Models without it keep their files directly in the
+/// model directory.
+const CURRENT_FILE: &str = "CURRENT";
+const VERSION_PREFIX: &str = "v-";
+const STAGING_PREFIX: &str = "staging-";
+
+/// The files of a model directory before it was first updated incrementally.
+const UNVERSIONED_FILES: [&str; 4] = [
+    "term_dict",
+    "stupid_backoff",
+    "error_model.json",
+    LEARNED_ERRORS_FILE,
+];
+
+/// The directory with the current files of the model at `path`.
+pub(crate) fn model_dir<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = path.as_ref();
+
+    match std::fs::read_to_string(path.join(CURRENT_FILE)) {
+        Ok(version) => Ok(path.join(version.trim())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(path.to_path_buf()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Feeds new text into an existing model directory, as created by [`SecondTrainer`].
+///
+/// The new term counts and ngrams are merged with a copy of the current model in a new
+/// version directory inside the model directory, and the error model is refreshed for the
+/// terms that occur in the new text and the terms within their edit distance. The new
+/// version then replaces the current one in a single rename of the `CURRENT` file, so the
+/// model directory always holds a complete model. A running
+/// [`SpellChecker`](crate::SpellChecker) keeps using the old model until
+/// [`SpellChecker::reload`](crate::SpellChecker::reload) is called.
+///
+/// The replaced version is kept until the next trainer is created for the model, which
+/// also removes what an interrupted update left behind. Only one trainer may update a
+/// model at a time.
+pub struct IncrementalTrainer {
+    first: FirstTrainer,
+    staging: PathBuf,
+    version: PathBuf,
+    current: PathBuf,
+    path: PathBuf,
+}
+
+impl IncrementalTrainer {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let current = model_dir(&path)?;
+
+        if !current.join("term_dict").exists() || !current.join("stupid_backoff").exists() {
+            return Err(Error::CheckerNotFound);
+        }
+
+        remove_stale(&path, &current)?;
+
+        let staging = path.join(format!("{}{}", STAGING_PREFIX, Uuid::new_v4()));
+        let version = path.join(format!("{}{}", VERSION_PREFIX, Uuid::new_v4()));
+
+        Ok(Self {
+            first: FirstTrainer::new(staging.join("first"))?,
+            staging,
+            version,
+            current,
+            path,
+        })
+    }
+
+    pub fn with_tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.first = self.first.with_tokenizer(tokenizer);
+        self
+    }
+
+    pub fn add(&mut self, text: &str) {
+        self.first.add(text);
+    }
+
+    /// Merge the added text into the model and swap it into place.
+    pub fn commit(self) -> Result<()> {
+        let staging = self.staging.clone();
+        let version = self.version.clone();
+        let res = self.update();
+
+        if staging.exists() {
+            std::fs::remove_dir_all(staging)?;
+        }
+
+        if res.is_err() && version.exists() {
+            std::fs::remove_dir_all(version)?;
+        }
+
+        res
+    }
+
+    fn update(self) -> Result<()> {
+        let FirstTrainerResult {
+            term_dict: new_terms,
+            lm_model: new_lm_model,
+        } = self.first.next_training_step()?;
+
+        let changed_terms = new_terms.terms();
+        let model = &self.version;
+        std::fs::create_dir_all(model)?;
+
+        // `StupidBackoff::merge` deletes the merged models, so merge a copy of the current one
+        copy_dir(
+            self.current.join("stupid_backoff"),
+            self.staging.join("stupid_backoff"),
+        )?;
+        let lm_model = StupidBackoff::merge(
+            vec![
+                StupidBackoff::open(self.staging.join("stupid_backoff"))?,
+                new_lm_model,
+            ],
+            model.join("stupid_backoff"),
+        )?;
+
+        copy_dir(self.current.join("term_dict"), model.join("term_dict"))?;
+        let mut term_dict = TermDict::open(model.join("term_dict"))?;
+        term_dict.merge(new_terms)?;
+        term_dict.merge_dicts()?;
+        tracing::debug!("merged {} new terms", changed_terms.len());
+
+        let learned_path = self.current.join(LEARNED_ERRORS_FILE);
+
+        let (error_model, learned) = if learned_path.exists() {
+            let mut error_model = ErrorModel::open(self.current.join("error_model.json"))?;
+            let mut learned = LearnedErrors::open(learned_path)?;
+
+            let affected = affected_terms(&term_dict, changed_terms);
+
+            for term in &affected {
+                for (a, b) in learned.remove(term) {
+                    error_model.remove(&a, &b);
+                }
+            }
+
+            let relearned = LearnedErrors::learn(&term_dict, &lm_model, affected);
+            for (a, b) in relearned.pairs() {
+                error_model.add(a, b);
+            }
+            learned.extend(relearned);
+
+            (error_model, learned)
+        } else {
+            // the model was trained before the learned errors were stored, so it is unknown
+            // which pairs its error model was built from. Relearn it from all terms once.
+            tracing::info!("relearning the error model of a model without learned errors");
+            let learned = LearnedErrors::learn(&term_dict, &lm_model, term_dict.terms());
+
+            let mut error_model = ErrorModel::new();
+            for (a, b) in learned.pairs() {
+                error_model.add(a, b);
+            }
+
+            (error_model, learned)
+        };
+
+        error_model.save(model.join("error_model.json"))?;
+        learned.save(model.join(LEARNED_ERRORS_FILE))?;
+
+        drop(term_dict);
+        drop(lm_model);
+
+        let version = model
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+
+        let tmp = self.path.join(format!("{CURRENT_FILE}.tmp"));
+        std::fs::write(&tmp, version)?;
+        std::fs::rename(&tmp, self.path.join(CURRENT_FILE))?;
+
+        Ok(())
+    }
+}
+
+/// Remove the versions of the model at `path` other than `current`, and what interrupted
+/// updates left behind.
+fn remove_stale(path: &Path, current: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let entry_path = entry.path();
+
+        if entry_path == current {
+            continue;
+        }
+
+        let name = entry.file_name();
+        let name = name.to_str().unwrap_or_default();
+
+        let stale = name.starts_with(VERSION_PREFIX)
+            || name.starts_with(STAGING_PREFIX)
+            || name == format!("{CURRENT_FILE}.tmp")
+            || (current != path && UNVERSIONED_FILES.contains(&name));
+
+        if !stale {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(entry_path)?;
+        } else {
+            std::fs::remove_file(entry_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The edit distance within which a term of `len` bytes is compared to other terms.
+fn max_edit_distance(len: usize) -> u32 {
+    if len <= 4 {
+        1
+    } else if len <= 12 {
+        2
+    } else {
+        3
+    }
+}
+
+/// The terms whose learned errors can change when `changed` terms get new counts: the
+/// changed terms themselves, and the terms that are within their edit distance of one,
+/// since they may have become or stopped being a misspelling of it.
+///
+/// Space errors are only re-evaluated for these terms, so a term that is a space away
+/// from a changed bigram keeps its pairs until it occurs in new text itself.
+fn affected_terms(term_dict: &TermDict, changed: Vec<String>) -> Vec<String> {
+    let neighbours: Vec<_> = changed
+        .par_iter()
+        .flat_map_iter(|term| {
+            // a neighbour is compared within its own edit distance, which may be larger
+            // than the one of the changed term
+            let distance = (1..=3)
+                .rev()
+                .find(|d| *d <= max_edit_distance(term.len() + *d as usize))
+                .unwrap_or(1);
+
+            term_dict.search(term, distance)
+        })
+        .collect();
+
+    let mut terms: BTreeSet<String> = changed.into_iter().collect();
+    terms.extend(neighbours);
+
+    terms.into_iter().collect()
+}
+
+fn copy_dir<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
+    std::fs::create_dir_all(to.as_ref())?;
+
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.as_ref().join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir(entry.path(), target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Stored next to the error model, so the errors learned for a term can be replaced
+/// when the model is updated incrementally.
+const LEARNED_ERRORS_FILE: &str = "learned_errors.json";
+
+/// The `(misspelling, correction)` pairs each term contributed to the error model.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct LearnedErrors {
+    pairs: BTreeMap<String, Vec<(String, String)>>,
+}
+
+impl LearnedErrors {
+    fn learn(term_dict: &TermDict, lm_model: &StupidBackoff, terms: Vec<String>) -> Self {
         let errors: Vec<_> = terms
+            .clone()
             .into_par_iter()
             .progress()
             .map(|term| {
-                let max_edit_distance = if term.len() <= 4 {
-                    1
-                } else if term.len() <= 12 {
-                    2
-                } else {
-                    3
-                };
-
-                let possible_corrections = self
-                    .term_dict
-                    .search(&term, max_edit_distance)
+                let possible_corrections = term_dict
+                    .search(&term, max_edit_distance(term.len()))
                     .into_iter()
                     .filter(|correction| {
                         correction != &term
-                            && 10 * self.term_dict.freq(&term).unwrap_or_default()
-                                < self.term_dict.freq(correction).unwrap_or_default()
+                            && 10 * term_dict.freq(&term).unwrap_or_default()
+                                < term_dict.freq(correction).unwrap_or_default()
                     })
                     .collect::<Vec<_>>();
 
@@ -134,7 +435,7 @@ impl SecondTrainer {
             })
             .filter(|(_, errors)| !errors.is_empty())
             .map(|(term, possible_corrections)| {
-                let contexts = self.lm_model.contexts(&term);
+                let contexts = lm_model.contexts(&term);
 
                 let best_terms = contexts
                     .into_iter()
@@ -155,7 +456,7 @@ impl SecondTrainer {
                             .map(|t| {
                                 let mut words = context.clone();
                                 words[1].clone_from(t);
-                                (self.lm_model.freq(&words), t)
+                                (lm_model.freq(&words), t)
                             })
                             .max_by(|(a, _), (b, _)| a.cmp(b))
                             .unwrap()
@@ -176,22 +477,87 @@ impl SecondTrainer {
             })
             .collect();
 
-        let mut error_model = ErrorModel::new();
+        let mut pairs: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
 
-        for (term, possible_correction) in errors
-            .into_iter()
-            .flat_map(|(term, possible_corrections)| {
-                possible_corrections
-                    .into_keys()
-                    .map(move |other| (term.clone(), other.clone()))
-            })
-            .filter(|(a, b)| a != b)
-        {
-            error_model.add(&term, &possible_correction);
+        for (term, possible_corrections) in errors {
+            for other in possible_corrections.into_keys() {
+                if term != other {
+                    pairs
+                        .entry(term.clone())
+                        .or_default()
+                        .push((term.clone(), other));
+                }
+            }
         }
 
-        error_model.save(self.path.join("error_model.json"))?;
+        for (term, pair) in space_errors(term_dict, lm_model, terms) {
+            pairs.entry(term).or_default().push(pair);
+        }
+
+        Self { pairs }
+    }
+
+    fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer(BufWriter::new(file), self)?;
 
         Ok(())
     }
+
+    fn pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs
+            .values()
+            .flatten()
+            .map(|(a, b)| (a.as_str(), b.as_str()))
+    }
+
+    fn remove(&mut self, term: &str) -> Vec<(String, String)> {
+        self.pairs.remove(term).unwrap_or_default()
+    }
+
+    fn extend(&mut self, other: Self) {
+        for (term, pairs) in other.pairs {
+            self.pairs.entry(term).or_default().extend(pairs);
+        }
+    }
+}
+
+/// Find words that are most likely a missing or superfluous space away from
+/// a much more frequent spelling, like `newyork` for `new york` or `face book`
+/// for `facebook`. The pairs are returned together with the term they were found for.
+fn space_errors(
+    term_dict: &TermDict,
+    lm_model: &StupidBackoff,
+    terms: Vec<String>,
+) -> Vec<(String, (String, String))> {
+    tracing::info!("finding space errors");
+
+    terms
+        .into_par_iter()
+        .flat_map_iter(|term| {
+            let freq = term_dict.freq(&term).unwrap_or_default();
+
+            term.char_indices()
+                .skip(1)
+                .filter_map(|(split, _)| {
+                    let words = vec![term[..split].to_string(), term[split..].to_string()];
+                    let bigram_freq = lm_model.freq(&words)?;
+                    let words = words.join(" ");
+
+                    if 10 * freq < bigram_freq {
+                        Some((term.clone(), (term.clone(), words)))
+                    } else if 10 * bigram_freq < freq {
+                        Some((term.clone(), (words, term.clone())))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
 }
@@ -0,0 +1,75 @@
+/**
+ * @file eval.rs
+ * @author Krisna Pranav
+ * @brief eval
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+use std::path::Path;
+
+use web_spell::{
+    eval::{self, Grid},
+    CorrectionConfig, FirstTrainer, Lang, SecondTrainer, SpellChecker,
+};
+
+mod test_support;
+
+use test_support::{train_lang, ENGLISH, GERMAN};
+
+fn dataset() -> Vec<eval::Example> {
+    eval::open_dataset(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/eval.tsv")).unwrap()
+}
+
+#[test]
+fn evaluate_default_config() {
+    let dir = file_store::gen_temp_dir().unwrap();
+    train_lang(&dir, Lang::Eng, ENGLISH);
+    train_lang(&dir, Lang::Deu, GERMAN);
+
+    let checker = SpellChecker::open(&dir, CorrectionConfig::default()).unwrap();
+    let metrics = eval::evaluate(&checker, &dataset());
+
+    assert_eq!(metrics.examples, 8);
+    assert_eq!(metrics.misspelled, 4);
+    assert_eq!(metrics.false_corrections, 0);
+    assert_eq!(metrics.precision(), 1.0);
+    assert_eq!(metrics.recall(), 1.0);
+}
+
+#[test]
+fn grid_search() {
+    let dir = file_store::gen_temp_dir().unwrap();
+    train_lang(&dir, Lang::Eng, ENGLISH);
+    train_lang(&dir, Lang::Deu, GERMAN);
+
+    let mut checker = SpellChecker::open(&dir, CorrectionConfig::default()).unwrap();
+    let grid = Grid {
+        misspelled_prob: vec![0.1],
+        lm_prob_weight: vec![0.0, 5.77],
+        correction_threshold: vec![-1000.0, 50.0],
+    };
+
+    let results = eval::grid_search(&mut checker, &dataset(), &grid);
+
+    assert_eq!(results.len(), 4);
+    for pair in results.windows(2) {
+        assert!(pair[0].metrics.f1() >= pair[1].metrics.f1());
+    }
+
+    let best = &results[0];
+    assert_eq!(best.config.lm_prob_weight, 5.77);
+    assert_eq!(best.config.correction_threshold, 50.0);
+
+    // a negative threshold corrects everything it can, including correct queries
+    assert!(results
+        .iter()
+        .any(|result| result.metrics.false_corrections > 0));
+
+    assert_eq!(
+        checker.config().correction_threshold,
+        CorrectionConfig::default().correction_threshold
+    );
+}
@@ -0,0 +1,42 @@
+/**
+ * @file mod.rs
+ * @author Krisna Pranav
+ * @brief test support
+ * @version 1.0
+ * @date 2024-11-25
+ *
+ * @copyright Copyright (c) 2024 Doodle Developers, Krisna Pranav
+ *
+ */
+// Corpora and training helpers shared by the unit tests and the integration tests.
+// Both include this file as a child of a module that has the trainers and `Lang`
+// in scope.
+use std::path::Path;
+
+use super::{FirstTrainer, Lang, SecondTrainer};
+
+pub const ENGLISH: &str = "the quick brown fox jumps over the lazy dog. \
+    this is a test of the spell checker. \
+    the weather is nice today and the sun is shining. \
+    we are going to the beach this weekend with our friends. \
+    the dog is sleeping in the sun. the fox is hungry.";
+
+pub const GERMAN: &str = "der schnelle braune fuchs springt über den faulen hund. \
+    das wetter ist heute schön und die sonne scheint. \
+    wir gehen am wochenende mit unseren freunden an den strand. \
+    der hund schläft in der sonne. der fuchs ist hungrig.";
+
+pub fn train_lang<P: AsRef<Path>>(root: P, lang: Lang, text: &str) {
+    let path = root.as_ref().join(lang.code());
+
+    let mut trainer = FirstTrainer::new(path.join("first")).unwrap();
+    for _ in 0..20 {
+        trainer.add(text);
+    }
+    let first = trainer.next_training_step().unwrap();
+
+    SecondTrainer::new(vec![first], &path)
+        .unwrap()
+        .train()
+        .unwrap();
+}